@@ -0,0 +1,81 @@
+//! How long the common operations take on lists far longer than anyone
+//! keeps by hand, as long archives get. Each has a budget it should stay
+//! well under on an ordinary laptop:
+//!
+//! | operation                  | items   | budget |
+//! |----------------------------|---------|--------|
+//! | load todo.json             | 100,000 | 500 ms |
+//! | save todo.json             | 100,000 | 500 ms |
+//! | search, plain and fuzzy    | 100,000 | 100 ms |
+//! | sort by due and priority   | 100,000 | 100 ms |
+//! | list                       | 10,000  | 100 ms |
+//!
+//! ```sh
+//! cargo bench --features bench
+//! ```
+
+use chrono::{Duration, Local, TimeZone};
+use criterion::{criterion_group, criterion_main, Criterion};
+use todo::{bench, Item, JsonFileStore, Priority, TodoStore};
+
+/// `count` items with a mix of what items have: tags, people, due dates,
+/// priorities and some checked off.
+fn items(count: usize) -> Vec<Item> {
+    let start = Local.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+    let priorities = [
+        None,
+        Some(Priority::Low),
+        Some(Priority::Medium),
+        Some(Priority::High),
+    ];
+    (0..count)
+        .map(|i| {
+            let mut item = Item::new(format!("Item {} about report number {} @sam", i, i % 97));
+            item.tags = vec![format!("tag{}", i % 13)];
+            item.contacts = vec!["sam".to_string()];
+            item.due = (i % 3 == 0).then(|| start + Duration::hours(i as i64));
+            item.priority = priorities[i % priorities.len()];
+            item.completed = i % 5 == 0;
+            item.created = Some(start);
+            item
+        })
+        .collect()
+}
+
+fn load_and_save(c: &mut Criterion) {
+    let todos = items(100_000);
+    let dir = std::env::temp_dir().join(format!("todo-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let store = JsonFileStore::new(dir.join("todo.json"));
+
+    // Each takes long enough that fewer samples do.
+    let mut group = c.benchmark_group("todo.json");
+    group.sample_size(20);
+    group.bench_function("save 100k", |b| b.iter(|| store.save(&todos).unwrap()));
+    group.bench_function("load 100k", |b| b.iter(|| store.load().unwrap()));
+    group.finish();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+fn search_and_sort(c: &mut Criterion) {
+    let todos = items(100_000);
+
+    c.bench_function("search 100k", |b| {
+        b.iter(|| bench::search(&todos, "report 42", false))
+    });
+    c.bench_function("fuzzy search 100k", |b| {
+        b.iter(|| bench::search(&todos, "rprt 42", true))
+    });
+    c.bench_function("sort 100k", |b| {
+        b.iter(|| bench::sort(&todos, "due asc, priority desc"))
+    });
+}
+
+fn list(c: &mut Criterion) {
+    let todos = items(10_000);
+
+    c.bench_function("list 10k", |b| b.iter(|| bench::list(&todos)));
+}
+
+criterion_group!(benches, load_and_save, search_and_sort, list);
+criterion_main!(benches);
@@ -0,0 +1,1822 @@
+//! The `todo` command: one-shot commands given on the command line, and
+//! the interactive prompt when there are none.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::{CommandFactory, Parser, Subcommand};
+
+use crate::config::Config;
+use crate::energy::{self, Energy};
+use crate::formats::{self, Format, OnConflict};
+use crate::history::{self, Operation};
+use crate::output::{self, ListFormat};
+use crate::priority::{self, Priority, SortOrder};
+use crate::prompt::Prompt;
+use crate::recurrence::Recurrence;
+use crate::report::{self, ReportCommand};
+use crate::screen::Screen;
+use crate::shop::{self, Quantity};
+#[cfg(feature = "lsp")]
+use crate::stdio;
+use crate::trash::{self, TrashCommand};
+#[cfg(feature = "tui")]
+use crate::tui;
+use crate::{
+    archive, autosave, bugreport, bundle, burnout, celebrate, check_index, clock, config, date,
+    day, deadline, defer, demo, edit, expiry, focus, heatmap, legacy, lists, maintenance, notes,
+    notify, parse_indices, people, quickadd, read_from_file, recipients, render, scan, search,
+    secret, shell, show, someday, stats, store, subtasks, sync, tags, theme, usage, validate,
+    waiting, write_to_file, Item, Todos,
+};
+
+#[derive(Debug, Copy, Clone)]
+enum Command {
+    Add,
+    Print,
+    Exit,
+    Check,
+    Remove,
+    Edit,
+    Move,
+    Undo,
+    Redo,
+    Filter,
+    Search,
+    Switch,
+    ScanGit,
+    Continue,
+}
+
+/// A todo list kept in a single todo.json file
+#[derive(Parser, Debug, Clone)]
+pub(crate) struct Cli {
+    #[clap(subcommand)]
+    command: Option<CliCommand>,
+
+    /// Use this file instead of the one in the data directory. Can also be
+    /// set with TODO_FILE
+    #[clap(long, global = true, parse(from_os_str))]
+    file: Option<std::path::PathBuf>,
+
+    /// Work on the named list instead of the default one
+    #[clap(long, global = true)]
+    list: Option<String>,
+
+    /// How lists are kept: json, or sqlite if built with it. Can also be
+    /// set with TODO_BACKEND
+    #[clap(long, global = true)]
+    backend: Option<store::Backend>,
+
+    /// Also list items whose start date hasn't come yet
+    #[clap(long, global = true)]
+    all: bool,
+
+    /// How to order the list: manual, the default, priority, or keys like
+    /// "due asc, priority desc"; see the README for the keys
+    #[clap(long, global = true)]
+    sort: Option<SortOrder>,
+
+    /// Color open items by age, from green when new to red when stale
+    #[clap(long, global = true)]
+    heatmap: bool,
+
+    /// Run as if it were this time, like "2024-12-31 23:00", to try out due
+    /// dates and reports at any point in time
+    #[clap(long, global = true, parse(try_from_str = clock::parse))]
+    now: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Never use colors. Can also be set with NO_COLOR
+    #[clap(long, global = true)]
+    no_color: bool,
+
+    /// Mask item names and tags, keeping everything else, for showing the
+    /// list on a shared screen
+    #[clap(long, global = true)]
+    redact: bool,
+
+    /// Check the theme's colors for contrast against dark and light
+    /// backgrounds, suggesting fixes for those that are hard to read
+    #[clap(long)]
+    check_theme: bool,
+}
+
+/// How the list is shown, shared by every command that prints it.
+#[derive(Debug, Clone)]
+pub(crate) struct View {
+    pub(crate) show_all: bool,
+    pub(crate) sort: SortOrder,
+    pub(crate) tag: Option<String>,
+    pub(crate) heatmap: bool,
+    pub(crate) redact: bool,
+}
+
+impl View {
+    /// Whether the item is listed, as opposed to filtered out or hidden
+    /// for being deferred, snoozed or for someday.
+    pub(crate) fn shows(&self, item: &Item) -> bool {
+        let hidden = !defer::is_started(item)
+            || defer::is_snoozed(item, clock::now())
+            || someday::is_someday(item);
+        self.tag.as_ref().is_none_or(|tag| item.tags.contains(tag)) && (self.show_all || !hidden)
+    }
+}
+
+/// Commands given on the command line run once and exit. Without a
+/// command, or without the arguments a command needs, the interactive
+/// prompt is started instead.
+#[derive(Subcommand, Debug, Clone)]
+enum CliCommand {
+    /// Add an item
+    #[clap(alias = "a")]
+    Add {
+        name: Vec<String>,
+        /// When it's due, e.g. "tomorrow 5pm", "friday" or "2024-06-30"
+        #[clap(long, parse(try_from_str = date::parse_due_arg))]
+        due: Option<chrono::DateTime<chrono::Local>>,
+        /// Make the due date soft: missing it moves it to the next day
+        /// instead of making the item overdue
+        #[clap(long)]
+        soft: bool,
+        /// Cancel it if it isn't done by then, e.g. "friday"
+        #[clap(long, parse(try_from_str = date::parse_due_arg))]
+        expires: Option<chrono::DateTime<chrono::Local>>,
+        /// low, medium or high
+        #[clap(long)]
+        priority: Option<Priority>,
+        /// Energy it takes: low, medium or high
+        #[clap(long)]
+        energy: Option<Energy>,
+        /// Add it again when it's checked off, e.g. "daily", "every 3 days"
+        /// or "every mon, thu"
+        #[clap(long)]
+        repeat: Option<Recurrence>,
+        /// How much to buy, which puts it on the shopping list, e.g. 2 or "500 g"
+        #[clap(long)]
+        qty: Option<Quantity>,
+        /// Put it straight on the someday list
+        #[clap(long)]
+        someday: bool,
+        /// Add it as a subtask of the item at this index
+        #[clap(long)]
+        under: Option<usize>,
+        /// Tag the item, in addition to any +tags in the name
+        #[clap(long)]
+        tag: Vec<String>,
+        /// Longer text to keep with it, shown by `todo show`
+        #[clap(long)]
+        note: Option<String>,
+    },
+    /// Toggle whether an item is completed. Set TODO_AUTO_ARCHIVE=1 to
+    /// archive items as they're checked off
+    #[clap(aliases = &["c", "uncheck", "u"])]
+    Check {
+        /// Indices and ranges, like `1 3 5-7`, or none with --all to check
+        /// off every open item, after asking
+        indices: Vec<String>,
+        /// Don't ask before checking off every open item
+        #[clap(long, short)]
+        yes: bool,
+    },
+    /// Remove items
+    #[clap(alias = "r")]
+    Remove {
+        /// Indices and ranges, like `1 3 5-7`
+        indices: Vec<String>,
+        /// Remove every completed item
+        #[clap(long, conflicts_with = "indices")]
+        completed: bool,
+    },
+    /// Remove every item
+    Clear,
+    /// Move an item, with its subtasks, to another index
+    #[clap(alias = "mv")]
+    Move {
+        from: Option<usize>,
+        to: Option<usize>,
+    },
+    /// Move an item to the top of the list
+    Top { index: usize },
+    /// Move an item to the bottom of the list
+    Bottom { index: usize },
+    /// Change an item's name, due date, priority or tags
+    #[clap(alias = "d")]
+    Edit {
+        index: Option<usize>,
+        #[clap(flatten)]
+        changes: edit::Changes,
+    },
+    /// Revert the last add, check, remove or edit
+    Undo,
+    /// Apply the last undone change again
+    Redo,
+    /// Suggest open items that fit how much energy is left
+    Suggest {
+        /// low, medium or high
+        #[clap(long)]
+        energy: Energy,
+        /// How many to suggest
+        #[clap(long, default_value = "5")]
+        count: usize,
+    },
+    /// Move an item to the someday list, or back from it
+    Someday { index: usize },
+    /// Go through the someday list a few items at a time
+    Review {
+        /// How many items to bring up
+        #[clap(long, default_value = "3")]
+        count: usize,
+    },
+    /// Browse and change the list full screen with the keyboard
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Try todo out on a made-up list, leaving the real ones alone
+    Demo,
+    /// Print the items whose name contains a query
+    #[clap(alias = "find")]
+    Search {
+        query: Vec<String>,
+        /// Also match the query's letters spread out in order
+        #[clap(long)]
+        fuzzy: bool,
+    },
+    /// Show what's left to buy by store and check items off
+    Shop,
+    /// Write the list as todo.txt, Markdown, CSV or JSON
+    Export {
+        /// txt, md, csv or json; guessed from --output if not given
+        #[clap(long)]
+        format: Option<Format>,
+        /// The file to write, instead of printing
+        #[clap(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Add the items from a todo.txt, Markdown, CSV or JSON file
+    Import {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+        /// txt, md, csv or json; guessed from the file's extension if not given
+        #[clap(long)]
+        format: Option<Format>,
+        /// For JSON from another app, a jq-style map making an object for
+        /// each item, like '.tasks[] | {name: .title, completed: .done}'
+        #[clap(long)]
+        map: Option<String>,
+        /// What to do with items named like ones already on the list:
+        /// skip, overwrite, duplicate or merge
+        #[clap(long, default_value = "duplicate")]
+        on_conflict: OnConflict,
+    },
+    /// Block time in the calendar for high priority items over the next
+    /// week, written as an iCalendar file to import
+    BlockTime {
+        /// How many days ahead, from today
+        #[clap(long, default_value = "7")]
+        days: u32,
+        /// The working day, like 9-17
+        #[clap(long, default_value = "9-17", parse(try_from_str = focus::parse_hours))]
+        hours: (u32, u32),
+        /// How long each block is, like 90m or 2h
+        #[clap(long, default_value = "90m", parse(try_from_str = focus::parse_length))]
+        length: chrono::Duration,
+        /// The file to write, instead of printing
+        #[clap(long, short, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Bundle every list, with its archive and history, and the config into
+    /// one file, like backup.tar.zst, to move to another machine
+    ExportAll {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Put everything from a bundle written by export-all in place
+    ImportAll {
+        #[clap(parse(from_os_str))]
+        path: PathBuf,
+        /// Replace lists and config already here
+        #[clap(long)]
+        overwrite: bool,
+    },
+    /// Merge the list with the same list on another machine over SSH, or
+    /// kept in S3, a git repository or on a web server
+    Sync {
+        /// user@host to sync with
+        #[clap(
+            long,
+            required_unless_present_any = &["s3", "git", "http", "send", "receive"],
+            conflicts_with_all = &["s3", "git", "http"]
+        )]
+        ssh: Option<String>,
+        /// How to run todo on the other machine
+        #[clap(long, default_value = "todo")]
+        remote_command: String,
+        /// s3://bucket/path to keep the list in
+        #[clap(long, conflicts_with_all = &["git", "http"])]
+        s3: Option<String>,
+        /// A git repository to keep the list in, committing on every sync
+        #[clap(long, conflicts_with = "http")]
+        git: Option<String>,
+        /// A URL to keep the list under, read with GET and written with PUT
+        #[clap(long)]
+        http: Option<String>,
+        /// Storage to use instead of AWS, like MinIO or B2
+        #[clap(long, requires = "s3")]
+        endpoint: Option<String>,
+        /// age key to encrypt the list with before it's stored
+        #[clap(long, requires = "s3", parse(from_os_str))]
+        age_identity: Option<PathBuf>,
+        /// Print the list for the other side of a sync
+        #[clap(long, hide = true)]
+        send: bool,
+        /// Replace the list with the merged one from the other side
+        #[clap(long, hide = true)]
+        receive: bool,
+        /// Take every change without asking
+        #[clap(long, short)]
+        yes: bool,
+    },
+    /// Show or change who else a list synced with --age-identity is
+    /// encrypted for
+    Recipients {
+        #[clap(subcommand)]
+        command: Option<recipients::RecipientsCommand>,
+    },
+    /// Keep a password or token in the system's keyring
+    Secret {
+        #[clap(subcommand)]
+        command: secret::SecretCommand,
+    },
+    /// Show removed items, restore them or empty the trash
+    Trash {
+        #[clap(subcommand)]
+        command: Option<trash::TrashCommand>,
+    },
+    /// Move completed items off the list into its archive
+    Archive,
+    /// Show everything about an item, with its notes
+    Show { index: usize },
+    /// Show the archive of completed items, or those matching a search
+    Done { query: Vec<String> },
+    /// Show how much is open and done, and how fast items get completed
+    Stats {
+        /// Show which commands you use and which you've never tried instead
+        #[clap(long)]
+        usage: bool,
+    },
+    /// Write a report to attach to an issue, with nothing about the items
+    Bugreport,
+    /// Reports on how the list is going
+    Report {
+        #[clap(subcommand)]
+        report: ReportCommand,
+    },
+    /// Look for todo.json files left by older versions and show what's in
+    /// them, or with --merge add them to the list. `migrate sqlite` copies
+    /// the list into that backend instead
+    Migrate {
+        /// The backend to copy the list into
+        to: Option<store::Backend>,
+        /// Add the items found to the list, filling in those it has already
+        #[clap(long, conflicts_with = "to")]
+        merge: bool,
+    },
+    /// Show the list as it was at a past time, kept by the sqlite backend
+    #[cfg(feature = "sqlite")]
+    Asof {
+        /// e.g. "2024-05-01" for the end of that day, or "yesterday 9am"
+        #[clap(parse(try_from_str = date::parse_due_arg))]
+        time: chrono::DateTime<chrono::Local>,
+    },
+    /// Back up the list, clear out old backups and archive completed
+    /// items, as is done on the first run of each day
+    Maintain,
+    /// Show every list and which one is in use
+    Lists,
+    /// Print the list
+    #[clap(aliases = &["print", "p", "ls"])]
+    List {
+        /// Only list items with this tag
+        #[clap(long)]
+        tag: Option<String>,
+        /// text, or json or tsv for scripts
+        #[clap(long, default_value = "text")]
+        format: ListFormat,
+    },
+    /// Talk newline-delimited JSON for editor plugins
+    #[cfg(feature = "lsp")]
+    LspLike {
+        /// Use stdin/stdout as the transport
+        #[clap(long)]
+        stdio: bool,
+    },
+    /// Import TODO/FIXME markers from the current git repository
+    ScanGit,
+    /// Track // TODO comments under a directory as items
+    Scan {
+        #[clap(default_value = ".")]
+        dir: String,
+    },
+    /// Show or change today's picks
+    Day {
+        #[clap(subcommand)]
+        command: Option<day::DayCommand>,
+    },
+    /// Mark an item as waiting on someone, or clear it without a person
+    Wait { index: usize, person: Vec<String> },
+    /// List items waiting on someone
+    Waiting,
+    /// List open items grouped by the people they mention
+    People,
+    /// Print a person's open items as a meeting agenda
+    Agenda {
+        #[clap(long)]
+        person: String,
+        /// Output format, only md is supported
+        #[clap(long, default_value = "md")]
+        format: String,
+    },
+    /// Hide an item until a date (YYYY-MM-DD), or show it again without one
+    Defer {
+        index: usize,
+        date: Option<chrono::NaiveDate>,
+    },
+    /// Hide an item for a while, e.g. `snooze 3 2h`, `snooze 3 "monday
+    /// 9am"`, or show it again with `snooze 3 off`
+    Snooze {
+        index: usize,
+        #[clap(required = true)]
+        until: Vec<String>,
+    },
+    /// Keep running and show a desktop notification when an item falls due
+    Notify {
+        /// How many minutes to wait between checks
+        #[clap(long, default_value = "1", parse(try_from_str = notify::parse_every))]
+        every: u64,
+    },
+    /// Check todo.json for problems
+    Validate {
+        /// Repair what can be repaired
+        #[clap(long)]
+        fix: bool,
+    },
+    /// Install shell completions and create the config directory
+    InitShell {
+        /// bash, zsh, fish, elvish or powershell; guessed from $SHELL if not given
+        #[clap(long, arg_enum)]
+        shell: Option<clap_complete::Shell>,
+        /// Also show the number of open items in the shell prompt
+        #[clap(long)]
+        prompt_hook: bool,
+        /// Only take the prompt hook out again
+        #[clap(long, conflicts_with = "prompt-hook")]
+        remove_prompt_hook: bool,
+    },
+    /// Print the number of open items for a shell prompt
+    #[clap(hide = true)]
+    Prompt,
+}
+
+/// Commands that other programs run rather than people, which aren't
+/// counted in `stats --usage`.
+const RUN_BY_PROGRAMS: [&str; 2] = ["prompt", "lsp-like"];
+
+/// The name `command` is given by, as clap names it: its variant's name in
+/// kebab case, like `lsp-like`.
+fn command_name(command: &CliCommand) -> String {
+    let mut name = String::new();
+    for c in format!("{:?}", command)
+        .chars()
+        .take_while(|c| c.is_alphanumeric())
+    {
+        if c.is_uppercase() && !name.is_empty() {
+            name.push('-');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+/// Every command people run, with what it does, for `stats --usage`.
+fn commands() -> Vec<(String, String)> {
+    Cli::command()
+        .get_subcommands()
+        .filter(|command| !RUN_BY_PROGRAMS.contains(&command.get_name()))
+        .map(|command| {
+            let about = command.get_about().unwrap_or_default();
+            (command.get_name().to_string(), about.to_string())
+        })
+        .collect()
+}
+
+impl CliCommand {
+    /// Whether the day's maintenance is done before the command. Commands
+    /// run by other programs, and those that do it themselves, skip it.
+    fn maintains(&self) -> bool {
+        match self {
+            CliCommand::Prompt
+            | CliCommand::Sync { send: true, .. }
+            | CliCommand::Sync { receive: true, .. }
+            | CliCommand::Validate { .. }
+            | CliCommand::Notify { .. }
+            | CliCommand::Secret { .. }
+            | CliCommand::Bugreport
+            | CliCommand::ImportAll { .. }
+            | CliCommand::Maintain => false,
+            #[cfg(feature = "lsp")]
+            CliCommand::LspLike { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+const USER_COMMANDS: [Command; 12] = [
+    Command::Add,
+    Command::Check,
+    Command::Remove,
+    Command::Edit,
+    Command::Move,
+    Command::Undo,
+    Command::Redo,
+    Command::Print,
+    Command::Filter,
+    Command::Search,
+    Command::Switch,
+    Command::Exit,
+];
+
+/// The command line with the flags from `[defaults.<command>]` in the
+/// config added after the command's name, leaving out those given already.
+fn with_defaults(args: Vec<OsString>, config: &Config) -> Result<Vec<OsString>, String> {
+    let cli = Cli::command();
+    let takes_value: Vec<&str> = cli
+        .get_arguments()
+        .filter(|arg| arg.is_takes_value_set())
+        .filter_map(|arg| arg.get_long())
+        .collect();
+
+    // The command is the first word that isn't a global flag or its value.
+    let mut position = 1;
+    while let Some(arg) = args.get(position).and_then(|arg| arg.to_str()) {
+        match arg.strip_prefix("--") {
+            Some(long) if takes_value.contains(&long) => position += 2,
+            _ if arg.starts_with('-') => position += 1,
+            _ => break,
+        }
+    }
+    let Some(command) = args
+        .get(position)
+        .and_then(|arg| arg.to_str())
+        .and_then(|name| cli.find_subcommand(name))
+    else {
+        return Ok(args);
+    };
+    let Some(defaults) = config.defaults.get(command.get_name()) else {
+        return Ok(args);
+    };
+
+    let given = |long: &str| {
+        args.iter().filter_map(|arg| arg.to_str()).any(|arg| {
+            arg == long
+                || arg
+                    .strip_prefix(long)
+                    .is_some_and(|rest| rest.starts_with('='))
+        })
+    };
+    let mut added: Vec<OsString> = Vec::new();
+    for (flag, value) in defaults {
+        let known = command
+            .get_arguments()
+            .chain(cli.get_arguments())
+            .any(|arg| arg.get_long() == Some(flag));
+        if !known {
+            return Err(format!(
+                "The config gives `{}` a default for --{}, which it doesn't have",
+                command.get_name(),
+                flag
+            ));
+        }
+        let long = format!("--{}", flag);
+        if given(&long) {
+            continue;
+        }
+        let values = match value {
+            toml::Value::Boolean(true) => {
+                added.push(long.into());
+                continue;
+            }
+            toml::Value::Boolean(false) => continue,
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                toml::Value::String(value) => value.clone(),
+                toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Datetime(_) => {
+                    value.to_string()
+                }
+                _ => return Err(format!("The config's default for --{} isn't a value", flag)),
+            };
+            added.push(long.clone().into());
+            added.push(value.into());
+        }
+    }
+
+    let mut args = args;
+    args.splice(position + 1..position + 1, added);
+    Ok(args)
+}
+
+/// Runs `todo` with the arguments it was started with. Errors are left for
+/// `main` to report, which exits with status 1 for them.
+pub fn run() -> Result<(), String> {
+    bugreport::install();
+    let mut config = config::load()?;
+    let args: Vec<OsString> = std::env::args_os().collect();
+    clock::init(&args)?;
+    let mut args = Cli::parse_from(with_defaults(args, &config)?);
+    if let Some(now) = args.now {
+        // Stopped already by `clock::init`, this is for when it's given in
+        // `[defaults]`.
+        clock::set(Box::new(clock::Fixed(now)));
+    }
+    let command = match &args.command {
+        Some(command) => command_name(command),
+        None => usage::PROMPT.to_string(),
+    };
+    bugreport::note_command(&command);
+    if args.no_color {
+        config.color = Some(false);
+    }
+    let config = config::set(config);
+    if args.check_theme {
+        let mut screen = Screen::new();
+        let failing = theme::print_check(&mut screen, theme::get());
+        screen.flush();
+        return match failing {
+            0 => Ok(()),
+            _ => Err("The theme has colors that are hard to read".to_string()),
+        };
+    }
+    if !RUN_BY_PROGRAMS.contains(&command.as_str()) && !clock::is_simulated() {
+        usage::record(&command, clock::now());
+    }
+    let demo = matches!(args.command, Some(CliCommand::Demo));
+    if demo {
+        demo::start(clock::now())?;
+        args.command = None;
+    } else {
+        let list = args.list.or_else(|| config.list.clone());
+        store::init(args.file, list, args.backend)?;
+    }
+    if !demo && args.command.as_ref().is_none_or(CliCommand::maintains) {
+        match maintenance::daily(clock::now().date_naive()) {
+            Ok(Some(summary)) => eprintln!("{}", summary),
+            Ok(None) => {}
+            Err(e) => eprintln!("Maintenance failed: {}", e),
+        }
+    }
+    let mut view = View {
+        show_all: args.all,
+        sort: args
+            .sort
+            .or_else(|| config.sort.clone())
+            .unwrap_or_default(),
+        tag: None,
+        heatmap: args.heatmap,
+        redact: args.redact,
+    };
+
+    let mut command = match args.command {
+        None => Command::Continue,
+        Some(cli_command) => match run_cli_command(cli_command, view.clone())? {
+            Some(command) => command,
+            None => return Ok(()),
+        },
+    };
+
+    let mut screen = Screen::new();
+    screen.enter_alternate_screen();
+    screen.clear();
+    loop {
+        let mut todos = load(&mut screen);
+        if !clock::is_simulated() {
+            match deadline::move_missed(&mut todos, clock::now()) {
+                Ok(true) => autosave::save(&todos),
+                Ok(false) => {}
+                Err(e) => writeln!(screen, "{}\n", e),
+            }
+            match expiry::cancel_expired(&mut todos, clock::now()) {
+                Ok(notices) if !notices.is_empty() => {
+                    for notice in notices {
+                        writeln!(screen, "{}", notice);
+                    }
+                    writeln!(screen);
+                    autosave::save(&todos);
+                }
+                Ok(_) => {}
+                Err(e) => writeln!(screen, "{}\n", e),
+            }
+        }
+        let today = clock::now().date_naive();
+        let progress = celebrate::Progress::of(&todos, today);
+        if process_command(command, &mut todos, &mut view, &mut screen) {
+            autosave::save(&todos);
+            if let Some(message) = celebrate::celebrate(progress, &todos, today) {
+                writeln!(screen, "{}\n", message);
+            }
+        }
+        command = get_new_command(&mut screen);
+    }
+}
+
+/// Runs a command given on the command line. Returns the command to start
+/// the interactive prompt with when it still needs input from the user.
+fn run_cli_command(command: CliCommand, mut view: View) -> Result<Option<Command>, String> {
+    // Validating reads the file itself, as it has to cope with damage.
+    if let CliCommand::Validate { fix } = command {
+        return validate::run(&mut Screen::new(), fix).map(|_| None);
+    }
+    if let CliCommand::Notify { every } = command {
+        notify::watch(every);
+    }
+    // Secrets have nothing to do with the list.
+    if let CliCommand::Secret { command } = command {
+        return secret::run(&mut Screen::new(), command).map(|_| None);
+    }
+    // Nor is a report, which has to be written however damaged it is.
+    if let CliCommand::Bugreport = command {
+        let path = bugreport::write(None)?;
+        println!(
+            "Wrote {}, look it over before attaching it to an issue",
+            path.display()
+        );
+        return Ok(None);
+    }
+    // Bundles are about every list, and the files rather than the items.
+    if let CliCommand::ExportAll { path } = &command {
+        let count = bundle::export(path)?;
+        println!("Wrote {} files to {}", count, path.display());
+        return Ok(None);
+    }
+    if let CliCommand::ImportAll { path, overwrite } = &command {
+        let count = bundle::import(path, *overwrite)?;
+        println!("Put {} files from {} in place", count, path.display());
+        return Ok(None);
+    }
+
+    let mut todos = read_from_file()?;
+    let repairs = validate::check_references(&mut todos);
+    if !repairs.is_empty() {
+        eprintln!("{}", repairs.join("\n"));
+        write_to_file(&todos)?;
+    }
+    if !clock::is_simulated() {
+        let moved = deadline::move_missed(&mut todos, clock::now())?;
+        let notices = expiry::cancel_expired(&mut todos, clock::now())?;
+        if !notices.is_empty() {
+            eprintln!("{}", notices.join("\n"));
+        }
+        if moved || !notices.is_empty() {
+            write_to_file(&todos)?;
+        }
+    }
+    let today = clock::now().date_naive();
+    let progress = celebrate::Progress::of(&todos, today);
+    let mut screen = Screen::new();
+
+    match command {
+        CliCommand::Add {
+            name,
+            due: None,
+            soft: false,
+            expires: None,
+            priority: None,
+            energy: None,
+            repeat: None,
+            qty: None,
+            someday: false,
+            under: None,
+            tag,
+            note: None,
+        } if name.is_empty() && tag.is_empty() => return Ok(Some(Command::Add)),
+        CliCommand::Check { indices, .. } if indices.is_empty() && !view.show_all => {
+            return Ok(Some(Command::Check))
+        }
+        CliCommand::Remove {
+            indices,
+            completed: false,
+        } if indices.is_empty() => return Ok(Some(Command::Remove)),
+        CliCommand::Edit {
+            index: None,
+            changes,
+        } if changes.is_empty() => return Ok(Some(Command::Edit)),
+        CliCommand::Move { from: None, .. } => return Ok(Some(Command::Move)),
+        CliCommand::Add {
+            name,
+            due,
+            soft,
+            expires,
+            priority,
+            energy,
+            repeat,
+            qty,
+            someday,
+            under,
+            tag,
+            note,
+        } => {
+            let mut item = quickadd::parse(&name.join(" "), clock::now());
+            if item.name.is_empty() {
+                return Err("add needs a name when options are given".to_string());
+            }
+            item.due = due.or(item.due);
+            if soft && item.due.is_none() {
+                return Err("--soft needs a due date".to_string());
+            }
+            item.soft = soft;
+            item.expires = expires;
+            item.priority = priority.or(item.priority);
+            item.energy = energy.or(item.energy);
+            item.recurrence = repeat;
+            item.quantity = qty;
+            item.notes = note.as_deref().and_then(notes::clean);
+            if someday {
+                item.someday = Some(clock::now().date_naive());
+            }
+            for tag in tag.iter().map(|tag| tags::normalize(tag)) {
+                if !tag.is_empty() && !item.tags.contains(&tag) {
+                    item.tags.push(tag);
+                }
+            }
+            let operation = match under {
+                Some(parent) => Operation::Insert {
+                    index: subtasks::position_under(&todos, parent, &mut item)?,
+                    item,
+                },
+                None => Operation::add(&todos, item),
+            };
+            history::perform(&mut todos, operation)?;
+        }
+        CliCommand::Check { indices, yes } => {
+            if yes && !view.show_all {
+                return Err("--yes is for check --all".to_string());
+            }
+            let every = view.show_all && indices.is_empty();
+            let indices = to_check(&todos, &indices, every)?;
+            if every && !yes && !indices.is_empty() {
+                let question = format!("Check off all {} open items? (y/N)", indices.len());
+                if Prompt::confirm(question).default(false).ask(&mut screen) != Some(true) {
+                    return Ok(None);
+                }
+            }
+            if !indices.is_empty() {
+                archive::toggle(&mut todos, &indices)?;
+            }
+        }
+        CliCommand::Remove { indices, completed } => {
+            let indices = match completed {
+                true => (0..todos.len()).filter(|&i| todos[i].completed).collect(),
+                false => parse_indices(&indices.join(" "), todos.len())?,
+            };
+            if !indices.is_empty() {
+                trash::remove(&mut todos, &indices)?;
+            }
+        }
+        CliCommand::Move {
+            from: Some(_),
+            to: None,
+        } => return Err("move needs the index to move the item to".to_string()),
+        CliCommand::Move {
+            from: Some(index),
+            to: Some(to),
+        } => move_item(&mut todos, index, to)?,
+        CliCommand::Top { index } => move_item(&mut todos, index, 0)?,
+        CliCommand::Bottom { index } => move_item(&mut todos, index, usize::MAX)?,
+        CliCommand::Clear => {
+            if !todos.is_empty() {
+                let indices: Vec<usize> = (0..todos.len()).collect();
+                trash::remove(&mut todos, &indices)?;
+                println!("Removed everything, `todo undo` brings it back");
+            }
+        }
+        CliCommand::Edit { index: None, .. } => {
+            return Err("edit needs the index of the item to change".to_string());
+        }
+        CliCommand::Edit {
+            index: Some(index),
+            changes,
+        } => edit::edit(&mut todos, index, changes)?,
+        CliCommand::Suggest { energy, count } => {
+            energy::print_suggestions(&mut screen, &todos, energy, count);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Someday { index } => {
+            println!(
+                "{}",
+                someday::toggle(&mut todos, index, clock::now().date_naive())?
+            );
+        }
+        CliCommand::Review { count } => {
+            someday::review(&mut screen, &mut todos, count, clock::now().date_naive());
+            write_to_file(&todos)?;
+            screen.flush();
+            return Ok(None);
+        }
+        #[cfg(feature = "tui")]
+        CliCommand::Tui => {
+            let result = tui::run(&mut todos, view);
+            autosave::wait()?;
+            result?;
+            return Ok(None);
+        }
+        CliCommand::Search { query, fuzzy } => {
+            let matches = search::find(&todos, &query.join(" "), fuzzy);
+            search::print_matches(&mut screen, &todos, &matches);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Shop => {
+            shop::shop(&mut screen, &mut todos)?;
+            if let Some(message) = celebrate::celebrate(progress, &todos, today) {
+                writeln!(screen, "{}\n", message);
+            }
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Export { format, output } => {
+            let format = match (format, &output) {
+                (Some(format), _) => format,
+                (None, Some(output)) => Format::of(output)?,
+                (None, None) => return Err("export needs --format or --output".to_string()),
+            };
+            let exported = format.export(&todos);
+            match output {
+                Some(output) => std::fs::write(&output, exported)
+                    .map_err(|e| format!("Could not write {}: {}", output.display(), e))?,
+                None => print!("{}", exported),
+            }
+            return Ok(None);
+        }
+        CliCommand::Import {
+            path,
+            format,
+            map,
+            on_conflict,
+        } => {
+            let format = match format {
+                Some(format) => format,
+                None => Format::of(&path)?,
+            };
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            let imported = match map {
+                Some(map) if format == Format::Json => formats::import_mapped(&content, &map)?,
+                Some(_) => return Err("--map is only for JSON".to_string()),
+                None => format.import(&content)?,
+            };
+            let (operation, counts) = formats::import_into(&todos, imported, on_conflict);
+            println!(
+                "Imported {} items from {}, {} updated, {} skipped",
+                counts.added,
+                path.display(),
+                counts.updated,
+                counts.skipped
+            );
+            history::perform(&mut todos, operation)?;
+        }
+        CliCommand::BlockTime {
+            days,
+            hours,
+            length,
+            output,
+        } => {
+            let plan = focus::Plan {
+                days,
+                hours,
+                length,
+            };
+            let (calendar, count) = focus::calendar(&todos, plan, clock::now());
+            match output {
+                Some(output) => {
+                    std::fs::write(&output, calendar)
+                        .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+                    println!("Blocked time for {} items in {}", count, output.display());
+                }
+                None => print!("{}", calendar),
+            }
+            return Ok(None);
+        }
+        CliCommand::Sync { send: true, .. } => {
+            sync::send(&todos)?;
+            return Ok(None);
+        }
+        CliCommand::Sync { receive: true, .. } => {
+            sync::receive(&mut todos)?;
+            write_to_file(&todos)?;
+            return Ok(None);
+        }
+        CliCommand::Sync {
+            ssh,
+            remote_command,
+            s3,
+            endpoint,
+            age_identity,
+            git,
+            http,
+            yes,
+            ..
+        } => {
+            let remote = match (s3, git, http) {
+                (Some(url), _, _) => sync::Remote::S3 {
+                    url,
+                    endpoint,
+                    age_identity,
+                },
+                (_, Some(url), _) => sync::Remote::Git { url },
+                (_, _, Some(url)) => sync::Remote::Http { url },
+                _ => sync::Remote::Ssh {
+                    host: ssh.expect("clap requires a remote"),
+                    command: remote_command,
+                },
+            };
+            let ask = screen.is_interactive() && !yes;
+            for message in sync::sync(&mut todos, &remote, &mut screen, ask)? {
+                println!("{}", message);
+            }
+        }
+        CliCommand::Recipients { command } => {
+            recipients::run(&mut screen, command)?;
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Trash {
+            command: None | Some(TrashCommand::List),
+        } => {
+            trash::print(&mut screen, &trash::read()?);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Trash {
+            command: Some(TrashCommand::Restore { ids }),
+        } => println!("{}", trash::restore(&mut todos, &ids)?),
+        CliCommand::Trash {
+            command: Some(TrashCommand::Empty),
+        } => {
+            println!("{}", trash::empty()?);
+            return Ok(None);
+        }
+        CliCommand::Archive => println!("{}", archive::archive(&mut todos)?),
+        CliCommand::Done { query } => {
+            let query = Some(query.join(" ")).filter(|query| !query.is_empty());
+            archive::print_done(&mut screen, &archive::read()?, query.as_deref());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Show { index } => {
+            check_index(&todos, index)?;
+            show::print_item(&mut screen, &todos, index, clock::now());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Report {
+            report: ReportCommand::Procrastination,
+        } => {
+            report::print_procrastination(&mut screen, &todos, clock::now());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Report {
+            report: ReportCommand::LateNights,
+        } => {
+            let archived = archive::read()?.into_iter().map(|done| done.item);
+            let completed: Vec<_> = todos
+                .iter()
+                .cloned()
+                .chain(archived)
+                .filter_map(|item| item.completed_at)
+                .collect();
+            burnout::print_late_nights(&mut screen, &completed, burnout::cutoff(), clock::now());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Stats { usage: true } => {
+            usage::print(&mut screen, &commands());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Stats { usage: false } => {
+            stats::print_stats(&mut screen, &todos, &archive::read()?, clock::now());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Migrate { to: None, merge } => {
+            let found = legacy::find();
+            if !merge {
+                let mut screen = Screen::new();
+                legacy::print_found(&mut screen, &found);
+                screen.flush();
+                return Ok(None);
+            }
+            let lists: Vec<(PathBuf, Todos)> = found
+                .into_iter()
+                .filter_map(|file| Some((file.path, file.todos?)))
+                .collect();
+            for (path, imported) in &lists {
+                let (operation, counts) =
+                    formats::import_into(&todos, imported.clone(), OnConflict::Merge);
+                history::perform(&mut todos, operation)?;
+                println!(
+                    "Added {} items from {}, filled in {}, {} were here already",
+                    counts.added,
+                    path.display(),
+                    counts.updated,
+                    counts.skipped
+                );
+            }
+            // Set aside only once they're safely on the list.
+            write_to_file(&todos)?;
+            for (path, _) in &lists {
+                legacy::set_aside(path)?;
+            }
+            return Ok(None);
+        }
+        #[cfg(not(feature = "sqlite"))]
+        CliCommand::Migrate { to: Some(_), .. } => {
+            return Err("There's only the json backend in this build".to_string());
+        }
+        #[cfg(feature = "sqlite")]
+        CliCommand::Migrate { to: Some(to), .. } => {
+            let from = match to {
+                store::Backend::Json => store::Backend::Sqlite,
+                store::Backend::Sqlite => store::Backend::Json,
+            };
+            let (source, target) = (store::open_with(from), store::open_with(to));
+            if !target.load()?.is_empty() {
+                return Err(format!("The {} list isn't empty, not replacing it", to));
+            }
+            let migrated = source.load()?;
+            target.save(&migrated)?;
+            println!(
+                "Copied {} items, use --backend or TODO_BACKEND to use them",
+                migrated.len()
+            );
+            return Ok(None);
+        }
+        #[cfg(feature = "sqlite")]
+        CliCommand::Asof { time } => {
+            if store::backend() != store::Backend::Sqlite {
+                return Err("asof needs the sqlite backend, see `todo migrate`".to_string());
+            }
+            let todos = store::sqlite().as_of(time)?;
+            print_todo(&mut screen, &todos, false, &view);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Maintain => {
+            if clock::is_simulated() {
+                return Err("Maintenance doesn't run at a simulated time".to_string());
+            }
+            let done = maintenance::maintain(&mut todos, today, true)?;
+            println!("{}", maintenance::summary(&done));
+        }
+        CliCommand::Lists => {
+            lists::print_lists(&mut screen);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Undo => println!("{}", history::undo(&mut todos)?),
+        CliCommand::Redo => println!("{}", history::redo(&mut todos)?),
+        CliCommand::List { tag, format } => {
+            view.tag = tag.map(|tag| tags::normalize(&tag));
+            let listed: Vec<usize> = priority::display_order(&todos, &view.sort)
+                .into_iter()
+                .filter(|&i| view.shows(&todos[i]))
+                .collect();
+            match format {
+                ListFormat::Text => print_todo(&mut screen, &todos, true, &view),
+                ListFormat::Json => output::print_json(&mut screen, &todos, &listed),
+                ListFormat::Tsv => output::print_tsv(&mut screen, &todos, &listed),
+            }
+            screen.flush();
+            return Ok(None);
+        }
+        #[cfg(feature = "lsp")]
+        CliCommand::LspLike { stdio } => {
+            if !stdio {
+                return Err("lsp-like only supports the --stdio transport".to_string());
+            }
+            stdio::run_session();
+            return Ok(None);
+        }
+        CliCommand::ScanGit => scan::scan_git(&mut todos, &mut screen)?,
+        CliCommand::Scan { dir } => {
+            scan::scan_source(&mut todos, &dir, &mut screen)?;
+            write_to_file(&todos)?;
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Day { command } => {
+            day::run(&mut screen, &mut todos, command)?;
+            write_to_file(&todos)?;
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Wait { index, person } => {
+            waiting::wait(&mut screen, &mut todos, index, person)?;
+            write_to_file(&todos)?;
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Waiting => {
+            waiting::print_waiting(&mut screen, &todos, clock::now().date_naive());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::People => {
+            people::print_people(&mut screen, &todos);
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Agenda { person, format } => {
+            if format != "md" {
+                return Err(format!("No agenda format called {}, expected md", format));
+            }
+            people::print_agenda(&mut screen, &todos, &person, clock::now().date_naive());
+            screen.flush();
+            return Ok(None);
+        }
+        CliCommand::Defer { index, date } => {
+            defer::defer(&mut todos, index, date)?;
+            write_to_file(&todos)?;
+            return Ok(None);
+        }
+        CliCommand::Snooze { index, until } => {
+            let until = defer::parse_snooze(&until.join(" "), clock::now())?;
+            println!("{}", defer::snooze(&mut todos, index, until)?);
+        }
+        CliCommand::InitShell {
+            shell,
+            prompt_hook,
+            remove_prompt_hook,
+        } => {
+            shell::init(shell, prompt_hook, remove_prompt_hook)?;
+            return Ok(None);
+        }
+        CliCommand::Prompt => {
+            shell::print_prompt(&todos);
+            return Ok(None);
+        }
+        CliCommand::Validate { .. }
+        | CliCommand::Notify { .. }
+        | CliCommand::Secret { .. }
+        | CliCommand::Bugreport
+        | CliCommand::ExportAll { .. }
+        | CliCommand::ImportAll { .. }
+        | CliCommand::Demo => {
+            unreachable!()
+        }
+    }
+
+    write_to_file(&todos)?;
+    print_todo(&mut screen, &todos, true, &view);
+    if let Some(message) = celebrate::celebrate(progress, &todos, today) {
+        writeln!(screen, "{}\n", message);
+    }
+    screen.flush();
+    Ok(None)
+}
+
+fn get_new_command(screen: &mut Screen) -> Command {
+    if screen.is_interactive() {
+        writeln!(screen, "Enter command: ");
+        writeln!(screen, "OPTIONS: ");
+        for command in USER_COMMANDS {
+            writeln!(screen, " - {}", get_command_string(command));
+        }
+        writeln!(screen, "\n\n");
+    }
+
+    let input = screen.read_line();
+    get_command(&input, screen)
+}
+
+fn get_command(command_str: &str, screen: &mut Screen) -> Command {
+    match command_str {
+        "add" | "a" => Command::Add,
+        "check" | "c" | "uncheck" | "u" => Command::Check,
+        "remove" | "r" => Command::Remove,
+        "edit" | "d" => Command::Edit,
+        "move" | "m" => Command::Move,
+        "undo" => Command::Undo,
+        "redo" => Command::Redo,
+        "print" | "p" => Command::Print,
+        "filter" | "f" => Command::Filter,
+        "search" | "/" => Command::Search,
+        "switch" | "s" => Command::Switch,
+        "exit" | "e" => Command::Exit,
+        "scan-git" => Command::ScanGit,
+        "" => Command::Continue,
+        _ => {
+            writeln!(screen, "No Command called {}\n", command_str);
+            Command::Continue
+        }
+    }
+}
+
+fn get_command_string(command: Command) -> String {
+    match command {
+        Command::Add => "(a)dd".to_string(),
+        Command::Print => "(p)rint".to_string(),
+        Command::Exit => "(e)xit".to_string(),
+        Command::Check => "(c)heck/uncheck".to_string(),
+        Command::Remove => "(r)emove".to_string(),
+        Command::Edit => "e(d)it".to_string(),
+        Command::Move => "(m)ove".to_string(),
+        Command::Undo => "undo".to_string(),
+        Command::Redo => "redo".to_string(),
+        Command::Filter => "(f)ilter by tag".to_string(),
+        Command::Search => "(/)search".to_string(),
+        Command::Switch => "(s)witch list".to_string(),
+        Command::ScanGit | Command::Continue => panic!("Should not happen"),
+    }
+}
+
+/// Returns whether the command may have changed `todos`, so read-only
+/// commands don't rewrite the file.
+fn process_command(
+    command: Command,
+    todos: &mut Todos,
+    view: &mut View,
+    screen: &mut Screen,
+) -> bool {
+    if let Command::Continue = command {
+        return false;
+    }
+
+    screen.clear();
+    match command {
+        Command::Add => {
+            add_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Check => {
+            check_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Print => {
+            print_todo(screen, todos, false, view);
+            false
+        }
+        Command::Search => {
+            search::search_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Filter => {
+            filter_todo(view, screen);
+            print_todo(screen, todos, false, view);
+            false
+        }
+        Command::Switch => {
+            lists::switch_list(screen);
+            *todos = load(screen);
+            print_todo(screen, todos, false, view);
+            false
+        }
+        Command::Exit => {
+            screen.leave_alternate_screen();
+            autosave::exit(0);
+        }
+        Command::Remove => {
+            remove_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Edit => {
+            edit::edit_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Move => {
+            move_todo(todos, screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Undo => {
+            report(history::undo(todos), screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Redo => {
+            report(history::redo(todos), screen);
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::ScanGit => {
+            if let Err(e) = scan::scan_git(todos, screen) {
+                writeln!(screen, "{}\n", e);
+            }
+            print_todo(screen, todos, false, view);
+            true
+        }
+        Command::Continue => false,
+    }
+}
+
+/// Reads the list for the interactive prompt, asking what to do if it's
+/// damaged.
+fn load(screen: &mut Screen) -> Todos {
+    // Changes still being saved are part of what's read.
+    if let Err(e) = autosave::wait() {
+        writeln!(screen, "That change wasn't saved: {}\n", e);
+    }
+    loop {
+        let error = match read_from_file() {
+            Ok(mut todos) => {
+                let repairs = validate::check_references(&mut todos);
+                if !repairs.is_empty() {
+                    writeln!(screen, "{}\n", repairs.join("\n"));
+                    autosave::save(&todos);
+                }
+                return todos;
+            }
+            Err(e) => e,
+        };
+
+        writeln!(screen, "{}\n", error);
+        let answer = Prompt::new(
+            "(r)estore the previous version, start (o)ver and keep the damaged file, or (e)xit",
+            |line| match line {
+                "restore" | "r" => Ok(Recovery::Restore),
+                "over" | "o" => Ok(Recovery::StartOver),
+                "exit" | "e" => Ok(Recovery::Exit),
+                _ => Err("Answer r, o or e".to_string()),
+            },
+        )
+        .ask(screen)
+        .unwrap_or(Recovery::Exit);
+
+        let path = store::path();
+        let result = match answer {
+            Recovery::Restore => std::fs::copy(store::path_with("bak"), &path).map(|_| ()),
+            Recovery::StartOver => std::fs::rename(&path, store::path_with("damaged")),
+            Recovery::Exit => {
+                screen.leave_alternate_screen();
+                std::process::exit(1);
+            }
+        };
+        if let Err(e) = result {
+            writeln!(screen, "\nThat didn't work: {}\n", e);
+        }
+    }
+}
+
+enum Recovery {
+    Restore,
+    StartOver,
+    Exit,
+}
+
+fn add_todo(todos: &mut Todos, screen: &mut Screen) {
+    let items = ask_new_items(screen);
+    screen.clear();
+    for item in items.into_iter().flatten() {
+        record(todos, Operation::add(todos, item), screen);
+    }
+}
+
+/// Asks for items one per line, with their details written as `add` takes
+/// them, until an empty line. Returns `None` if the user cancels.
+fn ask_new_items(screen: &mut Screen) -> Option<Vec<Item>> {
+    writeln!(
+        screen,
+        "What's the Todo? One per line, e.g. buy milk +groceries !high @tomorrow; \
+         an empty line when done (cancel to stop)"
+    );
+    let mut items = Vec::new();
+    loop {
+        match screen.read_line().as_str() {
+            "cancel" => return None,
+            "" => break,
+            line => items.push(quickadd::parse(line, clock::now())),
+        }
+    }
+
+    if let [item] = &mut items[..] {
+        if screen.is_interactive()
+            && Prompt::confirm("Add notes in $EDITOR? (y/N)")
+                .default(false)
+                .ask(screen)?
+        {
+            edit::ask_notes(item, screen);
+        }
+    }
+
+    Some(items)
+}
+
+fn check_todo(todos: &mut Todos, screen: &mut Screen) {
+    let indices = get_operation_indices(todos, screen);
+    screen.clear();
+    if let Some(indices) = indices {
+        if let Err(e) = archive::toggle(todos, &indices) {
+            writeln!(screen, "{}\n", e);
+        }
+    }
+}
+
+fn remove_todo(todos: &mut Todos, screen: &mut Screen) {
+    let indices = get_operation_indices(todos, screen).filter(|indices| {
+        let question = match indices[..] {
+            [index] => format!("\nRemove \"{}\"? (y/N)", todos[index].name),
+            _ => format!("\nRemove these {} items? (y/N)", indices.len()),
+        };
+        Prompt::confirm(question).default(false).ask(screen) == Some(true)
+    });
+    screen.clear();
+    if let Some(indices) = indices {
+        if let Err(e) = trash::remove(todos, &indices) {
+            writeln!(screen, "{}\n", e);
+        }
+    }
+}
+
+fn move_item(todos: &mut Todos, index: usize, to: usize) -> Result<(), String> {
+    check_index(todos, index)?;
+    history::perform(todos, Operation::move_to(todos, index, to))
+}
+
+fn move_todo(todos: &mut Todos, screen: &mut Screen) {
+    let Some(from) = get_operation_index(todos, screen) else {
+        screen.clear();
+        return;
+    };
+    let to = Prompt::new("Move it where? (index, top or bottom)", |line| match line {
+        "top" | "t" => Ok(0),
+        "bottom" | "b" => Ok(usize::MAX),
+        _ => line.parse().map_err(|_| "Invalid input".to_string()),
+    })
+    .ask(screen);
+    screen.clear();
+    if let Some(to) = to {
+        record(todos, Operation::move_to(todos, from, to), screen);
+    }
+}
+
+/// The items `todo check` checks off: those at `indices`, or every open one.
+/// `--all` is the global flag for listing items that haven't started yet,
+/// taken without indices to mean all of them, as `check --all` reads.
+fn to_check(todos: &Todos, indices: &[String], every: bool) -> Result<Vec<usize>, String> {
+    match every {
+        true => Ok((0..todos.len()).filter(|&i| !todos[i].completed).collect()),
+        false => parse_indices(&indices.join(" "), todos.len()),
+    }
+}
+
+/// Makes a change at the prompt, recorded in the history to undo. A failure
+/// is shown rather than ending the session, so the user can fix it and try
+/// again.
+pub(crate) fn record(todos: &mut Todos, operation: Operation, screen: &mut Screen) {
+    if let Err(e) = history::perform(todos, operation) {
+        writeln!(screen, "{}\n", e);
+    }
+}
+
+fn report(result: Result<String, String>, screen: &mut Screen) {
+    match result {
+        Ok(message) | Err(message) => writeln!(screen, "{}\n", message),
+    }
+}
+
+/// Asks for the index of an item, or returns `None` if there are none or
+/// the user cancels.
+pub(crate) fn get_operation_index(todos: &Todos, screen: &mut Screen) -> Option<usize> {
+    if todos.is_empty() {
+        return None;
+    }
+
+    print_indexed(screen, todos);
+    Prompt::index("Which one? (cancel to stop)", todos.len()).ask(screen)
+}
+
+/// Like [`get_operation_index`], but takes several, like `1, 3, 5-7`.
+fn get_operation_indices(todos: &Todos, screen: &mut Screen) -> Option<Vec<usize>> {
+    if todos.is_empty() {
+        return None;
+    }
+
+    print_indexed(screen, todos);
+    Prompt::indices("Which ones? (e.g. 1, 3-5, or cancel to stop)", todos.len()).ask(screen)
+}
+
+/// The whole list with indices, to pick from.
+fn print_indexed(screen: &mut Screen, todos: &Todos) {
+    let view = View {
+        show_all: true,
+        sort: SortOrder::Manual,
+        tag: None,
+        heatmap: false,
+        redact: false,
+    };
+    print_todo(screen, todos, true, &view);
+}
+
+fn filter_todo(view: &mut View, screen: &mut Screen) {
+    let prompt = Prompt::text("Which tag? (empty to show everything)").default(String::new());
+    if let Some(tag) = prompt.ask(screen).map(|tag| tags::normalize(&tag)) {
+        view.tag = if tag.is_empty() { None } else { Some(tag) };
+    }
+    screen.clear();
+}
+
+pub(crate) fn print_todo(screen: &mut Screen, todos: &Todos, show_index: bool, view: &View) {
+    if todos.is_empty() {
+        writeln!(screen, "[Empty Todo List]");
+    }
+    if let Some(tag) = &view.tag {
+        let tag = match view.redact {
+            true => render::redact(tag),
+            false => tag.clone(),
+        };
+        writeln!(screen, "[Showing +{}]", tag);
+    }
+
+    let now = clock::now();
+    let index_width = todos.len().saturating_sub(1).to_string().len();
+    let mut deferred = 0;
+    let mut someday = 0;
+    let mut shown = Vec::new();
+    for i in priority::display_order(todos, &view.sort) {
+        let item = &todos[i];
+        if let Some(tag) = &view.tag {
+            if !item.tags.contains(tag) {
+                continue;
+            }
+        }
+        if !view.show_all && (!defer::is_started(item) || defer::is_snoozed(item, now)) {
+            deferred += 1;
+            continue;
+        }
+        if !view.show_all && someday::is_someday(item) {
+            someday += 1;
+            continue;
+        }
+
+        let mut indent = 0;
+        if show_index {
+            indent += index_width + 1;
+        }
+        if item.subtask {
+            indent += 2;
+        }
+        shown.push((i, indent));
+    }
+
+    let rows: Vec<(usize, &Item)> = shown
+        .iter()
+        .map(|&(i, indent)| (indent, &todos[i]))
+        .collect();
+    let layout = render::layout(screen.width(), &rows, view.redact);
+    for (i, indent) in shown {
+        let item = &todos[i];
+        if show_index {
+            // Lined up however many items there are.
+            write!(screen, "{:>width$} ", i, width = index_width);
+        }
+        if item.subtask {
+            write!(screen, "  ");
+        }
+        let mut style = render::style(item, now);
+        if let Some(created) = item.created.filter(|_| view.heatmap && !item.completed) {
+            style.color = Some(heatmap::color(created, now));
+        }
+        render::write_item(screen, item, indent, style, view.redact, layout);
+    }
+    if deferred > 0 {
+        writeln!(screen, "[{} deferred, run with --all to show]", deferred);
+    }
+    if someday > 0 {
+        writeln!(
+            screen,
+            "[{} for someday, run `todo review` to go through them]",
+            someday
+        );
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn config_defaults_come_before_the_flags_given() {
+        let config: Config = toml::from_str(
+            "[defaults.list]\nformat = \"tsv\"\n[defaults.add]\nlist = \"inbox\"\ntag = [\"a\", \"b\"]\n",
+        )
+        .unwrap();
+        let with_config = |line: &str| -> Vec<String> {
+            let args = line.split(' ').map(OsString::from).collect();
+            with_defaults(args, &config)
+                .unwrap()
+                .into_iter()
+                .map(|arg| arg.into_string().unwrap())
+                .collect()
+        };
+
+        assert_eq!(
+            with_config("todo --sort priority ls --all"),
+            ["todo", "--sort", "priority", "ls", "--format", "tsv", "--all"]
+        );
+        assert_eq!(
+            with_config("todo list --format=json"),
+            ["todo", "list", "--format=json"]
+        );
+        assert_eq!(
+            with_config("todo add milk"),
+            ["todo", "add", "--list", "inbox", "--tag", "a", "--tag", "b", "milk"]
+        );
+        assert_eq!(
+            with_config("todo --list home add milk"),
+            ["todo", "--list", "home", "add", "--tag", "a", "--tag", "b", "milk"]
+        );
+        assert_eq!(with_config("todo"), ["todo"]);
+
+        let config: Config = toml::from_str("[defaults.add]\ncolour = true\n").unwrap();
+        assert!(with_defaults(vec!["todo".into(), "add".into()], &config).is_err());
+    }
+
+    #[test]
+    fn check_takes_ranges_or_all() {
+        let todos = sample();
+        let parse = |line: &str| match Cli::try_parse_from(line.split(' ')) {
+            Ok(Cli {
+                command: Some(CliCommand::Check { indices, yes }),
+                all,
+                ..
+            }) => Ok((to_check(&todos, &indices, all && indices.is_empty())?, yes)),
+            Ok(_) => Err("not a check".to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        assert_eq!(parse("todo check 0 2-3"), Ok((vec![0, 2, 3], false)));
+        assert_eq!(parse("todo c 4, 1-2"), Ok((vec![1, 2, 4], false)));
+        assert_eq!(
+            parse("todo check --all --yes"),
+            Ok((vec![0, 1, 2, 4], true))
+        );
+        assert_eq!(parse("todo --all check -y"), Ok((vec![0, 1, 2, 4], true)));
+        assert_eq!(parse("todo check --all 3"), Ok((vec![3], false)));
+        assert!(parse("todo check 3-1").is_err());
+    }
+
+    fn view(sort: SortOrder, show_all: bool, tag: Option<&str>) -> View {
+        View {
+            show_all,
+            sort,
+            tag: tag.map(str::to_string),
+            heatmap: false,
+            redact: false,
+        }
+    }
+
+    fn render(todos: &Todos, show_index: bool, view: &View) -> String {
+        let mut screen = Screen::plain();
+        print_todo(&mut screen, todos, show_index, view);
+        screen.contents().to_string()
+    }
+
+    #[test]
+    fn list() {
+        let view = view(SortOrder::Manual, false, None);
+        insta::assert_snapshot!(render(&sample(), false, &view));
+    }
+
+    #[test]
+    fn list_with_indices() {
+        let view = view(SortOrder::Manual, false, None);
+        insta::assert_snapshot!(render(&sample(), true, &view));
+    }
+
+    #[test]
+    fn list_all_by_priority() {
+        let view = view(SortOrder::Priority, true, None);
+        insta::assert_snapshot!(render(&sample(), true, &view));
+    }
+
+    #[test]
+    fn list_by_tag() {
+        let view = view(SortOrder::Manual, false, Some("work"));
+        insta::assert_snapshot!(render(&sample(), true, &view));
+    }
+
+    #[test]
+    fn empty_list() {
+        let view = view(SortOrder::Manual, false, None);
+        insta::assert_snapshot!(render(&Vec::new(), true, &view));
+    }
+}
@@ -0,0 +1,208 @@
+//! Completed items don't have to stay on the list: `todo archive` moves
+//! them to `<list>.done.json` next to it, stamped with when they were
+//! archived, and `todo done` looks through what's there. With
+//! `TODO_AUTO_ARCHIVE=1`, checking an item off archives it straight away,
+//! so the stamp is when it was done.
+//!
+//! Archiving can be undone like any change to the list, which puts the
+//! items back; the archive keeps its copy.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::history::{self, Operation};
+use crate::screen::Screen;
+use crate::{clock, config, search, store, subtasks, Item, Todos};
+
+/// An archived item.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Done {
+    pub completed: DateTime<Local>,
+    pub item: Item,
+}
+
+fn path() -> PathBuf {
+    store::sibling("done.json")
+}
+
+/// Everything archived from the list, oldest first.
+pub fn read() -> Result<Vec<Done>, String> {
+    let path = path();
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("{} couldn't be read: {}", path.display(), e)),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn append(items: Vec<Item>, now: DateTime<Local>) -> Result<(), String> {
+    let mut done = read()?;
+    done.extend(items.into_iter().map(|item| Done {
+        completed: now,
+        item,
+    }));
+    let path = path();
+    std::fs::write(&path, serde_json::to_string_pretty(&done).unwrap())
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// The items to archive, last first so removing one leaves the indices of
+/// the others alone. A parent waits for its open subtasks and takes its
+/// completed ones along.
+fn archivable(todos: &Todos) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..todos.len())
+        .filter(|&i| todos[i].completed)
+        .filter(|&i| match subtasks::parent(todos, i) {
+            Some(parent) => !(todos[parent].completed && archivable_parent(todos, parent)),
+            None => archivable_parent(todos, i),
+        })
+        .collect();
+    indices.reverse();
+    indices
+}
+
+fn archivable_parent(todos: &Todos, index: usize) -> bool {
+    subtasks::children(todos, index).all(|i| todos[i].completed)
+}
+
+/// Moves the completed items to the archive after `first`, if given, as a
+/// single change to undo. Returns how many were archived.
+fn archive_after(todos: &mut Todos, first: Option<Operation>) -> Result<usize, String> {
+    let mut after = todos.clone();
+    if let Some(first) = &first {
+        first.apply(&mut after)?;
+    }
+
+    let mut items = Vec::new();
+    let mut operations: Vec<Operation> = first.into_iter().collect();
+    for index in archivable(&after) {
+        let removal = Operation::remove(&after, index);
+        let removed = std::iter::once(index).chain(subtasks::children(&after, index));
+        items.splice(0..0, removed.map(|i| after[i].clone()));
+        removal.apply(&mut after)?;
+        operations.push(removal);
+    }
+    let count = items.len();
+
+    if !operations.is_empty() {
+        history::perform(todos, Operation::Group { operations })?;
+    }
+    if !items.is_empty() {
+        append(items, clock::now())?;
+    }
+    Ok(count)
+}
+
+/// Moves the completed items to the archive. Returns how many there were.
+pub fn archive_completed(todos: &mut Todos) -> Result<usize, String> {
+    archive_after(todos, None)
+}
+
+/// Moves the completed items to the archive. Returns what to tell the user.
+pub fn archive(todos: &mut Todos) -> Result<String, String> {
+    Ok(match archive_completed(todos)? {
+        0 => "Nothing to archive".to_string(),
+        1 => "Archived 1 item".to_string(),
+        count => format!("Archived {} items", count),
+    })
+}
+
+/// Whether checked off items are archived right away, as
+/// `TODO_AUTO_ARCHIVE` says, or else `auto_archive` in the config.
+pub fn auto() -> bool {
+    match std::env::var("TODO_AUTO_ARCHIVE") {
+        Ok(value) => !["", "0", "false"].contains(&&*value),
+        Err(_) => config::get().auto_archive == Some(true),
+    }
+}
+
+/// Checks or unchecks items, archiving what's completed afterwards when
+/// `TODO_AUTO_ARCHIVE` is set.
+pub fn toggle(todos: &mut Todos, indices: &[usize]) -> Result<(), String> {
+    let operation = Operation::toggle_many(todos, indices);
+    match auto() {
+        true => archive_after(todos, Some(operation)).map(|_| ()),
+        false => history::perform(todos, operation),
+    }
+}
+
+/// The archive, newest first under the day each was archived, narrowed
+/// down to the names matching `query` if there is one.
+pub fn print_done(screen: &mut Screen, done: &[Done], query: Option<&str>) {
+    let items: Todos = done.iter().map(|done| done.item.clone()).collect();
+    let mut matches = match query {
+        Some(query) => search::find(&items, query, false),
+        None => (0..done.len()).collect(),
+    };
+    if matches.is_empty() {
+        writeln!(
+            screen,
+            "[Nothing archived{}]\n",
+            match query {
+                Some(_) => " matches",
+                None => " yet, run `todo archive` to move completed items here",
+            }
+        );
+        return;
+    }
+
+    matches.sort_by_key(|&i| std::cmp::Reverse(done[i].completed));
+    let mut day = None;
+    for &i in &matches {
+        let date = done[i].completed.date_naive();
+        if day != Some(date) {
+            writeln!(screen, "{}", date);
+            day = Some(date);
+        }
+        writeln!(screen, "  {}", done[i].item);
+    }
+    writeln!(screen, "\n{} done\n", matches.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn parents_wait_for_their_subtasks() {
+        let mut todos = sample();
+        for item in &mut todos[2..4] {
+            item.subtask = true;
+        }
+        todos[0].completed = true;
+        todos[1].completed = true;
+        todos[2].completed = true;
+        todos[3].completed = false;
+        assert_eq!(archivable(&todos), [2, 0]);
+
+        todos[3].completed = true;
+        assert_eq!(archivable(&todos), [1, 0]);
+    }
+
+    #[test]
+    fn done() {
+        let day = Local.with_ymd_and_hms(2024, 5, 6, 18, 0, 0).unwrap();
+        let done: Vec<Done> = sample()
+            .into_iter()
+            .take(3)
+            .enumerate()
+            .map(|(i, mut item)| {
+                item.completed = true;
+                Done {
+                    completed: day - Duration::hours(10 * i as i64),
+                    item,
+                }
+            })
+            .collect();
+
+        let mut screen = Screen::plain();
+        print_done(&mut screen, &done, None);
+        print_done(&mut screen, &done, Some("milk"));
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,106 @@
+//! Saving in the background for the interactive prompt and `todo tui`, so
+//! a change never waits on the disk, or on a list kept on a slow network
+//! drive. Each change sends a copy of the list to a thread that saves the
+//! latest copy it has, so a burst of changes is saved once.
+//!
+//! Before the list is read again, and before the program exits, `wait`
+//! makes sure everything sent has been saved. Exits go through `exit` to
+//! do that.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Condvar, Mutex, OnceLock};
+
+use crate::{write_to_file, Todos};
+
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Saving,
+    Saved,
+    Failed(String),
+}
+
+/// How far saving has got, counted in copies of the list sent.
+struct Progress {
+    sent: u64,
+    saved: u64,
+    /// Why the last save failed, until it's reported.
+    error: Option<String>,
+}
+
+static PROGRESS: Mutex<Progress> = Mutex::new(Progress {
+    sent: 0,
+    saved: 0,
+    error: None,
+});
+static SAVED: Condvar = Condvar::new();
+
+fn saver() -> &'static Mutex<Sender<(u64, Todos)>> {
+    static SENDER: OnceLock<Mutex<Sender<(u64, Todos)>>> = OnceLock::new();
+    SENDER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || save_sent(receiver));
+        Mutex::new(sender)
+    })
+}
+
+fn save_sent(receiver: Receiver<(u64, Todos)>) {
+    while let Ok(mut latest) = receiver.recv() {
+        // Only the newest copy is worth saving.
+        while let Ok(newer) = receiver.try_recv() {
+            latest = newer;
+        }
+        let (number, todos) = latest;
+        let result = write_to_file(&todos);
+
+        let mut progress = PROGRESS.lock().unwrap();
+        progress.saved = number;
+        if let Err(e) = result {
+            progress.error = Some(e);
+        }
+        SAVED.notify_all();
+    }
+}
+
+/// Saves `todos` in the background.
+pub fn save(todos: &Todos) {
+    let mut progress = PROGRESS.lock().unwrap();
+    progress.sent += 1;
+    let number = progress.sent;
+    drop(progress);
+    // The thread only stops with the program.
+    let _ = saver().lock().unwrap().send((number, todos.clone()));
+}
+
+/// Whether what was sent has been saved yet. A failure is reported once.
+#[cfg(feature = "tui")]
+pub fn status() -> Status {
+    let mut progress = PROGRESS.lock().unwrap();
+    match progress.error.take() {
+        Some(e) => Status::Failed(e),
+        None if progress.saved < progress.sent => Status::Saving,
+        None => Status::Saved,
+    }
+}
+
+/// Waits until everything sent has been saved, returning why the last save
+/// that failed did, if one did since the last time it was reported.
+pub fn wait() -> Result<(), String> {
+    let progress = PROGRESS.lock().unwrap();
+    let mut progress = SAVED
+        .wait_while(progress, |progress| progress.saved < progress.sent)
+        .unwrap();
+    match progress.error.take() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Exits with `code` once everything sent has been saved.
+pub fn exit(code: i32) -> ! {
+    if let Err(e) = wait() {
+        eprintln!("That change wasn't saved: {}", e);
+        std::process::exit(1);
+    }
+    std::process::exit(code)
+}
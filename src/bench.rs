@@ -0,0 +1,36 @@
+//! Entry points for the benchmarks in `benches/`, for what isn't public:
+//! searching, sorting and listing.
+//!
+//! ```sh
+//! cargo bench --features bench
+//! ```
+
+use crate::app::{self, View};
+use crate::priority::{self, SortOrder};
+use crate::screen::Screen;
+use crate::{search, Todos};
+
+/// The items matching `query`, as `todo search` finds them.
+pub fn search(todos: &Todos, query: &str, fuzzy: bool) -> Vec<usize> {
+    search::find(todos, query, fuzzy)
+}
+
+/// The items in the order `sort` gives, like `due asc, priority desc`.
+pub fn sort(todos: &Todos, sort: &str) -> Vec<usize> {
+    let sort: SortOrder = sort.parse().unwrap();
+    priority::display_order(todos, &sort)
+}
+
+/// The list as `todo list` shows it, without colors.
+pub fn list(todos: &Todos) -> String {
+    let view = View {
+        show_all: false,
+        sort: SortOrder::default(),
+        tag: None,
+        heatmap: false,
+        redact: false,
+    };
+    let mut screen = Screen::plain();
+    app::print_todo(&mut screen, todos, true, &view);
+    screen.contents().to_string()
+}
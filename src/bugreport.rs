@@ -0,0 +1,163 @@
+//! Reports for the maintainers when something goes wrong. If todo-rs
+//! crashes, it writes one to `reports` in its data directory and says
+//! where; `todo bugreport` writes one on demand.
+//!
+//! A report never leaves the machine by itself, it's there to attach to
+//! an issue if you choose to. It says which version and platform this is,
+//! which command ran, the crash and its backtrace, the config with every
+//! text value masked, and the kinds of the last changes made to the list,
+//! but nothing about the items themselves.
+
+use std::backtrace::Backtrace;
+use std::fmt::Write;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Local;
+
+use crate::{config, history, render, store};
+
+/// How many of the last changes a report lists.
+const CHANGES: usize = 20;
+
+/// The command being run, as far as a report tells.
+static COMMAND: Mutex<Option<String>> = Mutex::new(None);
+
+/// Notes the name of the command being run, without what was given with
+/// it.
+pub fn note_command(name: &str) {
+    if let Ok(mut noted) = COMMAND.lock() {
+        *noted = Some(name.to_string());
+    }
+}
+
+/// Writes a report whenever todo-rs panics, after the usual message.
+pub fn install() {
+    let default = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default(info);
+        match write(Some(info)) {
+            Ok(path) => eprintln!(
+                "\ntodo-rs crashed. A report for the maintainers is at {}\n\
+                 Please look it over and attach it to an issue at {}",
+                path.display(),
+                env!("CARGO_PKG_REPOSITORY")
+            ),
+            Err(e) => eprintln!("\ntodo-rs crashed, and couldn't write a report: {}", e),
+        }
+    }));
+}
+
+/// Writes a report, about `panic` if there was one, returning where.
+pub fn write(panic: Option<&PanicHookInfo>) -> Result<PathBuf, String> {
+    let dir = dirs::data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(store::APP_DIR)
+        .join("reports");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let path = dir.join(format!(
+        "bugreport-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, report(panic))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn report(panic: Option<&PanicHookInfo>) -> String {
+    let mut report = String::new();
+    let features: Vec<&str> = [
+        (cfg!(feature = "lsp"), "lsp"),
+        (cfg!(feature = "tui"), "tui"),
+        (cfg!(feature = "sqlite"), "sqlite"),
+    ]
+    .into_iter()
+    .filter_map(|(on, feature)| on.then_some(feature))
+    .collect();
+    let command = COMMAND.lock().ok().and_then(|command| command.clone());
+
+    let _ = writeln!(report, "todo-rs {} bug report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(
+        report,
+        "Written   {}",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    let _ = writeln!(
+        report,
+        "Platform  {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(report, "Features  {}", features.join(" "));
+    let _ = writeln!(report, "Command   {}", command.as_deref().unwrap_or("none"));
+    if store::is_initialized() {
+        let _ = writeln!(report, "Backend   {}", store::backend());
+    }
+
+    if let Some(panic) = panic {
+        let message = match (
+            panic.payload().downcast_ref::<&str>(),
+            panic.payload().downcast_ref::<String>(),
+        ) {
+            (Some(message), _) => message.to_string(),
+            (_, Some(message)) => message.clone(),
+            _ => "(no message)".to_string(),
+        };
+        let _ = writeln!(report, "\nCrashed\n\n{}", message);
+        if let Some(location) = panic.location() {
+            let _ = writeln!(report, "at {}", location);
+        }
+        let _ = writeln!(report, "\n{}", Backtrace::force_capture());
+    }
+
+    let _ = writeln!(report, "\nConfig, masked\n");
+    let _ = writeln!(report, "{}", masked_config());
+
+    if store::is_initialized() {
+        let _ = writeln!(report, "Last changes to the list\n");
+        for change in history::recent(CHANGES) {
+            let _ = writeln!(report, "{}", change);
+        }
+    }
+    report
+}
+
+/// The config file with every text in it masked, or why there isn't one.
+fn masked_config() -> String {
+    fn mask(value: &mut toml::Value) {
+        match value {
+            toml::Value::String(text) => *text = render::redact(text),
+            toml::Value::Array(values) => values.iter_mut().for_each(mask),
+            toml::Value::Table(table) => table.iter_mut().for_each(|(_, value)| mask(value)),
+            _ => {}
+        }
+    }
+
+    let Some(path) = config::path().filter(|path| path.exists()) else {
+        return "(no config file)\n".to_string();
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => return format!("(couldn't be read: {})\n", e),
+    };
+    match content.parse::<toml::Value>() {
+        Ok(mut config) => {
+            mask(&mut config);
+            toml::to_string(&config).unwrap_or_default()
+        }
+        Err(_) => "(not valid TOML)\n".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_name_the_command() {
+        note_command("add");
+        assert!(report(None).contains("\nCommand   add\n"));
+    }
+}
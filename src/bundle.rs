@@ -0,0 +1,198 @@
+//! `todo export-all` and `todo import-all`, to move everything todo-rs
+//! keeps to another machine in one file: every list with its archive,
+//! trash, history, backups and sync state, and the config.
+//!
+//! The bundle is a tarball made with `tar`, compressed as its name says,
+//! like `backup.tar.zst` or `backup.tar.gz`. In it, `lists` holds the
+//! lists' files and `config` the config directory.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{config, store};
+
+const LISTS: &str = "lists";
+const CONFIG: &str = "config";
+/// What the config file is called in a bundle, whatever it's called here.
+const CONFIG_FILE: &str = "config.toml";
+
+fn lists_dir() -> PathBuf {
+    match store::path().parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// The config file, and the directory holding it when it's the usual one,
+/// which has the rest of the config in it too.
+fn config_places() -> (Option<PathBuf>, Option<PathBuf>) {
+    let file = config::path();
+    let dir = match std::env::var_os("TODO_CONFIG") {
+        Some(_) => None,
+        None => file
+            .as_ref()
+            .and_then(|file| file.parent())
+            .map(Path::to_path_buf),
+    };
+    (file, dir)
+}
+
+/// Every file under `path`, or `path` itself if it's a file.
+fn files(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries =
+        std::fs::read_dir(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut found = Vec::new();
+    for entry in entries.flatten() {
+        found.extend(files(&entry.path())?);
+    }
+    Ok(found)
+}
+
+/// What goes in a bundle: each file here and where it goes in the bundle.
+fn contents() -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let mut contents = Vec::new();
+    let dir = lists_dir();
+    let names = store::list_names();
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Could not read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // A list's own file and its siblings, but not the lock held now.
+        let belongs = names
+            .iter()
+            .any(|list| name.starts_with(&format!("{}.", list)));
+        if !belongs || name.ends_with(".lock") {
+            continue;
+        }
+        for file in files(&entry.path())? {
+            let inside = file.strip_prefix(&dir).unwrap_or(&file);
+            contents.push((file.clone(), Path::new(LISTS).join(inside)));
+        }
+    }
+
+    let (config_file, config_dir) = config_places();
+    if let Some(file) = config_file.filter(|file| file.is_file()) {
+        contents.push((file, Path::new(CONFIG).join(CONFIG_FILE)));
+    }
+    if let Some(dir) = config_dir.filter(|dir| dir.is_dir()) {
+        for file in files(&dir)? {
+            let inside = file.strip_prefix(&dir).unwrap_or(&file);
+            if inside != Path::new(CONFIG_FILE) {
+                contents.push((file.clone(), Path::new(CONFIG).join(inside)));
+            }
+        }
+    }
+    Ok(contents)
+}
+
+/// Where a file from a bundle goes here, if anywhere.
+fn destination(inside: &Path) -> Option<PathBuf> {
+    if let Ok(rest) = inside.strip_prefix(LISTS) {
+        return Some(lists_dir().join(rest));
+    }
+    let rest = inside.strip_prefix(CONFIG).ok()?;
+    let (config_file, config_dir) = config_places();
+    match rest == Path::new(CONFIG_FILE) {
+        true => config_file,
+        false => config_dir.map(|dir| dir.join(rest)),
+    }
+}
+
+/// A directory of its own to put a bundle together or take it apart in.
+fn staging() -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir().join(format!("todo-bundle-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+fn copy(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    }
+    std::fs::copy(from, to).map(|_| ()).map_err(|e| {
+        format!(
+            "Could not copy {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    })
+}
+
+fn run(mut tar: Command) -> Result<(), String> {
+    let status = tar
+        .status()
+        .map_err(|e| format!("Could not run tar: {}", e))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err("tar failed".to_string()),
+    }
+}
+
+/// Writes everything to `bundle`, returning how many files went in.
+pub fn export(bundle: &Path) -> Result<usize, String> {
+    let contents = contents()?;
+    if contents.is_empty() {
+        return Err("There's nothing to bundle yet".to_string());
+    }
+    let dir = staging()?;
+    let result = contents
+        .iter()
+        .try_for_each(|(file, inside)| copy(file, &dir.join(inside)))
+        .and_then(|()| {
+            // -a compresses as the bundle's name says.
+            let mut tar = Command::new("tar");
+            tar.arg("-caf").arg(bundle).arg("-C").arg(&dir);
+            tar.args(
+                [LISTS, CONFIG]
+                    .iter()
+                    .filter(|part| dir.join(part).exists()),
+            );
+            run(tar)
+        });
+    let _ = std::fs::remove_dir_all(&dir);
+    result.map(|()| contents.len())
+}
+
+/// Puts everything in `bundle` in place, returning how many files it had.
+/// Files already here are only replaced with `overwrite`.
+pub fn import(bundle: &Path, overwrite: bool) -> Result<usize, String> {
+    if !bundle.is_file() {
+        return Err(format!("There's no bundle at {}", bundle.display()));
+    }
+    let dir = staging()?;
+    let mut tar = Command::new("tar");
+    tar.arg("-xf").arg(bundle).arg("-C").arg(&dir);
+    let result = run(tar).and_then(|()| files(&dir)).and_then(|files| {
+        let placed: Vec<(PathBuf, PathBuf)> = files
+            .into_iter()
+            .filter_map(|file| {
+                let inside = file.strip_prefix(&dir).ok()?.to_path_buf();
+                Some((file, destination(&inside)?))
+            })
+            .collect();
+        let existing: Vec<String> = placed
+            .iter()
+            .filter(|(_, to)| to.exists())
+            .map(|(_, to)| to.display().to_string())
+            .collect();
+        if !overwrite && !existing.is_empty() {
+            return Err(format!(
+                "These are here already, pass --overwrite to replace them:\n  {}",
+                existing.join("\n  ")
+            ));
+        }
+        for (from, to) in &placed {
+            copy(from, to)?;
+        }
+        Ok(placed.len())
+    });
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
@@ -0,0 +1,149 @@
+//! Keeping an eye on late nights. `todo report late-nights` shows when in
+//! the day items get checked off over the last four weeks, and says so when
+//! late nights have become a habit.
+//!
+//! With `late_after = 22` in the config, checking an item off after 22:00,
+//! or before 5 in the morning, brings a gentle note too.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Timelike};
+
+use crate::config;
+use crate::screen::Screen;
+
+/// Where the night ends, as an hour of the morning.
+const EARLY: u32 = 5;
+/// Where the night starts when the config doesn't say.
+const LATE: u32 = 22;
+/// How far back the report looks.
+const DAYS: i64 = 28;
+/// Nights in the last week with something done late that make a habit.
+const HABIT: usize = 3;
+
+/// The hour the night starts at.
+pub fn cutoff() -> u32 {
+    config::get().late_after.unwrap_or(LATE)
+}
+
+/// Whether `hour` is in the night starting at `cutoff`.
+fn is_late(hour: u32, cutoff: u32) -> bool {
+    match cutoff >= EARLY {
+        true => hour >= cutoff || hour < EARLY,
+        false => (cutoff..EARLY).contains(&hour),
+    }
+}
+
+/// The evening a night started on, so 1am counts towards the day before.
+fn night(time: DateTime<Local>) -> NaiveDate {
+    (time - Duration::hours(EARLY.into())).date_naive()
+}
+
+/// The note for checking something off at `now`, if it's late and the
+/// config asks for one.
+pub fn warning(now: DateTime<Local>) -> Option<String> {
+    let cutoff = config::get().late_after?;
+    is_late(now.hour(), cutoff).then(|| {
+        format!(
+            "🌙 Done after {}:00. The rest will keep until tomorrow.",
+            cutoff
+        )
+    })
+}
+
+/// When items were checked off in the last four weeks, by hour, and how
+/// many nights had some done after `cutoff`.
+pub fn print_late_nights(
+    screen: &mut Screen,
+    completed: &[DateTime<Local>],
+    cutoff: u32,
+    now: DateTime<Local>,
+) {
+    let recent: Vec<DateTime<Local>> = completed
+        .iter()
+        .copied()
+        .filter(|&time| time <= now && now - time < Duration::days(DAYS))
+        .collect();
+    if recent.is_empty() {
+        writeln!(screen, "[Nothing checked off in the last {} days]\n", DAYS);
+        return;
+    }
+
+    let mut hours = [0_usize; 24];
+    for time in &recent {
+        hours[time.hour() as usize] += 1;
+    }
+    let most = *hours.iter().max().unwrap();
+    let first = hours.iter().position(|&count| count > 0).unwrap();
+    let last = hours.iter().rposition(|&count| count > 0).unwrap();
+    writeln!(screen, "Checked off by hour, last {} days\n", DAYS);
+    for (hour, &count) in hours.iter().enumerate().take(last + 1).skip(first) {
+        let bar = "█".repeat((count * 30).div_ceil(most));
+        let late = if is_late(hour as u32, cutoff) {
+            " late"
+        } else {
+            ""
+        };
+        writeln!(screen, "  {:02} {:<30} {}{}", hour, bar, count, late);
+    }
+
+    let mut nights: Vec<NaiveDate> = recent
+        .iter()
+        .filter(|time| is_late(time.hour(), cutoff))
+        .map(|&time| night(time))
+        .collect();
+    nights.sort();
+    nights.dedup();
+    let this_week = nights
+        .iter()
+        .filter(|&&date| night(now) - date < Duration::days(7))
+        .count();
+    writeln!(
+        screen,
+        "\nDone after {}:00 on {} of the last {} nights, {} of the last 7.",
+        cutoff,
+        nights.len(),
+        DAYS,
+        this_week
+    );
+    if this_week >= HABIT {
+        writeln!(
+            screen,
+            "That's becoming a habit. Stopping earlier is part of keeping going."
+        );
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn late_nights() {
+        let at = |day, hour| Local.with_ymd_and_hms(2024, 5, day, hour, 30, 0).unwrap();
+        let now = at(10, 12);
+        let completed = [
+            at(6, 23),
+            at(8, 1),
+            at(8, 9),
+            at(8, 10),
+            at(9, 22),
+            at(9, 23),
+            // Too long ago to count.
+            at(1, 23) - Duration::days(DAYS),
+        ];
+
+        let mut screen = Screen::plain();
+        print_late_nights(&mut screen, &completed, 22, now);
+        print_late_nights(&mut screen, &completed[2..4], 22, now);
+        print_late_nights(&mut screen, &[], 22, now);
+        insta::assert_snapshot!(screen.contents());
+    }
+
+    #[test]
+    fn nights() {
+        assert!(is_late(23, 22) && is_late(2, 22) && !is_late(5, 22));
+        assert!(is_late(1, 1) && !is_late(23, 1));
+    }
+}
@@ -0,0 +1,130 @@
+//! A small reward for finishing: when the last open item of the list, or
+//! of today's picks, is checked off, a message is shown.
+//!
+//! An executable `celebrate` in the config directory
+//! (`~/.config/todo-rs/celebrate` on Linux) runs instead, with `list` or
+//! `day` as its argument and the list's name in `TODO_LIST`, so it can
+//! send a notification, play a sound or do nothing at all.
+//!
+//! Checking items off late at night can bring a gentle note too, see
+//! `burnout`.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use chrono::NaiveDate;
+
+use crate::{burnout, clock, store, Item, Todos};
+
+/// How far along the list and today's picks are, to tell when they get
+/// finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    list_done: bool,
+    day_done: bool,
+    completed: usize,
+}
+
+impl Progress {
+    pub fn of(todos: &Todos, today: NaiveDate) -> Self {
+        let done = |items: Vec<&Item>| !items.is_empty() && items.iter().all(|item| item.completed);
+
+        Progress {
+            list_done: done(todos.iter().collect()),
+            day_done: done(
+                todos
+                    .iter()
+                    .filter(|item| item.my_day == Some(today))
+                    .collect(),
+            ),
+            completed: todos.iter().filter(|item| item.completed).count(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Occasion {
+    List,
+    Day,
+}
+
+/// What got finished between two points, the whole list taking precedence
+/// over the day.
+fn finished(before: Progress, after: Progress) -> Option<Occasion> {
+    if after.list_done && !before.list_done {
+        Some(Occasion::List)
+    } else if after.day_done && !before.day_done {
+        Some(Occasion::Day)
+    } else {
+        None
+    }
+}
+
+/// Celebrates if the list or today's picks were finished since `before`,
+/// and notes it if that was late. Returns the message to show, if there is
+/// one.
+pub fn celebrate(before: Progress, todos: &Todos, today: NaiveDate) -> Option<String> {
+    let after = Progress::of(todos, today);
+    let late = match after.completed > before.completed {
+        true => burnout::warning(clock::now()),
+        false => None,
+    };
+    match (finished(before, after).and_then(cheer), late) {
+        (Some(cheer), Some(late)) => Some(format!("{}\n{}", cheer, late)),
+        (cheer, late) => cheer.or(late),
+    }
+}
+
+fn cheer(occasion: Occasion) -> Option<String> {
+    let list = store::list_name();
+
+    match hook() {
+        Some(hook) => {
+            let argument = match occasion {
+                Occasion::List => "list",
+                Occasion::Day => "day",
+            };
+            match Command::new(&hook)
+                .arg(argument)
+                .env("TODO_LIST", &list)
+                .status()
+            {
+                Ok(_) => None,
+                Err(e) => Some(format!("Could not run {}: {}", hook.display(), e)),
+            }
+        }
+        None => Some(match occasion {
+            Occasion::List => format!("🎉 Everything on {} is done!", list),
+            Occasion::Day => "🎉 Everything picked for today is done!".to_string(),
+        }),
+    }
+}
+
+fn hook() -> Option<PathBuf> {
+    let path = dirs::config_dir()?.join("todo-rs").join("celebrate");
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn finishing_is_noticed_once() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let mut todos = sample();
+        let start = Progress::of(&todos, today);
+
+        todos[4].completed = true;
+        let day_done = Progress::of(&todos, today);
+        assert_eq!(finished(start, day_done), Some(Occasion::Day));
+
+        for item in todos.iter_mut() {
+            item.completed = true;
+        }
+        let all_done = Progress::of(&todos, today);
+        assert_eq!(finished(day_done, all_done), Some(Occasion::List));
+        assert_eq!(finished(all_done, all_done), None);
+    }
+}
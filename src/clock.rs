@@ -0,0 +1,115 @@
+//! The time todo-rs goes by. That's the system's, unless `--now` gives a
+//! simulated one, stopped at that time, to try out or demo due dates,
+//! recurrence and reports at any point in time:
+//!
+//! ```sh
+//! todo --now "2024-12-31 23:00" list
+//! ```
+//!
+//! A simulated run leaves the store's upkeep alone: daily maintenance,
+//! usage counts, and moving missed soft deadlines or cancelling expired
+//! items would all be written with the simulated date.
+
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+
+use crate::date;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+
+    /// Whether this isn't the real time.
+    fn is_simulated(&self) -> bool {
+        false
+    }
+}
+
+/// The system's clock.
+pub struct System;
+
+impl Clock for System {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock stopped at one time.
+pub struct Fixed(pub DateTime<Local>);
+
+impl Clock for Fixed {
+    fn now(&self) -> DateTime<Local> {
+        self.0
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+}
+
+static CLOCK: OnceLock<Box<dyn Clock>> = OnceLock::new();
+
+/// Makes `clock` the one to go by, unless one was set already.
+pub fn set(clock: Box<dyn Clock>) {
+    let _ = CLOCK.set(clock);
+}
+
+/// The time now, as the clock goes.
+pub fn now() -> DateTime<Local> {
+    CLOCK
+        .get()
+        .map_or_else(|| System.now(), |clock| clock.now())
+}
+
+/// Whether the clock goes by a simulated time, from `--now`.
+pub fn is_simulated() -> bool {
+    CLOCK.get().is_some_and(|clock| clock.is_simulated())
+}
+
+/// clap value parser for `--now`: a time like `2024-06-30 17:00`, or
+/// anything `--due` takes.
+pub fn parse(input: &str) -> Result<DateTime<Local>, String> {
+    date::parse_due(input, Local::now()).ok_or_else(|| {
+        format!(
+            "Couldn't understand the time {:?}, try e.g. \"2024-06-30 17:00\"",
+            input
+        )
+    })
+}
+
+/// Stops the clock at the time `--now` gives on the command line `args`,
+/// if it does. This comes before the rest is parsed, as dates there, like
+/// `--due friday`, are read from now.
+pub fn init(args: &[OsString]) -> Result<(), String> {
+    let args: Vec<&str> = args
+        .iter()
+        .filter_map(|arg| arg.to_str())
+        .take_while(|&arg| arg != "--")
+        .collect();
+    let given = args.iter().enumerate().find_map(|(i, arg)| match *arg {
+        "--now" => args.get(i + 1).copied(),
+        _ => arg.strip_prefix("--now="),
+    });
+    if let Some(time) = given {
+        set(Box::new(Fixed(parse(time)?)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn clocks() {
+        let then = Local.with_ymd_and_hms(2024, 12, 31, 23, 0, 0).unwrap();
+        assert_eq!(Fixed(then).now(), then);
+        assert_eq!(parse("2024-12-31 23:00"), Ok(then));
+        assert!(parse("someday").is_err());
+        assert!(System.now() > then);
+        assert!(Fixed(then).is_simulated() && !System.is_simulated());
+    }
+}
@@ -0,0 +1,172 @@
+//! Defaults kept in `~/.config/todo-rs/config.toml`, or the file named by
+//! `TODO_CONFIG`:
+//!
+//! ```toml
+//! list = "work"            # the list to use without --list
+//! date_format = "%d/%m/%Y" # how dates are shown, as in strftime
+//! clear_screen = false     # keep the interactive prompt's scrollback
+//! color = false            # no colors, even on a terminal
+//! sort = "priority"        # the order without --sort
+//! auto_archive = true      # as TODO_AUTO_ARCHIVE=1
+//! trash_days = 7           # how long removed items can be restored
+//! theme = "high-contrast"  # or default or mono, see `theme`
+//! usage_stats = false      # don't count which commands are used
+//! late_after = 22          # a note when checking items off after 22:00
+//! relative_dates = true    # due dates in lists like "in 3 days"
+//!
+//! [checkbox]               # how items are ticked off in lists
+//! open = "☐"
+//! done = "☑"
+//! cancelled = "☒"
+//!
+//! [styles]                # changes to the theme
+//! overdue = "magenta bold"
+//!
+//! [notify.tags]           # where reminders go by tag, see `notify`
+//! work = "slack:work-webhook"
+//!
+//! [keys]                  # keys for `todo tui`, see there
+//! preset = "vim"
+//! delete = ["dd", "<del>"]
+//!
+//! [defaults.list]          # flags a command gets unless they're given
+//! format = "tsv"
+//! [defaults.add]
+//! list = "inbox"
+//! tag = ["new"]
+//! ```
+//!
+//! Flags and environment variables win over the file. Without a file
+//! everything is as if it were empty.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use chrono::format::{Item as FormatItem, StrftimeItems};
+use serde::Deserialize;
+
+use crate::notify::Routes;
+use crate::priority::SortOrder;
+use crate::store;
+use crate::theme::{Styles, ThemeName};
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub list: Option<String>,
+    pub date_format: Option<String>,
+    pub clear_screen: Option<bool>,
+    pub color: Option<bool>,
+    pub sort: Option<SortOrder>,
+    pub auto_archive: Option<bool>,
+    pub trash_days: Option<u32>,
+    pub usage_stats: Option<bool>,
+    /// The hour the night starts at, see `burnout`.
+    pub late_after: Option<u32>,
+    pub relative_dates: Option<bool>,
+    pub theme: Option<ThemeName>,
+    pub styles: Option<Styles>,
+    pub checkbox: Option<Checkboxes>,
+    pub notify: Option<Routes>,
+    /// Keys for `todo tui` by action, checked when it starts.
+    #[serde(default)]
+    pub keys: BTreeMap<String, toml::Value>,
+    /// Flags to give each command, by the command's name: `true` for a
+    /// switch, a list for a flag given more than once.
+    #[serde(default)]
+    pub defaults: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Checkboxes {
+    pub open: Option<String>,
+    pub done: Option<String>,
+    pub cancelled: Option<String>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static EMPTY: Config = Config {
+    list: None,
+    date_format: None,
+    clear_screen: None,
+    color: None,
+    sort: None,
+    auto_archive: None,
+    trash_days: None,
+    usage_stats: None,
+    late_after: None,
+    relative_dates: None,
+    theme: None,
+    styles: None,
+    checkbox: None,
+    notify: None,
+    keys: BTreeMap::new(),
+    defaults: BTreeMap::new(),
+};
+
+pub fn path() -> Option<PathBuf> {
+    match std::env::var_os("TODO_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(dirs::config_dir()?.join(store::APP_DIR).join("config.toml")),
+    }
+}
+
+fn parse(content: &str) -> Result<Config, String> {
+    let config: Config = toml::from_str(content).map_err(|e| e.to_string())?;
+    if let Some(format) = &config.date_format {
+        if StrftimeItems::new(format).any(|item| item == FormatItem::Error) {
+            return Err(format!("date_format {:?} isn't a valid format", format));
+        }
+    }
+    if config.late_after.is_some_and(|hour| hour > 23) {
+        return Err("late_after should be an hour from 0 to 23".to_string());
+    }
+    Ok(config)
+}
+
+/// Reads the config file, if there is one.
+pub fn load() -> Result<Config, String> {
+    Ok(match path() {
+        Some(path) if path.exists() => {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            parse(&content).map_err(|e| format!("{} couldn't be read: {}", path.display(), e))?
+        }
+        _ => Config::default(),
+    })
+}
+
+/// Makes `config`, read and with the flags given applied, the settings
+/// used from now on. Called once at startup.
+pub fn set(config: Config) -> &'static Config {
+    CONFIG.get_or_init(|| config)
+}
+
+/// The settings read at startup, or none if they weren't read, as in tests.
+pub fn get() -> &'static Config {
+    CONFIG.get().unwrap_or(&EMPTY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_read() {
+        let config = parse("list = \"work\"\nsort = \"priority\"\nclear_screen = false\n").unwrap();
+        assert_eq!(config.list.as_deref(), Some("work"));
+        assert_eq!(config.sort, Some(SortOrder::Priority));
+        assert_eq!(config.clear_screen, Some(false));
+        assert_eq!(config.color, None);
+
+        assert!(parse("colour = false").is_err());
+        assert!(parse("sort = \"sideways\"").is_err());
+        assert!(parse("date_format = \"%Q\"").is_err());
+        assert_eq!(parse("").unwrap(), Config::default());
+
+        let config = parse("[checkbox]\ndone = \"✓\"\n").unwrap();
+        assert_eq!(config.checkbox.unwrap().done.as_deref(), Some("✓"));
+    }
+}
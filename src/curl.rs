@@ -0,0 +1,77 @@
+//! Running `curl` with what mustn't show on the command line, where every
+//! user can see it: tokens, and URLs that work as passwords. Those go in a
+//! config on stdin, which `-K -` has curl read, as `name = "value"` lines.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// `value` quoted for curl's config, where a backslash escapes the next
+/// character.
+fn quoted(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The config giving curl `options`, each a long option without its `--`
+/// and the value for it.
+fn config(options: &[(&str, &str)]) -> String {
+    options
+        .iter()
+        .map(|(name, value)| format!("{} = {}\n", name, quoted(value)))
+        .collect()
+}
+
+/// Runs curl with `arguments` and `options`, given in its config on stdin,
+/// and returns what it prints.
+pub fn run(arguments: &[&str], options: &[(&str, &str)]) -> Result<Vec<u8>, String> {
+    let mut child = Command::new("curl")
+        .args(arguments)
+        .args(["-K", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run curl: {}", e))?;
+    child
+        .stdin
+        .take()
+        .ok_or("Could not talk to curl")?
+        .write_all(config(options).as_bytes())
+        .map_err(|e| format!("Could not talk to curl: {}", e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Could not talk to curl: {}", e))?;
+    if !output.status.success() {
+        return Err("curl failed".to_string());
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs() {
+        let options = [
+            ("header", "Authorization: Bearer abc"),
+            ("data-raw", "{\"name\": \"a\\b\"}\n@file"),
+        ];
+        assert_eq!(
+            config(&options),
+            "header = \"Authorization: Bearer abc\"\n\
+             data-raw = \"{\\\"name\\\": \\\"a\\\\b\\\"}\\n@file\"\n"
+        );
+    }
+}
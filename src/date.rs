@@ -0,0 +1,216 @@
+//! A small natural-language date parser for due dates.
+//!
+//! Accepts a date part and an optional time part in either order, e.g.
+//! `tomorrow 5pm`, `friday`, `next mon 9:30am`, `in 3 days`, `2024-06-30
+//! 17:00`. A due date without a time means the end of that day, so it only
+//! becomes overdue once the day is over.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+
+use crate::{clock, config};
+
+const END_OF_DAY: (u32, u32, u32) = (23, 59, 59);
+
+/// clap value parser for `--due`.
+pub fn parse_due_arg(input: &str) -> Result<DateTime<Local>, String> {
+    parse_due(input, clock::now()).ok_or_else(|| {
+        format!(
+            "Couldn't understand the date {:?}, try e.g. \"tomorrow 5pm\", \"friday\" or \"2024-06-30\"",
+            input
+        )
+    })
+}
+
+pub fn parse_due(input: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let input = input.trim().to_lowercase();
+    let words: Vec<&str> = input.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+
+    // `in 3 hours` is relative to the current time rather than a day.
+    if let ["in", amount, unit] = words.as_slice() {
+        let amount: i64 = amount.parse().ok()?;
+        // Amounts too large for a date are no date at all.
+        let offset = match unit.trim_end_matches('s') {
+            "minute" | "min" => Duration::try_minutes(amount),
+            "hour" => Duration::try_hours(amount),
+            "day" => Duration::try_days(amount),
+            "week" => Duration::try_weeks(amount),
+            _ => return None,
+        }?;
+        return if matches!(unit.trim_end_matches('s'), "minute" | "min" | "hour") {
+            now.checked_add_signed(offset)
+        } else {
+            at(now.date_naive().checked_add_signed(offset)?, None)
+        };
+    }
+
+    // Whatever parses as a time of day is the time, the rest is the date.
+    let mut time = None;
+    let mut date_words = Vec::new();
+    for word in &words {
+        match parse_time(word) {
+            Some(parsed) if time.is_none() => time = Some(parsed),
+            _ => date_words.push(*word),
+        }
+    }
+
+    let today = now.date_naive();
+    let date = match date_words.as_slice() {
+        [] if time.is_some() => today,
+        ["today" | "tod"] => today,
+        ["tonight"] => return at(today, time.or(NaiveTime::from_hms_opt(20, 0, 0))),
+        ["tomorrow" | "tmr" | "tom"] => today + Duration::days(1),
+        ["next", "week"] => weekday_next_week(today, Weekday::Mon),
+        ["next", day] => weekday_next_week(today, parse_weekday(day)?),
+        [day] => match parse_weekday(day) {
+            Some(weekday) => upcoming_weekday(today, weekday),
+            None => parse_absolute(day, today)?,
+        },
+        _ => return None,
+    };
+
+    at(date, time)
+}
+
+pub(crate) fn at(date: NaiveDate, time: Option<NaiveTime>) -> Option<DateTime<Local>> {
+    let (h, m, s) = END_OF_DAY;
+    let time = time.or_else(|| NaiveTime::from_hms_opt(h, m, s))?;
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+/// Whether a due date was given without a time of day.
+pub fn is_all_day(due: &DateTime<Local>) -> bool {
+    let (h, m, s) = END_OF_DAY;
+    due.time() == NaiveTime::from_hms_opt(h, m, s).unwrap()
+}
+
+/// A due date as shown to people, with the date as `date_format` in the
+/// config has it. Files and scripts get `format_due`, which reads back.
+pub fn show_due(due: &DateTime<Local>) -> String {
+    let date = due
+        .format(config::get().date_format.as_deref().unwrap_or("%Y-%m-%d"))
+        .to_string();
+    match is_all_day(due) {
+        true => date,
+        false => format!("{} {}", date, due.format("%H:%M")),
+    }
+}
+
+pub fn format_due(due: &DateTime<Local>) -> String {
+    if is_all_day(due) {
+        due.format("%Y-%m-%d").to_string()
+    } else {
+        due.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+/// The first `weekday` from today on.
+fn upcoming_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_ahead =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today + Duration::days(days_ahead as i64)
+}
+
+/// `weekday` in the calendar week (Monday to Sunday) after this one.
+fn weekday_next_week(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let next_monday = today + Duration::days(7 - today.weekday().num_days_from_monday() as i64);
+    next_monday + Duration::days(weekday.num_days_from_monday() as i64)
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// `2024-06-30`, or `06-30`/`6/30` for the next such day.
+fn parse_absolute(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let (month, day) = word.split_once(['-', '/'])?;
+    let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day)?;
+    if this_year >= today {
+        Some(this_year)
+    } else {
+        NaiveDate::from_ymd_opt(today.year() + 1, month, day)
+    }
+}
+
+/// `17:00`, `5pm`, `5:30pm`, `noon` or `midnight`.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    match word {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let (digits, offset) = if let Some(digits) = word.strip_suffix("am") {
+        (digits, Some(0))
+    } else if let Some(digits) = word.strip_suffix("pm") {
+        (digits, Some(12))
+    } else {
+        (word, None)
+    };
+
+    let (hour, minute) = match digits.split_once(':') {
+        Some((hour, minute)) => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm, otherwise `in 3 days`
+        // style input would be ambiguous.
+        None if offset.is_some() => (digits.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn formatted_due_dates_parse_back(
+            year in 1970..2100i32,
+            day in 1..=365u32,
+            time in prop::option::of((0..24u32, 0..60u32)),
+        ) {
+            let date = NaiveDate::from_yo_opt(year, day).unwrap();
+            let time = time.and_then(|(h, m)| NaiveTime::from_hms_opt(h, m, 0));
+            // Skip local times that don't exist, e.g. in a DST gap.
+            let Some(due) = at(date, time) else {
+                return Ok(());
+            };
+
+            prop_assert_eq!(parse_due(&format_due(&due), Local::now()), Some(due));
+        }
+    }
+
+    #[test]
+    fn out_of_range_amounts_are_no_date() {
+        // Found by the date fuzz target.
+        assert_eq!(parse_due("in 777777202 week", Local::now()), None);
+        assert_eq!(
+            parse_due("in 9223372036854775807 minutes", Local::now()),
+            None
+        );
+    }
+}
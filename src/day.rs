@@ -0,0 +1,84 @@
+//! "My Day": a hand-picked subset of the list for today. Membership is the
+//! date an item was picked on, so yesterday's picks drop out on their own.
+
+use chrono::NaiveDate;
+use clap::Subcommand;
+
+use crate::history::{self, Operation};
+use crate::screen::Screen;
+use crate::{check_index, clock, Todos};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DayCommand {
+    /// Pick an item for today
+    Add { index: usize },
+    /// Drop an item from today's picks
+    Remove { index: usize },
+}
+
+pub fn run(
+    screen: &mut Screen,
+    todos: &mut Todos,
+    command: Option<DayCommand>,
+) -> Result<(), String> {
+    let today = clock::now().date_naive();
+
+    let picked = match command {
+        None => None,
+        Some(DayCommand::Add { index }) => Some((index, Some(today))),
+        Some(DayCommand::Remove { index }) => Some((index, None)),
+    };
+    if let Some((index, my_day)) = picked {
+        check_index(todos, index)?;
+        let mut item = todos[index].clone();
+        item.my_day = my_day;
+        history::perform(todos, Operation::replace(todos, index, item))?;
+    }
+
+    print_day(screen, todos, today);
+    Ok(())
+}
+
+fn print_day(screen: &mut Screen, todos: &Todos, today: NaiveDate) {
+    writeln!(screen, "My Day ({})", today);
+
+    let mut empty = true;
+    for (i, item) in todos.iter().enumerate() {
+        if item.my_day == Some(today) {
+            writeln!(screen, "{} {}", i, item);
+            empty = false;
+        }
+    }
+
+    if empty {
+        writeln!(screen, "[Nothing picked for today]");
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn day() {
+        let mut screen = Screen::plain();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        print_day(&mut screen, &sample(), today);
+        insta::assert_snapshot!(screen.contents());
+    }
+
+    #[test]
+    fn picks_can_be_undone() {
+        let _store = crate::tests::temp_store();
+        let mut todos = sample();
+        let before = todos.clone();
+        let command = Some(DayCommand::Add { index: 2 });
+        run(&mut Screen::plain(), &mut todos, command).unwrap();
+        assert_eq!(todos[2].my_day, Some(clock::now().date_naive()));
+
+        history::undo(&mut todos).unwrap();
+        assert_eq!(todos, before);
+    }
+}
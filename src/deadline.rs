@@ -0,0 +1,81 @@
+//! Hard and soft due dates. A due date is hard unless `--soft` says
+//! otherwise: missing it makes the item overdue. A soft one is more of an
+//! aim, so missing it quietly moves it on to the same time the next day,
+//! noted as putting the item off, instead of leaving it overdue.
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::history::{self, Operation};
+use crate::{defer, Todos};
+
+/// Moves every open item whose soft due date has passed by `now` on, as one
+/// change that can be undone. Returns whether any were.
+pub fn move_missed(todos: &mut Todos, now: DateTime<Local>) -> Result<bool, String> {
+    match moves(todos, now) {
+        Some(operation) => history::perform(todos, operation).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+/// The change moving missed soft due dates on, or `None` when there aren't
+/// any.
+fn moves(todos: &Todos, now: DateTime<Local>) -> Option<Operation> {
+    let mut operations = Vec::new();
+    for (i, item) in todos.iter().enumerate() {
+        let Some(due) = item
+            .due
+            .filter(|due| item.soft && !item.completed && *due <= now)
+        else {
+            continue;
+        };
+        let mut after = item.clone();
+        after.due = Some(next(due, now));
+        defer::note_postponed(item, &mut after, now);
+        operations.push(Operation::replace(todos, i, after));
+    }
+    match operations.is_empty() {
+        true => None,
+        false => Some(Operation::Group { operations }),
+    }
+}
+
+/// The first time of day `due` was at that's after `now`.
+fn next(due: DateTime<Local>, now: DateTime<Local>) -> DateTime<Local> {
+    let days = (now - due).num_days() + 1;
+    (due.naive_local() + Duration::days(days))
+        .and_local_timezone(Local)
+        .earliest()
+        .unwrap_or(due + Duration::days(days))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn missed_soft_dates_move_on() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 12, 0, 0).unwrap();
+        let mut todos = sample();
+        todos[1].due = Some(Local.with_ymd_and_hms(2024, 5, 3, 9, 0, 0).unwrap());
+        todos[1].soft = true;
+        todos[2].due = Some(Local.with_ymd_and_hms(2024, 5, 6, 17, 0, 0).unwrap());
+        todos[2].soft = true;
+        let hard = todos[0].clone();
+
+        moves(&todos, now).unwrap().apply(&mut todos).unwrap();
+        assert_eq!(
+            todos[1].due,
+            Some(Local.with_ymd_and_hms(2024, 5, 7, 9, 0, 0).unwrap())
+        );
+        assert_eq!(todos[1].postponed, vec![now]);
+        assert_eq!(
+            todos[2].due,
+            Some(Local.with_ymd_and_hms(2024, 5, 6, 17, 0, 0).unwrap())
+        );
+        assert_eq!(todos[0], hard);
+        assert!(moves(&todos, now).is_none());
+    }
+}
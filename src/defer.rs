@@ -0,0 +1,179 @@
+//! Start dates for tickler-file style deferral: an item stays out of the
+//! default listing until the day it becomes relevant.
+//!
+//! Snoozing is the same for a while rather than until a day: `todo snooze
+//! 3 2h` hides item 3 for two hours, and `todo snooze 3 off` brings it
+//! back early.
+//!
+//! Each time an item is put off like that, or has its due date moved
+//! later, it's noted on the item for `todo report procrastination`.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+
+use crate::history::{self, Operation};
+use crate::{check_index, clock, date, Item, Todos};
+
+/// Notes on `after` that it was put off at `now`, if it was: snoozed,
+/// deferred or due later than `before`.
+pub fn note_postponed(before: &Item, after: &mut Item, now: DateTime<Local>) {
+    fn later<T: PartialOrd>(after: Option<T>, before: Option<T>) -> bool {
+        match (after, before) {
+            (Some(after), Some(before)) => after > before,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+    let due = before.due.is_some() && later(after.due, before.due);
+    let snoozed = later(after.hidden_until, before.hidden_until) && is_snoozed(after, now);
+    let deferred = later(after.starts_on, before.starts_on) && !is_started(after);
+    if due || snoozed || deferred {
+        after.postponed.push(now);
+    }
+}
+
+/// Hides an item until `starts_on`, or makes it visible again when no date
+/// is given.
+pub fn defer(todos: &mut Todos, index: usize, starts_on: Option<NaiveDate>) -> Result<(), String> {
+    check_index(todos, index)?;
+    let mut item = todos[index].clone();
+    item.starts_on = starts_on;
+    note_postponed(&todos[index], &mut item, clock::now());
+    match starts_on {
+        Some(date) => println!("{} is deferred until {}", item.name, date),
+        None => println!("{} is no longer deferred", item.name),
+    }
+    history::perform(todos, Operation::replace(todos, index, item))
+}
+
+pub fn is_started(item: &Item) -> bool {
+    item.starts_on
+        .is_none_or(|starts_on| starts_on <= clock::now().date_naive())
+}
+
+pub fn is_snoozed(item: &Item, now: DateTime<Local>) -> bool {
+    item.hidden_until.is_some_and(|until| until > now)
+}
+
+/// When a snooze given as `2h`, `30m`, `3d` or `1w`, or as a date like
+/// `friday 9am`, ends; `None` for `off`. A date without a time ends the
+/// snooze as the day starts.
+pub fn parse_snooze(input: &str, now: DateTime<Local>) -> Result<Option<DateTime<Local>>, String> {
+    let input = input.trim();
+    if input == "off" {
+        return Ok(None);
+    }
+
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+    if let Ok(amount) = amount.parse::<i64>() {
+        let duration = match unit.trim() {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+            "h" | "hour" | "hours" => Duration::try_hours(amount),
+            "d" | "day" | "days" => Duration::try_days(amount),
+            "w" | "week" | "weeks" => Duration::try_weeks(amount),
+            _ => None,
+        };
+        if let Some(until) = duration.and_then(|duration| now.checked_add_signed(duration)) {
+            return Ok(Some(until));
+        }
+    }
+
+    match date::parse_due(input, now) {
+        Some(until) if date::is_all_day(&until) => Ok(date::at(
+            until.date_naive(),
+            NaiveTime::from_hms_opt(0, 0, 0),
+        )),
+        Some(until) => Ok(Some(until)),
+        None => Err(format!(
+            "Couldn't understand \"{}\", try e.g. 2h, 3d, \"monday 9am\" or off",
+            input
+        )),
+    }
+}
+
+/// Hides an item until `until`, or shows it again when that's `None`.
+/// Returns what to tell the user.
+pub fn snooze(
+    todos: &mut Todos,
+    index: usize,
+    until: Option<DateTime<Local>>,
+) -> Result<String, String> {
+    check_index(todos, index)?;
+    let mut item = todos[index].clone();
+    item.hidden_until = until;
+    note_postponed(&todos[index], &mut item, clock::now());
+    let message = match until {
+        Some(until) => format!(
+            "Snoozed \"{}\" until {}",
+            item.name,
+            until.format("%Y-%m-%d %H:%M")
+        ),
+        None => format!("\"{}\" is back on the list", item.name),
+    };
+    history::perform(todos, Operation::replace(todos, index, item))?;
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn snoozes() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 10, 0, 0).unwrap();
+        let at = |h| Local.with_ymd_and_hms(2024, 5, 6, h, 0, 0).single();
+
+        assert_eq!(parse_snooze("2h", now), Ok(at(12)));
+        assert_eq!(
+            parse_snooze("3 days", now),
+            Ok(Some(now + Duration::days(3)))
+        );
+        assert_eq!(parse_snooze("off", now), Ok(None));
+        assert_eq!(parse_snooze("3pm", now), Ok(at(15)));
+        assert_eq!(
+            parse_snooze("tomorrow", now),
+            Ok(Local.with_ymd_and_hms(2024, 5, 7, 0, 0, 0).single())
+        );
+        assert!(parse_snooze("later", now).is_err());
+        assert!(parse_snooze("99999999999999w", now).is_err());
+
+        let mut item = Item::new("nap".to_string());
+        assert!(!is_snoozed(&item, now));
+        item.hidden_until = at(12);
+        assert!(is_snoozed(&item, now));
+        assert!(!is_snoozed(&item, at(12).unwrap()));
+    }
+
+    #[test]
+    fn putting_off_is_noted() {
+        let now = Local::now();
+        let before = Item::new("taxes".to_string());
+
+        let mut after = before.clone();
+        after.due = Some(now + Duration::days(1));
+        note_postponed(&before, &mut after, now);
+        assert!(
+            after.postponed.is_empty(),
+            "a first due date isn't putting off"
+        );
+
+        let before = after.clone();
+        after.due = Some(now + Duration::days(7));
+        note_postponed(&before, &mut after, now);
+        assert_eq!(after.postponed, [now]);
+
+        let before = after.clone();
+        after.hidden_until = Some(now + Duration::hours(2));
+        note_postponed(&before, &mut after, now);
+        assert_eq!(after.postponed.len(), 2);
+
+        let before = after.clone();
+        after.due = Some(now);
+        note_postponed(&before, &mut after, now);
+        assert_eq!(after.postponed.len(), 2, "bringing it forward isn't either");
+    }
+}
@@ -0,0 +1,64 @@
+//! `todo demo`: the interactive prompt with a made-up list, for trying
+//! todo-rs out, screenshots and docs.
+//!
+//! The list and everything kept next to it, like its history and backups,
+//! live in a directory of their own under the system's temporary directory,
+//! emptied each time the demo starts. Nothing the demo does can reach the
+//! real lists.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local};
+
+use crate::store::{self, Backend};
+use crate::{date, quickadd, Todos};
+
+/// A list showing off most of what an item can have.
+fn sample(now: DateTime<Local>) -> Todos {
+    let item = |line: &str| quickadd::parse(line, now);
+
+    let mut report = item("send the quarterly report to @bob +work !high @today");
+    report.notes = Some("numbers are in the shared drive".to_string());
+    let mut slides = item("draft the slides");
+    slides.subtask = true;
+    let mut figures = item("check the figures with @alice");
+    figures.subtask = true;
+    figures.completed = true;
+    figures.completed_at = Some(now - Duration::hours(3));
+
+    let mut milk = item("oat milk +groceries");
+    milk.quantity = "2".parse().ok();
+    let mut coffee = item("coffee beans +groceries");
+    coffee.quantity = "500 g".parse().ok();
+
+    let mut plants = item("water the plants +home ~low");
+    plants.recurrence = "every mon, thu".parse().ok();
+    let mut dentist = item("book the dentist !medium");
+    dentist.due = date::at(now.date_naive() - Duration::days(2), None);
+    let mut taxes = item("file taxes +home !high @next-fri");
+    taxes.starts_on = Some(now.date_naive() + Duration::days(3));
+    let mut japanese = item("learn japanese");
+    japanese.someday = Some(now.date_naive());
+    let mut gym = item("renew the gym membership");
+    gym.completed = true;
+    gym.completed_at = Some(now - Duration::days(1));
+
+    let mut todos = vec![
+        report, slides, figures, milk, coffee, plants, dentist, taxes, japanese, gym,
+    ];
+    for (age, item) in todos.iter_mut().enumerate() {
+        item.created = Some(now - Duration::days(age as i64));
+    }
+    todos
+}
+
+/// Makes a fresh copy of the sample list the current list.
+pub fn start(now: DateTime<Local>) -> Result<(), String> {
+    let dir: PathBuf = std::env::temp_dir().join("todo-rs-demo");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    store::init(Some(dir.join("demo.json")), None, Some(Backend::Json))?;
+    store::open().save(&sample(now))
+}
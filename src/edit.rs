@@ -0,0 +1,310 @@
+//! Changing an existing item in place, so fixing a typo doesn't mean
+//! removing and re-adding it.
+
+use chrono::{DateTime, Local};
+use clap::Args;
+
+use crate::app::{get_operation_index, record};
+use crate::energy::Energy;
+use crate::history::{self, Operation};
+use crate::priority::Priority;
+use crate::prompt::Prompt;
+use crate::recurrence::Recurrence;
+use crate::screen::Screen;
+use crate::shop::Quantity;
+use crate::{check_index, clock, date, defer, notes, people, tags, Item, Todos};
+
+/// What `todo edit <index>` changes; anything not given is kept.
+#[derive(Args, Debug, Clone)]
+pub struct Changes {
+    /// The new name
+    name: Vec<String>,
+    /// When it's due, e.g. "tomorrow 5pm", "friday" or "2024-06-30"
+    #[clap(long, parse(try_from_str = date::parse_due_arg), conflicts_with = "no-due")]
+    due: Option<DateTime<Local>>,
+    /// Remove the due date
+    #[clap(long)]
+    no_due: bool,
+    /// Make the due date soft: missing it moves it to the next day
+    #[clap(long, conflicts_with = "hard")]
+    soft: bool,
+    /// Make the due date hard again: missing it makes the item overdue
+    #[clap(long)]
+    hard: bool,
+    /// When to cancel it if it isn't done by then
+    #[clap(long, parse(try_from_str = date::parse_due_arg), conflicts_with = "no-expires")]
+    expires: Option<DateTime<Local>>,
+    /// Remove the expiry date
+    #[clap(long)]
+    no_expires: bool,
+    /// low, medium or high
+    #[clap(long, conflicts_with = "no-priority")]
+    priority: Option<Priority>,
+    /// Remove the priority
+    #[clap(long)]
+    no_priority: bool,
+    /// Energy it takes: low, medium or high
+    #[clap(long, conflicts_with = "no-energy")]
+    energy: Option<Energy>,
+    /// Remove the energy level
+    #[clap(long)]
+    no_energy: bool,
+    /// Add it again when it's checked off, e.g. "daily" or "every mon, thu"
+    #[clap(long, conflicts_with = "no-repeat")]
+    repeat: Option<Recurrence>,
+    /// Stop repeating it
+    #[clap(long)]
+    no_repeat: bool,
+    /// How much to buy, e.g. 2 or "500 g"
+    #[clap(long, conflicts_with = "no-qty")]
+    qty: Option<Quantity>,
+    /// Take it off the shopping list
+    #[clap(long)]
+    no_qty: bool,
+    /// Add a tag
+    #[clap(long)]
+    tag: Vec<String>,
+    /// Remove a tag
+    #[clap(long)]
+    untag: Vec<String>,
+    /// Replace the notes
+    #[clap(long, conflicts_with_all = &["no-note", "edit-note"])]
+    note: Option<String>,
+    /// Remove the notes
+    #[clap(long, conflicts_with = "edit-note")]
+    no_note: bool,
+    /// Write the notes in $EDITOR
+    #[clap(long)]
+    edit_note: bool,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty()
+            && self.due.is_none()
+            && !self.no_due
+            && !self.soft
+            && !self.hard
+            && self.expires.is_none()
+            && !self.no_expires
+            && self.priority.is_none()
+            && !self.no_priority
+            && self.energy.is_none()
+            && !self.no_energy
+            && self.repeat.is_none()
+            && !self.no_repeat
+            && self.qty.is_none()
+            && !self.no_qty
+            && self.tag.is_empty()
+            && self.untag.is_empty()
+            && self.note.is_none()
+            && !self.no_note
+            && !self.edit_note
+    }
+}
+
+pub fn edit(todos: &mut Todos, index: usize, changes: Changes) -> Result<(), String> {
+    check_index(todos, index)?;
+    if changes.is_empty() {
+        return Err("edit needs something to change, e.g. a new name or --due".to_string());
+    }
+
+    let mut item = todos[index].clone();
+    if !changes.name.is_empty() {
+        rename(&mut item, changes.name.join(" "));
+    }
+    if changes.due.is_some() || changes.no_due {
+        item.due = changes.due;
+    }
+    if changes.soft || changes.hard {
+        item.soft = changes.soft;
+    }
+    if changes.expires.is_some() || changes.no_expires {
+        item.expires = changes.expires;
+    }
+    if changes.priority.is_some() || changes.no_priority {
+        item.priority = changes.priority;
+    }
+    if changes.energy.is_some() || changes.no_energy {
+        item.energy = changes.energy;
+    }
+    if changes.repeat.is_some() || changes.no_repeat {
+        item.recurrence = changes.repeat;
+    }
+    if changes.qty.is_some() || changes.no_qty {
+        item.quantity = changes.qty;
+    }
+    for tag in &changes.tag {
+        add_tag(&mut item, tag);
+    }
+    for tag in &changes.untag {
+        let tag = tags::normalize(tag);
+        item.tags.retain(|t| *t != tag);
+    }
+    if let Some(note) = &changes.note {
+        item.notes = notes::clean(note);
+    }
+    if changes.no_note {
+        item.notes = None;
+    }
+    if changes.edit_note {
+        item.notes = notes::edit(item.notes.as_deref())?;
+    }
+
+    defer::note_postponed(&todos[index], &mut item, clock::now());
+    history::perform(todos, Operation::replace(todos, index, item))
+}
+
+/// `item` as retyped on one `line`, as `quickadd::line` writes it: the
+/// name and the details written with it, which are cleared if left out.
+#[cfg(feature = "tui")]
+pub fn retyped(item: &Item, line: &str, now: DateTime<Local>) -> Item {
+    let typed = crate::quickadd::parse(line, now);
+    let mut after = item.clone();
+    rename(&mut after, typed.name);
+    after.priority = typed.priority;
+    after.energy = typed.energy;
+    after.due = typed.due;
+    defer::note_postponed(item, &mut after, now);
+    after
+}
+
+/// Renames an item. Tags and mentions written in the old name are
+/// replaced by the ones in the new name; tags added separately are kept.
+fn rename(item: &mut Item, name: String) {
+    let old_tags = tags::parse_tags(&item.name);
+    let kept: Vec<String> = item
+        .tags
+        .drain(..)
+        .filter(|tag| !old_tags.contains(tag))
+        .collect();
+
+    item.name = name;
+    item.contacts = people::parse_mentions(&item.name);
+    item.tags = tags::parse_tags(&item.name);
+    for tag in &kept {
+        add_tag(item, tag);
+    }
+}
+
+fn add_tag(item: &mut Item, tag: &str) {
+    let tag = tags::normalize(tag);
+    if !tag.is_empty() && !item.tags.contains(&tag) {
+        item.tags.push(tag);
+    }
+}
+
+/// Asks which item to edit, then for each field in turn. An empty answer
+/// keeps the current value and `none` clears it. Nothing changes if the
+/// user cancels part way.
+pub fn edit_todo(todos: &mut Todos, screen: &mut Screen) {
+    let Some(index) = get_operation_index(todos, screen) else {
+        return screen.clear();
+    };
+
+    screen.clear();
+    let mut item = todos[index].clone();
+    let changed = ask_changes(&mut item, screen);
+    screen.clear();
+    if changed.is_some() {
+        defer::note_postponed(&todos[index], &mut item, clock::now());
+        record(todos, Operation::replace(todos, index, item), screen);
+    }
+}
+
+fn ask_changes(item: &mut Item, screen: &mut Screen) -> Option<()> {
+    let name = Prompt::text(format!("Name? (empty to keep \"{}\")", item.name))
+        .default(item.name.clone())
+        .ask(screen)?;
+
+    let due = Prompt::due("\nWhen is it due? (empty to keep, none to remove)")
+        .default(item.due)
+        .ask(screen)?;
+
+    let priority =
+        Prompt::priority("\nPriority? (h)igh, (m)edium, (l)ow; empty to keep, none to remove")
+            .default(item.priority)
+            .ask(screen)?;
+
+    let current_tags: Vec<String> = item.tags.iter().map(|tag| format!("+{}", tag)).collect();
+    let tag_changes = Prompt::text(format!(
+        "\nTags? +tag to add, -tag to remove; empty to keep {}",
+        current_tags.join(" ")
+    ))
+    .default(String::new())
+    .ask(screen)?;
+
+    let edit_notes = screen.is_interactive()
+        && Prompt::confirm(match item.notes {
+            Some(_) => "\nEdit the notes in $EDITOR? (y/N)",
+            None => "\nAdd notes in $EDITOR? (y/N)",
+        })
+        .default(false)
+        .ask(screen)?;
+
+    if name != item.name {
+        rename(item, name);
+    }
+    item.due = due;
+    item.priority = priority;
+    for word in tag_changes.split_whitespace() {
+        match word.strip_prefix('-') {
+            Some(tag) => {
+                let tag = tags::normalize(tag);
+                item.tags.retain(|t| *t != tag);
+            }
+            None => add_tag(item, word),
+        }
+    }
+    if edit_notes {
+        ask_notes(item, screen);
+    }
+
+    Some(())
+}
+
+/// Opens the item's notes in the user's editor, telling them if that
+/// didn't work.
+pub fn ask_notes(item: &mut Item, screen: &mut Screen) {
+    match notes::edit(item.notes.as_deref()) {
+        Ok(notes) => item.notes = notes,
+        Err(e) => writeln!(screen, "{}\n", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[derive(Parser)]
+    struct Edit {
+        #[clap(flatten)]
+        changes: Changes,
+    }
+
+    fn changes(line: &str) -> Changes {
+        Edit::try_parse_from(line.split(' ')).unwrap().changes
+    }
+
+    #[test]
+    fn edits_can_be_undone() {
+        let _store = crate::tests::temp_store();
+        let mut todos = sample();
+        let before = todos.clone();
+
+        let line = "edit buy oat milk +shop --due 2099-06-30 --tag errands --untag home";
+        edit(&mut todos, 1, changes(line)).unwrap();
+        let item = &todos[1];
+        assert_eq!(item.name, "buy oat milk +shop");
+        assert_eq!(item.due, date::parse_due_arg("2099-06-30").ok());
+        assert_eq!(item.tags, ["shop", "errands"]);
+        assert_eq!(item.priority, before[1].priority);
+
+        history::undo(&mut todos).unwrap();
+        assert_eq!(todos, before);
+        assert!(edit(&mut todos, 1, changes("edit")).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! How much energy an item takes, for picking what to do when there isn't
+//! much left.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::priority::Priority;
+use crate::screen::Screen;
+use crate::{clock, defer, someday, Todos};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Energy {
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for Energy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" | "l" => Ok(Energy::Low),
+            "medium" | "med" | "m" => Ok(Energy::Medium),
+            "high" | "h" => Ok(Energy::High),
+            _ => Err(format!(
+                "No energy level called {}, expected low, medium or high",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Energy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Energy::Low => write!(f, "low"),
+            Energy::Medium => write!(f, "medium"),
+            Energy::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Prints up to `count` open items that need no more than `energy`, most
+/// important and soonest due first.
+pub fn print_suggestions(screen: &mut Screen, todos: &Todos, energy: Energy, count: usize) {
+    let open = || {
+        todos.iter().enumerate().filter(|(_, item)| {
+            !item.completed
+                && defer::is_started(item)
+                && !defer::is_snoozed(item, clock::now())
+                && !someday::is_someday(item)
+        })
+    };
+
+    let mut picks: Vec<usize> = open()
+        .filter(|(_, item)| item.energy.is_some_and(|needed| needed <= energy))
+        .map(|(i, _)| i)
+        .collect();
+    picks.sort_by_key(|&i| {
+        let item = &todos[i];
+        let priority = item.priority.unwrap_or(Priority::Medium);
+        (std::cmp::Reverse(priority), item.due.is_none(), item.due)
+    });
+
+    if picks.is_empty() {
+        writeln!(screen, "[Nothing open needs {} energy or less]", energy);
+    }
+    for i in picks.into_iter().take(count) {
+        writeln!(screen, "{} {}", i, todos[i]);
+    }
+
+    let unrated = open().filter(|(_, item)| item.energy.is_none()).count();
+    if unrated > 0 {
+        writeln!(
+            screen,
+            "[{} open without an energy level, set one with `todo edit <index> --energy`]",
+            unrated
+        );
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn suggestions() {
+        let mut todos = sample();
+        todos[0].energy = Some(Energy::High);
+        todos[1].energy = Some(Energy::Low);
+        todos[4].energy = Some(Energy::Medium);
+
+        let mut screen = Screen::plain();
+        print_suggestions(&mut screen, &todos, Energy::Medium, 5);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,73 @@
+//! Time-limited items, like "use the coupon by Friday": once an item's
+//! expiry date passes without it being done it's cancelled, which closes
+//! it without counting it as done.
+
+use chrono::{DateTime, Local};
+
+use crate::history::{self, Operation};
+use crate::{date, Todos};
+
+/// Cancels every open item that has expired by `now`, as one change that
+/// can be undone. Returns a notice for each.
+pub fn cancel_expired(todos: &mut Todos, now: DateTime<Local>) -> Result<Vec<String>, String> {
+    match cancellations(todos, now) {
+        Some((operation, notices)) => {
+            history::perform(todos, operation)?;
+            Ok(notices)
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The change cancelling the items that have expired, with a notice for
+/// each, or `None` when there aren't any.
+fn cancellations(todos: &Todos, now: DateTime<Local>) -> Option<(Operation, Vec<String>)> {
+    let mut notices = Vec::new();
+    let mut operations = Vec::new();
+    for (i, item) in todos.iter().enumerate() {
+        match item.expires {
+            Some(expires) if !item.completed && expires < now => {
+                let mut after = item.clone();
+                after.completed = true;
+                after.cancelled = true;
+                notices.push(format!(
+                    "Cancelled \"{}\", it expired {}",
+                    item.name,
+                    date::show_due(&expires)
+                ));
+                operations.push(Operation::replace(todos, i, after));
+            }
+            _ => {}
+        }
+    }
+
+    match operations.is_empty() {
+        true => None,
+        false => Some((Operation::Group { operations }, notices)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn only_open_expired_items_are_cancelled() {
+        let now = Local::now();
+        let mut todos = sample();
+        todos[1].expires = date::parse_due("2024-05-01", now);
+        todos[2].expires = date::parse_due("2099-05-01", now);
+        todos[3].expires = date::parse_due("2024-05-01", now);
+
+        let (operation, notices) = cancellations(&todos, now).unwrap();
+        assert_eq!(
+            notices,
+            vec!["Cancelled \"buy milk\", it expired 2024-05-01"]
+        );
+        operation.apply(&mut todos).unwrap();
+        assert!(todos[1].cancelled && todos[1].completed);
+        assert!(!todos[2].cancelled && !todos[3].cancelled);
+        assert!(cancellations(&todos, now).is_none());
+    }
+}
@@ -0,0 +1,202 @@
+//! `todo block-time`: focus blocks for the open high priority items over
+//! the next week, as an iCalendar file to import into a work calendar so
+//! it reflects the plan.
+//!
+//! Items are taken soonest due first, then in list order, one block each,
+//! into the free stretches of working days (Monday to Friday) within the
+//! working hours. Items on the someday list or not started yet are left
+//! out.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, Utc, Weekday};
+
+use crate::priority::Priority;
+use crate::{date, defer, someday, Todos};
+
+/// When to block time, from `todo block-time`'s flags.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plan {
+    pub days: u32,
+    /// The working day as hours, like 9 and 17.
+    pub hours: (u32, u32),
+    pub length: Duration,
+}
+
+/// Reads working hours written like `9-17`.
+pub fn parse_hours(text: &str) -> Result<(u32, u32), String> {
+    let error = || format!("Couldn't understand {:?}, expected hours like 9-17", text);
+    let (start, end) = text.split_once('-').ok_or_else(error)?;
+    let (start, end): (u32, u32) = (
+        start.trim().parse().map_err(|_| error())?,
+        end.trim().parse().map_err(|_| error())?,
+    );
+    match start < end && end <= 24 {
+        true => Ok((start, end)),
+        false => Err(error()),
+    }
+}
+
+/// Reads a block's length written like `90m` or `2h`.
+pub fn parse_length(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let length = match (text[..split].parse::<i64>(), &text[split..]) {
+        (Ok(amount), "m" | "min" | "minutes") => Duration::try_minutes(amount),
+        (Ok(amount), "h" | "hours") => Duration::try_hours(amount),
+        _ => None,
+    }
+    .ok_or_else(|| format!("Couldn't understand {:?}, try 90m or 2h", text))?;
+    match length > Duration::zero() && length <= Duration::days(1) {
+        true => Ok(length),
+        false => Err("A block needs some length, up to a day".to_string()),
+    }
+}
+
+/// The items to block time for, in the order they get it.
+fn wanted(todos: &Todos) -> Vec<usize> {
+    let mut wanted: Vec<usize> = (0..todos.len())
+        .filter(|&i| {
+            let item = &todos[i];
+            !item.completed
+                && item.priority == Some(Priority::High)
+                && defer::is_started(item)
+                && !someday::is_someday(item)
+        })
+        .collect();
+    // Stable, so items without a due date keep the list's order.
+    wanted.sort_by_key(|&i| (todos[i].due.is_none(), todos[i].due));
+    wanted
+}
+
+/// Where each block goes: an item's index and when its block starts, in
+/// working hours from `now` on.
+fn schedule(todos: &Todos, plan: Plan, now: DateTime<Local>) -> Vec<(usize, NaiveDateTime)> {
+    let mut slots = Vec::new();
+    for day in (0..plan.days).map(|days| now.date_naive() + Duration::days(days.into())) {
+        if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            continue;
+        }
+        let end = day.and_hms_opt(0, 0, 0).unwrap() + Duration::hours(plan.hours.1.into());
+        let mut start = day.and_hms_opt(plan.hours.0, 0, 0).unwrap();
+        while start + plan.length <= end {
+            if start >= now.naive_local() {
+                slots.push(start);
+            }
+            start += plan.length;
+        }
+    }
+    wanted(todos).into_iter().zip(slots).collect()
+}
+
+/// Escapes text for an iCalendar value.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Writes a content line, folded at 75 bytes as iCalendar asks.
+fn line(out: &mut String, text: &str) {
+    let mut width = 0;
+    for c in text.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// The focus blocks as an iCalendar file, and how many there are.
+pub fn calendar(todos: &Todos, plan: Plan, now: DateTime<Local>) -> (String, usize) {
+    let format = "%Y%m%dT%H%M%S";
+    let blocks = schedule(todos, plan, now);
+    let mut out = String::new();
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, "PRODID:-//todo-rs//block-time//EN");
+    for (i, start) in &blocks {
+        let item = &todos[*i];
+        // The same item on the same day keeps its identity if exported
+        // again, so calendars update blocks instead of doubling them.
+        let created = match item.created {
+            Some(created) => created.timestamp_nanos_opt().unwrap_or_default(),
+            None => *i as i64,
+        };
+        line(&mut out, "BEGIN:VEVENT");
+        line(
+            &mut out,
+            &format!("UID:{}-{}@todo-rs", created, start.format("%Y%m%d")),
+        );
+        line(
+            &mut out,
+            &format!("DTSTAMP:{}Z", now.with_timezone(&Utc).format(format)),
+        );
+        line(&mut out, &format!("DTSTART:{}", start.format(format)));
+        line(
+            &mut out,
+            &format!("DTEND:{}", (*start + plan.length).format(format)),
+        );
+        line(
+            &mut out,
+            &format!("SUMMARY:{}", escape(&format!("Focus: {}", item.name))),
+        );
+        if let Some(due) = &item.due {
+            let description = format!("Due {}", date::show_due(due));
+            line(&mut out, &format!("DESCRIPTION:{}", escape(&description)));
+        }
+        line(&mut out, "TRANSP:OPAQUE");
+        line(&mut out, "END:VEVENT");
+    }
+    line(&mut out, "END:VCALENDAR");
+    (out, blocks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tests::sample;
+    use crate::Item;
+
+    #[test]
+    fn blocks() {
+        let mut todos = sample();
+        let mut slides = Item::new("slides, for the review".to_string());
+        slides.priority = Some(Priority::High);
+        slides.created = None;
+        todos.push(slides);
+        todos[0].created = None;
+        let plan = Plan {
+            days: 7,
+            hours: (9, 17),
+            length: parse_length("4h").unwrap(),
+        };
+        // A Friday afternoon, so the second block waits for Monday.
+        let now = Local.with_ymd_and_hms(2024, 5, 10, 12, 0, 0).unwrap();
+
+        let (calendar, count) = calendar(&todos, plan, now);
+        assert_eq!(count, 2);
+        // Stamped in UTC, which depends on where the tests run.
+        let lines: Vec<&str> = calendar
+            .lines()
+            .filter(|line| !line.starts_with("DTSTAMP"))
+            .collect();
+        insta::assert_snapshot!(lines.join("\n"));
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(parse_hours("9-17"), Ok((9, 17)));
+        assert!(parse_hours("17-9").is_err());
+        assert_eq!(parse_length("90m"), Ok(Duration::minutes(90)));
+        assert!(parse_length("0h").is_err());
+        assert!(parse_length("99999999999999h").is_err());
+        assert!(parse_length("soon").is_err());
+    }
+}
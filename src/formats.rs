@@ -0,0 +1,692 @@
+//! Other tools' list formats, for `todo export` and `todo import`:
+//!
+//! - todo.txt, e.g. `x (A) 2024-05-01 send report +work due:2024-05-03`
+//! - GitHub style Markdown checklists, `- [ ] item`, subtasks indented
+//! - CSV with a header row naming the columns
+//! - JSON, as todo-rs keeps items itself, or in another app's shape with a
+//!   map saying where its items are, see `mapping`
+//!
+//! Each keeps what it has room for. An import reads back what an export
+//! wrote, but todo.txt and Markdown drop fields they can't express.
+//!
+//! An imported item with the same name as one on the list, ignoring case
+//! and tags, is a conflict, settled by `--on-conflict`. Subtasks are matched among
+//! the subtasks of the item they were imported under.
+
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use serde_json::Value;
+
+use crate::history::Operation;
+use crate::priority::Priority;
+use crate::{clock, date, mapping, subtasks, tags, Item, Todos};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    TodoTxt,
+    Markdown,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "txt" | "todo.txt" | "todotxt" => Ok(Format::TodoTxt),
+            "md" | "markdown" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "No format called {}, expected txt, md, csv or json",
+                s
+            )),
+        }
+    }
+}
+
+impl Format {
+    /// The format a file's extension stands for.
+    pub fn of(path: &Path) -> Result<Self, String> {
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+        match extension.to_lowercase().as_str() {
+            "txt" => Ok(Format::TodoTxt),
+            "md" | "markdown" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "Can't tell the format of {}, pass --format",
+                path.display()
+            )),
+        }
+    }
+
+    pub fn export(self, todos: &Todos) -> String {
+        match self {
+            Format::TodoTxt => export_todo_txt(todos),
+            Format::Markdown => export_markdown(todos),
+            Format::Csv => export_csv(todos),
+            Format::Json => serde_json::to_string_pretty(todos).unwrap() + "\n",
+        }
+    }
+
+    pub fn import(self, content: &str) -> Result<Todos, String> {
+        match self {
+            Format::TodoTxt => Ok(import_todo_txt(content)),
+            Format::Markdown => Ok(import_markdown(content)),
+            Format::Csv => import_csv(content),
+            Format::Json => serde_json::from_str(content)
+                .map_err(|e| format!("Not a list of todo-rs items ({}), pass --map", e)),
+        }
+    }
+}
+
+/// Reads JSON in another app's shape, with `map` making an object for each
+/// item, like `{name: .title, completed: .done}`.
+pub fn import_mapped(content: &str, map: &str) -> Result<Todos, String> {
+    let map = mapping::parse(map)?;
+    let content: Value =
+        serde_json::from_str(content).map_err(|e| format!("Not valid JSON: {}", e))?;
+    mapping::run(&map, &content)?
+        .iter()
+        .enumerate()
+        .map(|(number, value)| {
+            item_from_json(value).map_err(|e| format!("Item {}: {}", number + 1, e))
+        })
+        .collect()
+}
+
+/// An item from an object the map made. Only a name is required, and
+/// fields are taken in whatever shape other apps write them, as for CSV.
+fn item_from_json(value: &Value) -> Result<Item, String> {
+    let Value::Object(object) = value else {
+        return Err(format!(
+            "The map should make objects like {{name: .title}}, not {}",
+            value
+        ));
+    };
+    let text = |key: &str, value: &Value| match value {
+        Value::String(text) => Ok(text.trim().to_string()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => Err(format!("{} should be text, not {}", key, value)),
+    };
+
+    let mut item = Item::new(String::new());
+    for (key, value) in object.iter().filter(|(_, value)| !value.is_null()) {
+        match key.as_str() {
+            "name" => item.name = text(key, value)?,
+            "completed" => {
+                item.completed = match value {
+                    Value::Bool(completed) => *completed,
+                    Value::Number(number) => number.as_f64() != Some(0.0),
+                    _ => matches!(
+                        text(key, value)?.to_lowercase().as_str(),
+                        "true" | "x" | "yes" | "1" | "done"
+                    ),
+                }
+            }
+            "priority" => item.priority = Some(text(key, value)?.parse()?),
+            "due" | "created" => {
+                let written = text(key, value)?;
+                let date = parse_date_time(&written)
+                    .ok_or(format!("Couldn't understand {:?}", written))?;
+                match key.as_str() {
+                    "due" => item.due = Some(date),
+                    _ => item.created = Some(date),
+                }
+            }
+            "tags" => {
+                let written = match value {
+                    Value::Array(tags) => tags
+                        .iter()
+                        .map(|tag| text(key, tag))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    _ => text(key, value)?
+                        .split_whitespace()
+                        .map(str::to_string)
+                        .collect(),
+                };
+                for tag in written.iter().map(|tag| tags::normalize(tag)) {
+                    if !item.tags.contains(&tag) {
+                        item.tags.push(tag);
+                    }
+                }
+            }
+            "notes" => item.notes = Some(text(key, value)?),
+            _ => {
+                return Err(format!(
+                    "{} isn't part of an item, expected name, completed, priority, due, \
+                     tags, created or notes",
+                    key
+                ))
+            }
+        }
+    }
+    match item.name.is_empty() {
+        true => Err("It needs a name".to_string()),
+        false => Ok(item),
+    }
+}
+
+/// What to do with an imported item matching one already on the list.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OnConflict {
+    /// Leave the item on the list as it is.
+    Skip,
+    /// Replace it with the imported one.
+    Overwrite,
+    /// Add the imported one as well.
+    #[default]
+    Duplicate,
+    /// Fill in what the list's item is missing from the imported one.
+    Merge,
+}
+
+impl FromStr for OnConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(OnConflict::Skip),
+            "overwrite" => Ok(OnConflict::Overwrite),
+            "duplicate" => Ok(OnConflict::Duplicate),
+            "merge" => Ok(OnConflict::Merge),
+            _ => Err(format!(
+                "No conflict policy called {}, expected skip, overwrite, duplicate or merge",
+                s
+            )),
+        }
+    }
+}
+
+/// How an import went.
+#[derive(Debug, Default, PartialEq)]
+pub struct Imported {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Whether the names match, ignoring case and `+tags`, which formats
+/// without a place for tags write into the name.
+fn same_name(a: &Item, b: &Item) -> bool {
+    let words = |item: &Item| -> Vec<String> {
+        item.name
+            .split_whitespace()
+            .filter(|word| !word.starts_with('+'))
+            .map(str::to_lowercase)
+            .collect()
+    };
+    words(a) == words(b)
+}
+
+/// `existing` with what it's missing taken from `incoming`: its tags and
+/// people, dates, priority and so on, and being completed.
+fn merge_item(existing: &Item, incoming: &Item) -> Item {
+    let mut merged = existing.clone();
+    if incoming.completed && !merged.completed {
+        merged.completed = true;
+        merged.completed_at = incoming.completed_at;
+    }
+    for tag in &incoming.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    for person in &incoming.contacts {
+        if !merged.contacts.contains(person) {
+            merged.contacts.push(person.clone());
+        }
+    }
+    merged.due = merged.due.or(incoming.due);
+    merged.expires = merged.expires.or(incoming.expires);
+    merged.starts_on = merged.starts_on.or(incoming.starts_on);
+    merged.priority = merged.priority.or(incoming.priority);
+    merged.energy = merged.energy.or(incoming.energy);
+    merged.recurrence = merged.recurrence.clone().or(incoming.recurrence.clone());
+    merged.created = merged.created.or(incoming.created);
+    merged
+}
+
+/// The change adding `imported` to `todos`, with conflicts settled by
+/// `on_conflict`, and how it went.
+pub fn import_into(
+    todos: &Todos,
+    imported: Todos,
+    on_conflict: OnConflict,
+) -> (Operation, Imported) {
+    let mut after = todos.clone();
+    let mut operations = Vec::new();
+    let mut counts = Imported::default();
+    // Where the last item that wasn't a subtask ended up.
+    let mut parent = None;
+
+    for item in imported {
+        let (candidates, end) = match parent.filter(|_| item.subtask) {
+            Some(parent) => {
+                let children = subtasks::children(&after, parent);
+                (children.clone().collect::<Vec<_>>(), children.end)
+            }
+            None => (
+                (0..after.len()).filter(|&i| !after[i].subtask).collect(),
+                after.len(),
+            ),
+        };
+        let found = candidates
+            .into_iter()
+            .find(|&i| same_name(&after[i], &item));
+
+        let operation = match (found, on_conflict) {
+            (Some(_), OnConflict::Skip) => {
+                counts.skipped += 1;
+                None
+            }
+            (Some(index), OnConflict::Overwrite) => {
+                counts.updated += 1;
+                let mut item = item.clone();
+                item.subtask = after[index].subtask;
+                item.id = after[index].id.or(item.id);
+                Some(Operation::replace(&after, index, item))
+            }
+            (Some(index), OnConflict::Merge) => {
+                let merged = merge_item(&after[index], &item);
+                match merged == after[index] {
+                    true => {
+                        counts.skipped += 1;
+                        None
+                    }
+                    false => {
+                        counts.updated += 1;
+                        Some(Operation::replace(&after, index, merged))
+                    }
+                }
+            }
+            (Some(_), OnConflict::Duplicate) | (None, _) => {
+                counts.added += 1;
+                let mut item = item.clone();
+                // A second copy of an item is an item of its own.
+                if item.id.is_some() && after.iter().any(|other| other.id == item.id) {
+                    item.id = Some(uuid::Uuid::new_v4());
+                }
+                Some(Operation::Insert { index: end, item })
+            }
+        };
+        let index = match &operation {
+            Some(Operation::Insert { index, .. }) => *index,
+            _ => found.expect("only inserted when not found"),
+        };
+        if !item.subtask {
+            parent = Some(index);
+        }
+        if let Some(operation) = operation {
+            operation.apply(&mut after).expect("made for this list");
+            operations.push(operation);
+        }
+    }
+
+    (Operation::Group { operations }, counts)
+}
+
+/// The name with the tags that aren't written in it added as `+tag`s, for
+/// formats without a place of their own for tags.
+fn name_with_tags(item: &Item) -> String {
+    let named = tags::parse_tags(&item.name);
+    let mut name = item.name.clone();
+    for tag in item.tags.iter().filter(|tag| !named.contains(tag)) {
+        write!(name, " +{}", tag).unwrap();
+    }
+    name
+}
+
+fn export_todo_txt(todos: &Todos) -> String {
+    let mut out = String::new();
+    for item in todos {
+        if item.completed {
+            out.push_str("x ");
+        }
+        let letter = match item.priority {
+            Some(Priority::High) => Some('A'),
+            Some(Priority::Medium) => Some('B'),
+            Some(Priority::Low) => Some('C'),
+            None => None,
+        };
+        if let Some(letter) = letter {
+            write!(out, "({}) ", letter).unwrap();
+        }
+        // A single date on a completed item would be read as the date it
+        // was completed, which isn't known.
+        if let Some(created) = item.created.filter(|_| !item.completed) {
+            write!(out, "{} ", created.format("%Y-%m-%d")).unwrap();
+        }
+        out.push_str(&name_with_tags(item));
+        if let Some(due) = item.due {
+            write!(out, " due:{}", due.format("%Y-%m-%d")).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn import_todo_txt(content: &str) -> Todos {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut words: Vec<&str> = line.split_whitespace().collect();
+            let completed = words.first() == Some(&"x");
+            if completed {
+                words.remove(0);
+            }
+            let priority = match words.first() {
+                Some(&"(A)") => Some(Priority::High),
+                Some(&"(B)") => Some(Priority::Medium),
+                Some(&"(C)") => Some(Priority::Low),
+                _ => None,
+            };
+            if priority.is_some() {
+                words.remove(0);
+            }
+            // Completion and creation dates, in that order when both are there.
+            let mut dates = Vec::new();
+            while let Some(date) = words
+                .first()
+                .and_then(|word| word.parse::<NaiveDate>().ok())
+            {
+                dates.push(date);
+                words.remove(0);
+            }
+            let created = match completed {
+                true => dates.get(1),
+                false => dates.first(),
+            };
+
+            let mut due = None;
+            words.retain(|word| match word.strip_prefix("due:") {
+                Some(value) => {
+                    due = date::parse_due(value, clock::now());
+                    false
+                }
+                None => true,
+            });
+
+            let mut item = Item::new(words.join(" "));
+            item.completed = completed;
+            item.priority = priority;
+            item.due = due;
+            item.created = created.and_then(|&created| date::at(created, None));
+            item
+        })
+        .collect()
+}
+
+fn export_markdown(todos: &Todos) -> String {
+    let mut out = String::new();
+    for item in todos {
+        let indent = if item.subtask { "  " } else { "" };
+        let check = if item.completed { 'x' } else { ' ' };
+        writeln!(out, "{}- [{}] {}", indent, check, name_with_tags(item)).unwrap();
+    }
+    out
+}
+
+/// Reads `- [ ]` and `* [x]` lines, skipping anything else such as
+/// headings and notes.
+fn import_markdown(content: &str) -> Todos {
+    content
+        .lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            let rest = trimmed
+                .strip_prefix("- [")
+                .or_else(|| trimmed.strip_prefix("* ["))?;
+            let (check, name) = rest.split_once("] ")?;
+            let completed = match check {
+                " " => false,
+                "x" | "X" => true,
+                _ => return None,
+            };
+
+            let mut item = Item::new(name.trim().to_string());
+            item.completed = completed;
+            item.subtask = trimmed.len() < line.len();
+            Some(item)
+        })
+        .collect()
+}
+
+const CSV_COLUMNS: [&str; 6] = ["name", "completed", "priority", "due", "tags", "created"];
+
+fn export_csv(todos: &Todos) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+    for item in todos {
+        let fields = [
+            item.name.clone(),
+            item.completed.to_string(),
+            item.priority.map(|p| p.to_string()).unwrap_or_default(),
+            item.due
+                .map(|due| date::format_due(&due))
+                .unwrap_or_default(),
+            item.tags.join(" "),
+            item.created
+                .map(|created| date::format_due(&created))
+                .unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Quotes a field when it needs it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits CSV into records of fields, following RFC 4180 quoting.
+fn csv_records(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Reads CSV with a header row. Only the name column is required; other
+/// columns are matched by name and unknown ones are ignored.
+fn import_csv(content: &str) -> Result<Todos, String> {
+    let mut records = csv_records(content).into_iter();
+    let header = records.next().unwrap_or_default();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let name_column = column("name").ok_or("The CSV needs a name column")?;
+    let [completed, priority, due, tag_column, created] =
+        ["completed", "priority", "due", "tags", "created"].map(column);
+
+    let mut todos = Vec::new();
+    for (row, record) in records.enumerate() {
+        let error = |e: String| format!("Row {}: {}", row + 2, e);
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| record.get(column))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+        let Some(name) = field(Some(name_column)) else {
+            continue;
+        };
+
+        let mut item = Item::new(name.to_string());
+        item.completed = matches!(field(completed), Some("true" | "x" | "yes" | "1"));
+        item.priority = field(priority).map(str::parse).transpose().map_err(error)?;
+        item.due = field(due)
+            .map(|due| parse_date_time(due).ok_or(format!("Couldn't understand {:?}", due)))
+            .transpose()
+            .map_err(error)?;
+        item.created = field(created).and_then(parse_date_time);
+        for tag in field(tag_column).unwrap_or_default().split_whitespace() {
+            let tag = tags::normalize(tag);
+            if !item.tags.contains(&tag) {
+                item.tags.push(tag);
+            }
+        }
+        todos.push(item);
+    }
+
+    Ok(todos)
+}
+
+/// Dates as written by the export, or in RFC 3339 as other tools do.
+fn parse_date_time(s: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|date| date.with_timezone(&Local))
+        .ok()
+        .or_else(|| date::parse_due(s, clock::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    /// The sample without creation times, which differ between runs.
+    fn stable_sample() -> Todos {
+        let mut todos = sample();
+        for item in &mut todos {
+            item.created = None;
+        }
+        todos[2].subtask = true;
+        todos
+    }
+
+    #[test]
+    fn todo_txt() {
+        insta::assert_snapshot!(Format::TodoTxt.export(&stable_sample()));
+    }
+
+    #[test]
+    fn markdown() {
+        insta::assert_snapshot!(Format::Markdown.export(&stable_sample()));
+    }
+
+    #[test]
+    fn csv() {
+        insta::assert_snapshot!(Format::Csv.export(&stable_sample()));
+    }
+
+    #[test]
+    fn imports_read_back_exports() {
+        for format in [Format::TodoTxt, Format::Markdown, Format::Csv, Format::Json] {
+            let exported = format.export(&sample());
+            let imported = format.import(&exported).unwrap();
+            assert_eq!(format.export(&imported), exported, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn conflicts() {
+        let todos = stable_sample();
+        let mut milk = Item::new("Buy milk +dairy".to_string());
+        milk.completed = true;
+        let mut oats = Item::new("oats".to_string());
+        oats.subtask = true;
+        let imported = vec![milk, oats, Item::new("walk dog".to_string())];
+
+        let import = |on_conflict| {
+            let mut after = todos.clone();
+            let (operation, counts) = import_into(&todos, imported.clone(), on_conflict);
+            operation.apply(&mut after).unwrap();
+            (after, counts)
+        };
+
+        let (after, counts) = import(OnConflict::Skip);
+        assert_eq!((counts.added, counts.updated, counts.skipped), (2, 0, 1));
+        assert_eq!(after.len(), 7);
+        assert_eq!(after[1], todos[1]);
+        // Added under the item it was imported under, after its subtask.
+        assert_eq!(after[3].name, "oats");
+        assert_eq!(after[6].name, "walk dog");
+
+        let (after, _) = import(OnConflict::Overwrite);
+        assert_eq!(after[1].name, "Buy milk +dairy");
+        assert_eq!(after[1].tags, vec!["dairy"]);
+
+        let (after, counts) = import(OnConflict::Merge);
+        assert_eq!(counts.updated, 1);
+        assert_eq!(after[1].name, "buy milk");
+        assert_eq!(after[1].tags, vec!["home", "dairy"]);
+        assert!(after[1].completed);
+
+        let (after, counts) = import(OnConflict::Duplicate);
+        assert_eq!(counts.added, 3);
+        assert_eq!(after.len(), 8);
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let content = "name,tags\n\"say \"\"hi\"\", then go\",a b\n";
+        let todos = import_csv(content).unwrap();
+        assert_eq!(todos[0].name, "say \"hi\", then go");
+        assert_eq!(todos[0].tags, vec!["a", "b"]);
+        assert!(export_csv(&todos).contains("\"say \"\"hi\"\", then go\""));
+    }
+
+    #[test]
+    fn mapped_json() {
+        let content = r#"{"tasks": [
+            {"title": "buy milk", "done": true, "labels": ["Home"], "prio": "high"},
+            {"title": "call bob", "done": false, "labels": [], "due": "2024-06-01T09:00:00Z"}
+        ]}"#;
+        let todos = import_mapped(
+            content,
+            ".tasks[] | {name: .title, completed: .done, tags: .labels, priority: .prio, due}",
+        )
+        .unwrap();
+        assert_eq!(todos.len(), 2);
+        assert!(todos[0].completed);
+        assert_eq!(todos[0].tags, vec!["home"]);
+        assert_eq!(todos[0].priority, Some(Priority::High));
+        assert_eq!(todos[1].name, "call bob");
+        assert!(todos[1].due.is_some());
+
+        assert!(import_mapped(content, ".tasks[] | .title").is_err());
+        assert!(import_mapped(content, ".tasks[] | {title}").is_err());
+        assert!(import_mapped(content, ".tasks[] | {name: .done}").is_err());
+    }
+}
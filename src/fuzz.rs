@@ -0,0 +1,87 @@
+//! Entry points for the fuzz targets in `fuzz/`, one for each parser that
+//! reads what people type or files from elsewhere. Each feeds it arbitrary
+//! input and panics if it panics, or if what it made wouldn't come back the
+//! same from todo.json, which would corrupt the list. Only built with the
+//! `fuzz` feature, which `fuzz/` turns on.
+//!
+//! ```sh
+//! cargo +nightly fuzz run quickadd
+//! ```
+
+use chrono::{DateTime, Local, TimeZone};
+
+use crate::formats::{self, Format};
+use crate::priority::{self, SortOrder};
+use crate::recurrence::{self, Recurrence};
+use crate::{date, defer, focus, mapping, quickadd, search, Item, Todos};
+
+/// The time the input is read at, the same each run so crashes repeat.
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap()
+}
+
+/// Checks the items would be read back from todo.json as they are.
+fn stored(todos: &Todos) {
+    let json = serde_json::to_string(todos).unwrap();
+    assert_eq!(&serde_json::from_str::<Todos>(&json).unwrap(), todos);
+}
+
+/// A line with inline details, as `todo add` takes it.
+pub fn quickadd(line: &str) {
+    stored(&vec![quickadd::parse(line, now())]);
+}
+
+/// A due date, which should read back the same once written out, and the
+/// other times and lengths people type: snoozes and focus blocks.
+pub fn date(text: &str) {
+    if let Some(due) = date::parse_due(text, now()) {
+        assert_eq!(date::parse_due(&date::format_due(&due), now()), Some(due));
+    }
+    let _ = defer::parse_snooze(text, now());
+    let _ = focus::parse_length(text);
+}
+
+/// A search, sort keys like `due asc, priority desc` and a repeat rule,
+/// over an item named `name`.
+pub fn query(name: &str, query: &str) {
+    let todos = vec![Item::new(name.to_string())];
+    search::find(&todos, query, false);
+    search::find(&todos, query, true);
+    #[cfg(feature = "tui")]
+    search::matched(name, query, true);
+    if let Ok(sort) = query.parse::<SortOrder>() {
+        priority::display_order(&todos, &sort);
+    }
+    if let Ok(recurrence) = query.parse::<Recurrence>() {
+        let mut item = todos[0].clone();
+        item.due = Some(now());
+        item.recurrence = Some(recurrence);
+        recurrence::next(&item, now());
+    }
+}
+
+/// A file to import, in the format `format` picks.
+pub fn import(format: u8, content: &str) {
+    let format = match format % 4 {
+        0 => Format::TodoTxt,
+        1 => Format::Markdown,
+        2 => Format::Csv,
+        _ => Format::Json,
+    };
+    if let Ok(todos) = format.import(content) {
+        stored(&todos);
+        format.export(&todos);
+    }
+}
+
+/// JSON in another app's shape, with a `--map` for it.
+pub fn mapped(map: &str, content: &str) {
+    if let Ok(expr) = mapping::parse(map) {
+        if let Ok(content) = serde_json::from_str(content) {
+            let _ = mapping::run(&expr, &content);
+        }
+    }
+    if let Ok(todos) = formats::import_mapped(content, map) {
+        stored(&todos);
+    }
+}
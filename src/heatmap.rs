@@ -0,0 +1,29 @@
+//! Coloring open items by age, from green when new to red once they've
+//! been sitting around for a month, so neglected items stand out.
+
+use chrono::{DateTime, Local};
+use crossterm::style::Color;
+
+/// Items this old or older get the stalest color.
+const STALE_DAYS: f64 = 30.0;
+
+const FRESH: (f64, f64, f64) = (0.0, 200.0, 0.0);
+const MIDDLE: (f64, f64, f64) = (220.0, 200.0, 0.0);
+const STALE: (f64, f64, f64) = (220.0, 0.0, 0.0);
+
+pub fn color(created: DateTime<Local>, now: DateTime<Local>) -> Color {
+    let days = (now - created).num_seconds() as f64 / 86_400.0;
+    let t = (days / STALE_DAYS).clamp(0.0, 1.0);
+
+    let (from, to, t) = if t < 0.5 {
+        (FRESH, MIDDLE, t * 2.0)
+    } else {
+        (MIDDLE, STALE, (t - 0.5) * 2.0)
+    };
+    let mix = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+    Color::Rgb {
+        r: mix(from.0, to.0),
+        g: mix(from.1, to.1),
+        b: mix(from.2, to.2),
+    }
+}
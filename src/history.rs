@@ -0,0 +1,397 @@
+//! Undo and redo. Adding, checking, removing and editing items are
+//! recorded as operations that know how to reverse themselves, kept in a
+//! file next to the list so `todo undo` works across runs.
+//!
+//! Each operation carries the items it touched, and is only undone when
+//! the list still holds them where it left them; otherwise the list has
+//! changed in some other way since and undoing would hit the wrong item.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock, recurrence, store, subtasks, Item, Todos};
+
+/// How many operations can be undone.
+const LIMIT: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Insert {
+        index: usize,
+        item: Item,
+    },
+    Remove {
+        index: usize,
+        item: Item,
+    },
+    Replace {
+        index: usize,
+        before: Box<Item>,
+        after: Box<Item>,
+    },
+    /// Several operations done and undone together, like checking off a
+    /// recurring item and adding its next occurrence.
+    Group {
+        operations: Vec<Operation>,
+    },
+}
+
+impl Operation {
+    pub fn add(todos: &Todos, item: Item) -> Self {
+        Operation::Insert {
+            index: todos.len(),
+            item,
+        }
+    }
+
+    /// Removes an item along with its subtasks.
+    pub fn remove(todos: &Todos, index: usize) -> Self {
+        // Each removal moves the next subtask up to `index`.
+        let operations = std::iter::once(index)
+            .chain(subtasks::children(todos, index))
+            .map(|i| Operation::Remove {
+                index,
+                item: todos[i].clone(),
+            })
+            .collect();
+        Operation::group(operations)
+    }
+
+    /// Checks or unchecks an item. Checking off a recurring item also adds
+    /// its next occurrence, and a parent is checked once all its subtasks
+    /// are, or unchecked again along with one of them. Unchecking a
+    /// cancelled item reopens it without its expiry date.
+    pub fn toggle(todos: &Todos, index: usize) -> Self {
+        let mut after = todos[index].clone();
+        after.completed = !after.completed;
+        if after.cancelled {
+            // Reopened by hand, so it shouldn't expire again straight away.
+            after.cancelled = false;
+            after.expires = None;
+        }
+        let completed = after.completed;
+        let now = clock::now();
+        after.completed_at = completed.then_some(now);
+        let mut operations = vec![Operation::replace(todos, index, after)];
+        let next = match completed {
+            true => recurrence::next(&todos[index], now),
+            false => None,
+        };
+
+        if let Some(parent) = subtasks::parent(todos, index) {
+            // A recurring subtask's next occurrence keeps the parent open.
+            let done = subtasks::all_done(todos, parent, index, completed) && next.is_none();
+            if todos[parent].completed != done {
+                let mut after = todos[parent].clone();
+                after.completed = done;
+                after.completed_at = done.then_some(now);
+                operations.push(Operation::replace(todos, parent, after));
+            }
+        }
+
+        if let Some(next) = next {
+            let index = match subtasks::parent(todos, index) {
+                Some(parent) => subtasks::children(todos, parent).end,
+                None => todos.len(),
+            };
+            operations.push(Operation::Insert { index, item: next });
+        }
+
+        Operation::group(operations)
+    }
+
+    /// Toggles several items as one change. Items are toggled last first,
+    /// so next occurrences added for recurring ones don't move the rest,
+    /// and an item already toggled along with another, like a parent with
+    /// its last subtask, is left as it is.
+    pub fn toggle_many(todos: &Todos, indices: &[usize]) -> Self {
+        let mut after = todos.clone();
+        let mut operations = Vec::new();
+        for &index in indices.iter().rev() {
+            if after[index].completed != todos[index].completed {
+                continue;
+            }
+            let operation = Operation::toggle(&after, index);
+            operation.apply(&mut after).expect("made for this list");
+            operations.push(operation);
+        }
+        Operation::group(operations)
+    }
+
+    /// Removes several items, with their subtasks, as one change.
+    pub fn remove_many(todos: &Todos, indices: &[usize]) -> Self {
+        let mut after = todos.clone();
+        let mut operations = Vec::new();
+        for &index in indices.iter().rev() {
+            let operation = Operation::remove(&after, index);
+            operation.apply(&mut after).expect("made for this list");
+            operations.push(operation);
+        }
+        Operation::group(operations)
+    }
+
+    /// Moves an item, with its subtasks, so it ends up at `to`, or last if
+    /// `to` is past the end. A subtask stays among its parent's subtasks,
+    /// and an item never lands between another one and its subtasks.
+    pub fn move_to(todos: &Todos, from: usize, to: usize) -> Self {
+        let block: Vec<Item> = std::iter::once(from)
+            .chain(subtasks::children(todos, from))
+            .map(|i| todos[i].clone())
+            .collect();
+        let mut rest = todos.clone();
+        rest.drain(from..from + block.len());
+
+        let mut at = to.min(rest.len());
+        match subtasks::parent(todos, from) {
+            Some(parent) => {
+                let siblings = subtasks::children(&rest, parent);
+                at = at.clamp(siblings.start, siblings.end);
+            }
+            None => {
+                while at < rest.len() && rest[at].subtask {
+                    at += 1;
+                }
+            }
+        }
+
+        let removals = block.iter().map(|item| Operation::Remove {
+            index: from,
+            item: item.clone(),
+        });
+        let insertions = block.iter().enumerate().map(|(i, item)| Operation::Insert {
+            index: at + i,
+            item: item.clone(),
+        });
+        Operation::Group {
+            operations: removals.chain(insertions).collect(),
+        }
+    }
+
+    fn group(mut operations: Vec<Operation>) -> Self {
+        match operations.len() {
+            1 => operations.remove(0),
+            _ => Operation::Group { operations },
+        }
+    }
+
+    pub fn replace(todos: &Todos, index: usize, after: Item) -> Self {
+        Operation::Replace {
+            index,
+            before: Box::new(todos[index].clone()),
+            after: Box::new(after),
+        }
+    }
+
+    fn inverse(self) -> Self {
+        match self {
+            Operation::Insert { index, item } => Operation::Remove { index, item },
+            Operation::Remove { index, item } => Operation::Insert { index, item },
+            Operation::Replace {
+                index,
+                before,
+                after,
+            } => Operation::Replace {
+                index,
+                before: after,
+                after: before,
+            },
+            Operation::Group { operations } => Operation::Group {
+                operations: operations.into_iter().rev().map(Self::inverse).collect(),
+            },
+        }
+    }
+
+    pub(crate) fn apply(&self, todos: &mut Todos) -> Result<(), String> {
+        let changed = || "The list has changed since, so this can't be replayed".to_string();
+
+        match self {
+            Operation::Insert { index, item } => {
+                if *index > todos.len() {
+                    return Err(changed());
+                }
+                todos.insert(*index, item.clone());
+            }
+            Operation::Remove { index, item } => {
+                if todos.get(*index) != Some(item) {
+                    return Err(changed());
+                }
+                todos.remove(*index);
+            }
+            Operation::Replace {
+                index,
+                before,
+                after,
+            } => {
+                if todos.get(*index) != Some(before) {
+                    return Err(changed());
+                }
+                todos[*index] = Item::clone(after);
+            }
+            Operation::Group { operations } => {
+                for (done, operation) in operations.iter().enumerate() {
+                    if let Err(e) = operation.apply(todos) {
+                        for operation in operations[..done].iter().rev() {
+                            operation.clone().inverse().apply(todos)?;
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Insert { item, .. } => write!(f, "adding \"{}\"", item.name),
+            Operation::Remove { item, .. } => write!(f, "removing \"{}\"", item.name),
+            Operation::Replace { before, .. } => write!(f, "changing \"{}\"", before.name),
+            Operation::Group { operations } => match (operations.first(), operations.last()) {
+                (Some(Operation::Remove { item, .. }), Some(Operation::Insert { .. })) => {
+                    write!(f, "moving \"{}\"", item.name)
+                }
+                (Some(operation), _) => operation.fmt(f),
+                (None, _) => write!(f, "nothing"),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl History {
+    fn load() -> Self {
+        let content = std::fs::read_to_string(store::sibling("history.json")).unwrap_or_default();
+
+        serde_json::from_str(&content).unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let write = || -> std::io::Result<()> {
+            let mut writer = BufWriter::new(File::create(store::sibling("history.json"))?);
+            serde_json::to_writer(&mut writer, self)?;
+            writer.flush()
+        };
+
+        write().map_err(|e| format!("Could not save the undo history: {}", e))
+    }
+}
+
+/// What the last `count` changes that can be undone were, oldest first,
+/// by kind only, as `insert` or `group of 2`.
+pub fn recent(count: usize) -> Vec<String> {
+    let undo = History::load().undo;
+    undo[undo.len().saturating_sub(count)..]
+        .iter()
+        .map(|operation| match operation {
+            Operation::Insert { .. } => "insert".to_string(),
+            Operation::Remove { .. } => "remove".to_string(),
+            Operation::Replace { .. } => "replace".to_string(),
+            Operation::Group { operations } => format!("group of {}", operations.len()),
+        })
+        .collect()
+}
+
+/// Applies `operation` to the list and records it so it can be undone.
+/// Anything undone before can't be redone after this.
+pub fn perform(todos: &mut Todos, operation: Operation) -> Result<(), String> {
+    operation.apply(todos)?;
+
+    let mut history = History::load();
+    history.undo.push(operation);
+    if history.undo.len() > LIMIT {
+        history.undo.remove(0);
+    }
+    history.redo.clear();
+    history.save()
+}
+
+/// Reverts the last operation, returning what was undone.
+pub fn undo(todos: &mut Todos) -> Result<String, String> {
+    let mut history = History::load();
+    let operation = history.undo.pop().ok_or("Nothing to undo")?;
+
+    operation.clone().inverse().apply(todos)?;
+    let message = format!("Undid {}", operation);
+    history.redo.push(operation);
+    history.save()?;
+
+    Ok(message)
+}
+
+/// Applies the last undone operation again, returning what was redone.
+pub fn redo(todos: &mut Todos) -> Result<String, String> {
+    let mut history = History::load();
+    let operation = history.redo.pop().ok_or("Nothing to redo")?;
+
+    operation.apply(todos)?;
+    let message = format!("Redid {}", operation);
+    history.undo.push(operation);
+    history.save()?;
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::tests::item;
+
+    /// A list and an operation that applies to it.
+    fn list_and_operation() -> impl Strategy<Value = (Todos, Operation)> {
+        (
+            prop::collection::vec(item(), 1..6),
+            item(),
+            0..7u8,
+            any::<prop::sample::Index>(),
+        )
+            .prop_map(|(todos, new_item, kind, index)| {
+                let index = index.index(todos.len());
+                let every_other: Vec<usize> = (index % 2..todos.len()).step_by(2).collect();
+                let operation = match kind {
+                    0 => Operation::add(&todos, new_item),
+                    1 => Operation::remove(&todos, index),
+                    2 => Operation::toggle(&todos, index),
+                    3 => Operation::toggle_many(&todos, &every_other),
+                    4 => Operation::remove_many(&todos, &every_other),
+                    5 => Operation::move_to(&todos, index, every_other[0]),
+                    _ => Operation::replace(&todos, index, new_item),
+                };
+                (todos, operation)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn inverse_undoes_an_operation((todos, operation) in list_and_operation()) {
+            let mut changed = todos.clone();
+            operation.apply(&mut changed).unwrap();
+            operation.inverse().apply(&mut changed).unwrap();
+            prop_assert_eq!(changed, todos);
+        }
+
+        #[test]
+        fn operations_survive_a_save((todos, operation) in list_and_operation()) {
+            let json = serde_json::to_string(&operation).unwrap();
+            let read: Operation = serde_json::from_str(&json).unwrap();
+
+            let (mut expected, mut actual) = (todos.clone(), todos);
+            operation.apply(&mut expected).unwrap();
+            read.apply(&mut actual).unwrap();
+            prop_assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! The keys `todo tui` answers to, which can be changed in the config:
+//!
+//! ```toml
+//! [keys]
+//! preset = "vim"          # gg/G to jump, dd to delete, x to check
+//! delete = ["dd", "<del>"]
+//! quit = "<c-c>"
+//! ```
+//!
+//! An action given there gets those keys instead of the preset's. A key
+//! is a character, or a name in angle brackets: `<space>`, `<esc>`,
+//! `<enter>`, `<tab>`, `<del>`, `<up>`, `<down>`, `<home>` and `<end>`,
+//! and `<c-x>` for Ctrl and a letter. Several make a sequence, like `gg`,
+//! pressed one after the other.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Down,
+    Up,
+    Top,
+    Bottom,
+    Check,
+    Delete,
+    Undo,
+    Add,
+    Edit,
+    Filter,
+    Redact,
+    Details,
+}
+
+const ACTIONS: [(&str, Action); 13] = [
+    ("quit", Action::Quit),
+    ("down", Action::Down),
+    ("up", Action::Up),
+    ("top", Action::Top),
+    ("bottom", Action::Bottom),
+    ("check", Action::Check),
+    ("delete", Action::Delete),
+    ("undo", Action::Undo),
+    ("add", Action::Add),
+    ("edit", Action::Edit),
+    ("filter", Action::Filter),
+    ("redact", Action::Redact),
+    ("details", Action::Details),
+];
+
+type Preset = [(&'static str, &'static [&'static str])];
+
+const DEFAULT: &Preset = &[
+    ("quit", &["q", "<esc>"]),
+    ("down", &["<down>", "j"]),
+    ("up", &["<up>", "k"]),
+    ("top", &["<home>"]),
+    ("bottom", &["<end>"]),
+    ("check", &["<space>"]),
+    ("delete", &["d"]),
+    ("undo", &["u"]),
+    ("add", &["a"]),
+    ("edit", &["e"]),
+    ("filter", &["/"]),
+    ("redact", &["r"]),
+    ("details", &["<tab>"]),
+];
+
+/// Changes to `DEFAULT` for vim's muscle memory.
+const VIM: &Preset = &[
+    ("top", &["gg", "<home>"]),
+    ("bottom", &["G", "<end>"]),
+    ("check", &["x", "<space>"]),
+    ("delete", &["dd"]),
+];
+
+/// A key press, as far as bindings tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        Key {
+            code: event.code,
+            // Shift is already in the character, as in `G`.
+            ctrl: event.modifiers.contains(KeyModifiers::CONTROL),
+        }
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.code, self.ctrl) {
+            (KeyCode::Char(c), true) => write!(f, "ctrl-{}", c),
+            (KeyCode::Char(' '), false) => write!(f, "space"),
+            (KeyCode::Char(c), false) => write!(f, "{}", c),
+            (KeyCode::Up, _) => write!(f, "↑"),
+            (KeyCode::Down, _) => write!(f, "↓"),
+            (KeyCode::Esc, _) => write!(f, "esc"),
+            (KeyCode::Enter, _) => write!(f, "enter"),
+            (KeyCode::Tab, _) => write!(f, "tab"),
+            (KeyCode::Delete, _) => write!(f, "del"),
+            (KeyCode::Home, _) => write!(f, "home"),
+            (KeyCode::End, _) => write!(f, "end"),
+            (code, _) => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The keys written in `sequence`, like `gg` or `<c-d>`.
+fn parse_sequence(sequence: &str) -> Result<Vec<Key>, String> {
+    let key = |code| Key { code, ctrl: false };
+    let mut keys = Vec::new();
+    let mut rest = sequence;
+    while let Some(c) = rest.chars().next() {
+        let named = rest
+            .strip_prefix('<')
+            .and_then(|after| after.split_once('>'))
+            .filter(|(name, _)| !name.is_empty());
+        let Some((name, after)) = named else {
+            keys.push(key(KeyCode::Char(c)));
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        let lower = name.to_lowercase();
+        keys.push(match lower.as_str() {
+            "space" => key(KeyCode::Char(' ')),
+            "esc" => key(KeyCode::Esc),
+            "enter" | "cr" => key(KeyCode::Enter),
+            "tab" => key(KeyCode::Tab),
+            "del" => key(KeyCode::Delete),
+            "up" => key(KeyCode::Up),
+            "down" => key(KeyCode::Down),
+            "home" => key(KeyCode::Home),
+            "end" => key(KeyCode::End),
+            _ => match lower
+                .strip_prefix("c-")
+                .map(|c| c.chars().collect::<Vec<_>>())
+            {
+                Some(c) if c.len() == 1 && c[0].is_ascii_alphabetic() => Key {
+                    code: KeyCode::Char(c[0]),
+                    ctrl: true,
+                },
+                _ => return Err(format!("<{}> in {:?} isn't a key", name, sequence)),
+            },
+        });
+        rest = after;
+    }
+    match keys.is_empty() {
+        true => Err("A key can't be empty".to_string()),
+        false => Ok(keys),
+    }
+}
+
+/// The sequences for each action, by name.
+type Table = BTreeMap<&'static str, Vec<String>>;
+
+fn table(preset: &Preset) -> Table {
+    preset
+        .iter()
+        .map(|(action, keys)| (*action, keys.iter().map(|key| key.to_string()).collect()))
+        .collect()
+}
+
+fn strings(action: &str, value: &toml::Value) -> Result<Vec<String>, String> {
+    let error = || format!("keys.{} should be a key or a list of them", action);
+    match value {
+        toml::Value::String(key) => Ok(vec![key.clone()]),
+        toml::Value::Array(keys) => keys
+            .iter()
+            .map(|key| key.as_str().map(str::to_string).ok_or_else(error))
+            .collect(),
+        _ => Err(error()),
+    }
+}
+
+pub struct Bindings {
+    bindings: Vec<(Vec<Key>, Action)>,
+    /// Keys pressed so far of a sequence that isn't finished.
+    pending: Vec<Key>,
+}
+
+impl Bindings {
+    /// The bindings the `[keys]` table in the config makes.
+    pub fn load(config: &BTreeMap<String, toml::Value>) -> Result<Self, String> {
+        let mut table = table(DEFAULT);
+        match config.get("preset").map(|preset| preset.as_str()) {
+            None | Some(Some("default")) => {}
+            Some(Some("vim")) => table.extend(self::table(VIM)),
+            Some(_) => return Err("keys.preset should be \"default\" or \"vim\"".to_string()),
+        }
+        for (action, value) in config.iter().filter(|(action, _)| *action != "preset") {
+            let Some((name, _)) = ACTIONS.iter().find(|(name, _)| name == action) else {
+                let names: Vec<&str> = ACTIONS.iter().map(|(name, _)| *name).collect();
+                return Err(format!(
+                    "keys.{} isn't something to bind, expected preset or one of {}",
+                    action,
+                    names.join(", ")
+                ));
+            };
+            table.insert(name, strings(action, value)?);
+        }
+
+        let mut bindings: Vec<(Vec<Key>, Action)> = Vec::new();
+        for (name, action) in ACTIONS {
+            for sequence in &table[name] {
+                let keys = parse_sequence(sequence)?;
+                if let Some((_, other)) = bindings
+                    .iter()
+                    .find(|(other, _)| other.starts_with(&keys) || keys.starts_with(other))
+                {
+                    let other = ACTIONS.iter().find(|(_, a)| a == other).unwrap().0;
+                    return Err(format!(
+                        "{:?} for {} clashes with a key for {}",
+                        sequence, name, other
+                    ));
+                }
+                bindings.push((keys, action));
+            }
+        }
+        Ok(Bindings {
+            bindings,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Takes a key press, returning the action it finishes, if any.
+    pub fn press(&mut self, key: Key) -> Option<Action> {
+        if !self.pending.is_empty() && key.code == KeyCode::Esc {
+            self.pending.clear();
+            return None;
+        }
+        self.pending.push(key);
+        if !self
+            .bindings
+            .iter()
+            .any(|(keys, _)| keys.starts_with(&self.pending))
+        {
+            // Not the rest of a sequence, so maybe the start of another.
+            self.pending = vec![key];
+        }
+
+        let action = self
+            .bindings
+            .iter()
+            .find(|(keys, _)| *keys == self.pending)
+            .map(|(_, action)| *action);
+        let started = self
+            .bindings
+            .iter()
+            .any(|(keys, _)| keys.starts_with(&self.pending));
+        if action.is_some() || !started {
+            self.pending.clear();
+        }
+        action
+    }
+
+    /// The first key for `action`, as shown in the help.
+    pub fn key(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(_, a)| *a == action)
+            .map(|(keys, _)| keys.iter().map(Key::to_string).collect())
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    /// The line at the bottom of the screen saying which keys do what.
+    pub fn help(&self) -> String {
+        format!(
+            "{}/{} move · {} check · {} add · {} edit · {} delete · {} filter · {} undo · {} redact · {} details · {} quit",
+            self.key(Action::Down),
+            self.key(Action::Up),
+            self.key(Action::Check),
+            self.key(Action::Add),
+            self.key(Action::Edit),
+            self.key(Action::Delete),
+            self.key(Action::Filter),
+            self.key(Action::Undo),
+            self.key(Action::Redact),
+            self.key(Action::Details),
+            self.key(Action::Quit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(bindings: &mut Bindings, keys: &str) -> Vec<Option<Action>> {
+        parse_sequence(keys)
+            .unwrap()
+            .into_iter()
+            .map(|key| bindings.press(key))
+            .collect()
+    }
+
+    #[test]
+    fn sequences() {
+        let config: BTreeMap<String, toml::Value> =
+            toml::from_str("preset = \"vim\"\nquit = [\"<c-c>\", \"ZZ\"]\n").unwrap();
+        let mut bindings = Bindings::load(&config).unwrap();
+
+        assert_eq!(press(&mut bindings, "gg"), [None, Some(Action::Top)]);
+        assert_eq!(press(&mut bindings, "G"), [Some(Action::Bottom)]);
+        // A sequence left unfinished starts over with the next key.
+        assert_eq!(press(&mut bindings, "dj"), [None, Some(Action::Down)]);
+        assert_eq!(press(&mut bindings, "<c-c>"), [Some(Action::Quit)]);
+        assert_eq!(press(&mut bindings, "q"), [None]);
+        assert_eq!(
+            bindings.help(),
+            "↓/↑ move · x check · a add · e edit · dd delete · / filter · u undo · r redact · tab details · ctrl-c quit"
+        );
+
+        assert_eq!(
+            press(&mut Bindings::load(&BTreeMap::new()).unwrap(), "d"),
+            [Some(Action::Delete)]
+        );
+    }
+
+    #[test]
+    fn bad_bindings() {
+        let load = |toml: &str| Bindings::load(&toml::from_str(toml).unwrap());
+        assert!(load("undo = \"d\"").is_err());
+        assert!(load("jump = \"J\"").is_err());
+        assert!(load("preset = \"emacs\"").is_err());
+        assert!(load("quit = \"<hyper-q>\"").is_err());
+        assert!(load("quit = 1").is_err());
+        assert!(load("undo = \"U\"").is_ok());
+    }
+}
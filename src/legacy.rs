@@ -0,0 +1,158 @@
+//! `todo migrate`: finding the todo.json files older versions left behind,
+//! when the list was kept in whatever directory `todo` ran in, and merging
+//! them into the list in the data directory. Only the current directory's
+//! is moved over by itself, see `store::init`; the others are looked for
+//! in the current directory's parents, the home directory, and Documents
+//! and Desktop.
+//!
+//! Merged files are renamed to `todo.json.migrated` rather than removed, so
+//! they aren't found again but nothing is lost.
+
+use std::path::{Path, PathBuf};
+
+use crate::screen::Screen;
+use crate::{store, Todos};
+
+const FILE_NAME: &str = "todo.json";
+const MIGRATED: &str = "todo.json.migrated";
+/// How many items of each file the preview shows.
+const PREVIEW: usize = 5;
+
+/// A todo.json found, with its items if it's a todo-rs list.
+pub struct Found {
+    pub path: PathBuf,
+    pub todos: Option<Todos>,
+}
+
+/// The directories older versions might have left a todo.json in.
+fn places(current: &Path, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut places: Vec<PathBuf> = current.ancestors().map(Path::to_path_buf).collect();
+    let others = [
+        home.map(Path::to_path_buf),
+        dirs::document_dir(),
+        dirs::desktop_dir(),
+    ];
+    for place in others.into_iter().flatten() {
+        if !places.contains(&place) {
+            places.push(place);
+        }
+    }
+    places
+}
+
+/// The todo.json files left behind, apart from the list in use.
+pub fn find() -> Vec<Found> {
+    let current = std::env::current_dir().unwrap_or_default();
+    let in_use = std::fs::canonicalize(store::path()).ok();
+    let mut found: Vec<Found> = Vec::new();
+    for place in places(&current, dirs::home_dir().as_deref()) {
+        let path = place.join(FILE_NAME);
+        let canonical = std::fs::canonicalize(&path).ok();
+        if !path.is_file()
+            || canonical == in_use
+            || found
+                .iter()
+                .any(|other| std::fs::canonicalize(&other.path).ok() == canonical)
+        {
+            continue;
+        }
+        let todos = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        found.push(Found { path, todos });
+    }
+    found
+}
+
+/// What's in each file found, and what `--merge` would do.
+pub fn print_found(screen: &mut Screen, found: &[Found]) {
+    if found.is_empty() {
+        writeln!(screen, "[No todo.json left by older versions]\n");
+        return;
+    }
+
+    writeln!(screen, "Found {} left by older versions:\n", found.len());
+    for file in found {
+        let Some(todos) = &file.todos else {
+            writeln!(
+                screen,
+                "{}, not a todo-rs list, left alone\n",
+                file.path.display()
+            );
+            continue;
+        };
+        let open = todos.iter().filter(|item| !item.completed).count();
+        writeln!(
+            screen,
+            "{}, {} items, {} open",
+            file.path.display(),
+            todos.len(),
+            open
+        );
+        for item in todos.iter().take(PREVIEW) {
+            let check = if item.completed { "x" } else { " " };
+            writeln!(screen, "  [{}] {}", check, item.name);
+        }
+        if todos.len() > PREVIEW {
+            writeln!(screen, "  ... and {} more", todos.len() - PREVIEW);
+        }
+        writeln!(screen);
+    }
+    if found.iter().all(|file| file.todos.is_none()) {
+        return;
+    }
+    writeln!(
+        screen,
+        "`todo migrate --merge` adds them to the list, filling in items it has\n\
+         already, and renames each to {}.\n",
+        MIGRATED
+    );
+}
+
+/// Renames a merged file so it isn't found again. Returns the new path.
+pub fn set_aside(path: &Path) -> Result<PathBuf, String> {
+    let aside = path.with_file_name(MIGRATED);
+    std::fs::rename(path, &aside)
+        .map_err(|e| format!("Could not rename {}: {}", path.display(), e))?;
+    Ok(aside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn looks_up_from_the_current_directory() {
+        let places = places(Path::new("/home/me/src/app"), Some(Path::new("/home/me")));
+        assert_eq!(
+            places[..5],
+            ["/home/me/src/app", "/home/me/src", "/home/me", "/home", "/"].map(PathBuf::from)
+        );
+        let home = places
+            .iter()
+            .filter(|place| *place == Path::new("/home/me"));
+        assert_eq!(home.count(), 1);
+    }
+
+    #[test]
+    fn previews() {
+        let mut many = sample();
+        many.extend(sample());
+        let found = [
+            Found {
+                path: PathBuf::from("/home/me/todo.json"),
+                todos: Some(many),
+            },
+            Found {
+                path: PathBuf::from("/home/me/src/app/todo.json"),
+                todos: None,
+            },
+        ];
+
+        let mut screen = Screen::plain();
+        print_found(&mut screen, &found);
+        print_found(&mut screen, &[]);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,442 @@
+//! A todo list kept in a JSON file.
+//!
+//! The [`TodoStore`] trait loads and changes a list of [`Item`]s, and
+//! [`JsonFileStore`] keeps one in a todo.json file, so other tools can
+//! read and update the same list as the `todo` command. [`app`] is the
+//! command line and interactive prompt built on top.
+
+use std::fmt::Display;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+pub use energy::Energy;
+pub use priority::Priority;
+pub use recurrence::Recurrence;
+pub use shop::Quantity;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::{Change, JsonFileStore, TodoStore};
+
+pub mod app;
+mod archive;
+mod autosave;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+mod bugreport;
+mod bundle;
+mod burnout;
+mod celebrate;
+mod clock;
+mod config;
+mod curl;
+mod date;
+mod day;
+mod deadline;
+mod defer;
+mod demo;
+mod edit;
+mod energy;
+mod expiry;
+mod focus;
+mod formats;
+#[cfg(feature = "fuzz")]
+#[doc(hidden)]
+pub mod fuzz;
+mod heatmap;
+mod history;
+#[cfg(feature = "tui")]
+mod keys;
+mod legacy;
+mod lists;
+mod maintenance;
+mod mapping;
+mod notes;
+mod notify;
+mod output;
+mod people;
+mod priority;
+mod prompt;
+mod quickadd;
+mod recipients;
+mod recurrence;
+mod relative;
+mod render;
+mod report;
+mod scan;
+mod screen;
+mod search;
+mod secret;
+mod shell;
+mod shop;
+mod show;
+mod someday;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+#[cfg(feature = "lsp")]
+mod stdio;
+mod store;
+mod subtasks;
+mod sync;
+mod tags;
+mod theme;
+mod trash;
+#[cfg(feature = "tui")]
+mod tui;
+mod usage;
+mod validate;
+mod waiting;
+
+pub type Todos = Vec<Item>;
+
+/// One entry of a todo list, as stored in todo.json.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Item {
+    pub name: String,
+    pub completed: bool,
+    /// Closed without being done, because it expired.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Whether this is a subtask of the closest item above it that isn't.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub subtask: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<scan::Source>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub my_day: Option<chrono::NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_on: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_since: Option<chrono::NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contacts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_on: Option<chrono::NaiveDate>,
+    /// Set while the item is on the someday list, to when it was put there
+    /// or last reviewed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub someday: Option<chrono::NaiveDate>,
+    /// Snoozed: left out of the list until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_until: Option<chrono::DateTime<chrono::Local>>,
+    /// Every time it was snoozed, deferred or had its due date moved later.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub postponed: Vec<chrono::DateTime<chrono::Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<chrono::DateTime<chrono::Local>>,
+    /// The due date is an aim rather than a deadline, see `deadline`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub soft: bool,
+    /// When it's cancelled if it isn't done by then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<chrono::DateTime<chrono::Local>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<Energy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<Quantity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// Longer text kept with the item, like details or a link.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Tells the item apart from every other, on this machine or another,
+    /// e.g. for `todo sync`. Items from before there were ids have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<uuid::Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<chrono::DateTime<chrono::Local>>,
+    /// When it was last checked off, kept while it stays completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<chrono::DateTime<chrono::Local>>,
+    /// Fields this version doesn't know about, kept so that saving doesn't
+    /// drop data written by newer versions or other tools.
+    #[serde(flatten)]
+    pub(crate) extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Item {
+    pub fn is_overdue(&self) -> bool {
+        !self.completed && !self.soft && self.due.is_some_and(|due| due < clock::now())
+    }
+
+    /// A new open item. `+tags` and `@mentions` in the name are picked up.
+    pub fn new(name: String) -> Self {
+        Item {
+            contacts: people::parse_mentions(&name),
+            tags: tags::parse_tags(&name),
+            name,
+            completed: false,
+            cancelled: false,
+            subtask: false,
+            source: None,
+            my_day: None,
+            waiting_on: None,
+            waiting_since: None,
+            starts_on: None,
+            someday: None,
+            hidden_until: None,
+            postponed: Vec::new(),
+            due: None,
+            soft: false,
+            expires: None,
+            priority: None,
+            energy: None,
+            quantity: None,
+            recurrence: None,
+            notes: None,
+            id: Some(uuid::Uuid::new_v4()),
+            created: Some(clock::now()),
+            completed_at: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", render::text(self))
+    }
+}
+
+fn check_index(todos: &Todos, index: usize) -> Result<(), String> {
+    check_index_len(index, todos.len())
+}
+
+fn check_index_len(index: usize, len: usize) -> Result<(), String> {
+    if index >= len {
+        return Err(format!("No item at index {}", index));
+    }
+
+    Ok(())
+}
+
+/// Reads a selection of items like `3`, `5-7` or `1, 3, 5-7`, separated by
+/// commas or spaces. Returns the indices in order, each once.
+fn parse_indices(s: &str, len: usize) -> Result<Vec<usize>, String> {
+    let number = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("{:?} isn't an index or a range like 5-7", s))
+    };
+    let mut indices = Vec::new();
+    for part in s.split([',', ' ']).filter(|part| !part.trim().is_empty()) {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (number(first)?, number(last)?),
+            None => (number(part)?, number(part)?),
+        };
+        if first > last {
+            return Err(format!("{} is backwards, try {}-{}", part, last, first));
+        }
+        for index in first..=last {
+            check_index_len(index, len)?;
+            indices.push(index);
+        }
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// The current list as it was last read or saved, to tell whether the file
+/// has been changed by something else since.
+static LOADED: Mutex<Option<(PathBuf, Todos)>> = Mutex::new(None);
+
+/// Saves the current list.
+///
+/// If the file was changed since it was read, say by `todo` in another
+/// terminal, the changes on both sides are merged the way `todo sync`
+/// merges, and where both changed the same item this side's version is
+/// kept. A lock on a file next to the list keeps two saves from
+/// overlapping.
+fn write_to_file(todos: &Todos) -> Result<(), String> {
+    let path = store::path();
+    let store = store::open();
+    let lock_path = store::sibling("lock");
+    let lock = File::create(&lock_path)
+        .and_then(|lock| lock.lock().map(|_| lock))
+        .map_err(|e| format!("Could not lock {}: {}", lock_path.display(), e))?;
+
+    let mut loaded = LOADED.lock().unwrap();
+    let merged = match (&*loaded, store.load()) {
+        (Some((loaded_path, base)), Ok(current)) if *loaded_path == path && current != *base => {
+            Some(sync::merge(base, todos, &current).0)
+        }
+        _ => None,
+    };
+    let todos = merged.as_ref().unwrap_or(todos);
+    store.save(todos)?;
+    *loaded = Some((path, todos.clone()));
+    drop(lock);
+    Ok(())
+}
+
+/// Reads the current list.
+fn read_from_file() -> Result<Todos, String> {
+    let path = store::path();
+    let todos = store::open().load()?;
+    *LOADED.lock().unwrap() = Some((path, todos.clone()));
+    Ok(todos)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::{Local, NaiveDate, TimeZone};
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// A list exercising every part of an item's line.
+    pub(crate) fn sample() -> Todos {
+        let mut report = Item::new("send report to @bob +work".to_string());
+        report.priority = Some(Priority::High);
+        report.due = Local.with_ymd_and_hms(2020, 1, 31, 17, 0, 0).earliest();
+
+        let mut milk = Item::new("buy milk".to_string());
+        milk.tags.push("home".to_string());
+        milk.priority = Some(Priority::Low);
+
+        let mut taxes = Item::new("do taxes".to_string());
+        taxes.starts_on = Some(date(2099, 3, 1));
+        taxes.due = date::parse_due("2099-04-15", Local::now());
+
+        let mut done = Item::new("call @alice about +work".to_string());
+        done.completed = true;
+
+        let mut lunch = Item::new("book lunch".to_string());
+        lunch.waiting_on = Some("alice".to_string());
+        lunch.waiting_since = Some(date(2024, 5, 1));
+        lunch.my_day = Some(date(2024, 5, 6));
+
+        vec![report, milk, taxes, done, lunch]
+    }
+
+    /// Points the store at an empty directory of its own, for tests that
+    /// read or write the list or what's kept next to it. They share the
+    /// store, so each holds the returned lock until it's done.
+    pub(crate) fn temp_store() -> std::sync::MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = std::env::temp_dir().join(format!("todo-tests-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        store::init(
+            Some(dir.join("todo.json")),
+            None,
+            Some(store::Backend::Json),
+        )
+        .unwrap();
+        lock
+    }
+
+    fn naive_date() -> impl Strategy<Value = NaiveDate> {
+        (1970..2100i32, 1..=365u32)
+            .prop_map(|(year, day)| NaiveDate::from_yo_opt(year, day).unwrap())
+    }
+
+    pub(crate) fn item() -> impl Strategy<Value = Item> {
+        (
+            ("[a-z@+ ]{0,24}", prop::option::of("[ -~\n]{0,40}")),
+            (any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>()),
+            (
+                prop::collection::vec("[a-z]{1,6}", 0..3),
+                prop::collection::vec(0..4_102_444_800i64, 0..3),
+            ),
+            (
+                prop::option::of(0..4_102_444_800i64),
+                prop::option::of(0..4_102_444_800i64),
+                prop::option::of(0..4_102_444_800i64),
+                prop::option::of(0..4_102_444_800i64),
+            ),
+            prop::option::of(prop_oneof![
+                Just(Priority::Low),
+                Just(Priority::Medium),
+                Just(Priority::High)
+            ]),
+            prop::option::of(prop_oneof![
+                Just(Energy::Low),
+                Just(Energy::Medium),
+                Just(Energy::High)
+            ]),
+            prop::option::of(recurrence::tests::rule()),
+            prop::option::of(naive_date()),
+            prop::option::of(naive_date()),
+            prop::option::of(("[a-z]{1,8}", naive_date())),
+            prop::collection::btree_map("x_[a-z]{1,6}", "[ -~]{0,10}", 0..3),
+        )
+            .prop_map(
+                |(
+                    (name, notes),
+                    (completed, subtask, cancelled, soft),
+                    (tags, postponed),
+                    (due, expires, completed_at, hidden_until),
+                    priority,
+                    energy,
+                    recurrence,
+                    starts_on,
+                    someday,
+                    waiting,
+                    extra,
+                )| {
+                    let mut item = Item::new(name);
+                    item.notes = notes;
+                    item.completed = completed;
+                    item.subtask = subtask;
+                    item.cancelled = cancelled;
+                    item.soft = soft;
+                    item.tags.extend(tags);
+                    item.postponed = postponed
+                        .into_iter()
+                        .filter_map(|secs| Local.timestamp_opt(secs, 0).single())
+                        .collect();
+                    item.due = due.and_then(|secs| Local.timestamp_opt(secs, 0).single());
+                    item.expires = expires.and_then(|secs| Local.timestamp_opt(secs, 0).single());
+                    item.completed_at =
+                        completed_at.and_then(|secs| Local.timestamp_opt(secs, 0).single());
+                    item.hidden_until =
+                        hidden_until.and_then(|secs| Local.timestamp_opt(secs, 0).single());
+                    item.priority = priority;
+                    item.energy = energy;
+                    item.recurrence = recurrence;
+                    item.starts_on = starts_on;
+                    item.someday = someday;
+                    item.waiting_on = waiting.as_ref().map(|(person, _)| person.clone());
+                    item.waiting_since = waiting.map(|(_, since)| since);
+                    item.extra = extra
+                        .into_iter()
+                        .map(|(key, value)| (key, serde_json::Value::String(value)))
+                        .collect();
+                    item
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn items_survive_a_save(todos in prop::collection::vec(item(), 0..8)) {
+            let json = serde_json::to_string(&todos).unwrap();
+            let read: Todos = serde_json::from_str(&json).unwrap();
+            prop_assert_eq!(read, todos);
+        }
+    }
+
+    #[test]
+    fn selections() {
+        assert_eq!(parse_indices("1 3 5-7", 9).unwrap(), [1, 3, 5, 6, 7]);
+        assert_eq!(parse_indices("3, 1,3", 9).unwrap(), [1, 3]);
+        assert!(parse_indices("7-5", 9).is_err());
+        assert!(parse_indices("5-9", 9).is_err());
+        assert!(parse_indices("one", 9).is_err());
+    }
+}
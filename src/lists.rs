@@ -0,0 +1,30 @@
+//! Separate named lists, e.g. one for work and one for home.
+
+use crate::prompt::Prompt;
+use crate::screen::Screen;
+use crate::{store, Todos};
+
+/// Prints every list with how many open items it has, marking the current
+/// one.
+pub fn print_lists(screen: &mut Screen) {
+    let current = store::list_name();
+    for name in store::list_names() {
+        let todos: Todos = store::open_list(&name).load().unwrap_or_default();
+        let open = todos.iter().filter(|item| !item.completed).count();
+
+        let marker = if name == current { "*" } else { " " };
+        writeln!(screen, "{} {} ({} open)", marker, name, open);
+    }
+    writeln!(screen);
+}
+
+/// Asks which list to work on from now on.
+pub fn switch_list(screen: &mut Screen) {
+    print_lists(screen);
+    let prompt = Prompt::text("Which list? A new name starts a new list (cancel to stop)")
+        .validate(|name| store::check_list_name(name));
+    if let Some(name) = prompt.ask(screen) {
+        store::switch(&name).unwrap();
+    }
+    screen.clear();
+}
@@ -0,0 +1,134 @@
+//! Daily upkeep, done on the first run of the day, by `todo notify` when
+//! the day changes, or any time with `todo maintain`:
+//!
+//! - a dated backup of the list is kept, `<list>.backup-2024-05-06.json`,
+//!   and backups beyond the last week's are removed
+//! - completed items are archived, when archiving is turned on with
+//!   `TODO_AUTO_ARCHIVE` or maintenance was asked for
+//! - items that have been in the trash longer than `trash_days` are
+//!   removed for good
+//!
+//! What was done is summed up in a line.
+
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+
+use crate::{archive, clock, read_from_file, store, trash, write_to_file, Todos};
+
+/// How many daily backups are kept.
+const BACKUPS: usize = 7;
+
+fn backup_path(day: NaiveDate) -> PathBuf {
+    store::sibling(&format!("backup-{}.json", day))
+}
+
+/// The backups of the current list, oldest first.
+fn backups() -> Vec<PathBuf> {
+    let prefix = format!("{}.backup-", store::list_name());
+    let path = store::path();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".json")
+        })
+        .collect();
+    // Dates in the names sort in the order they were made.
+    backups.sort();
+    backups
+}
+
+/// Which of `backups`, oldest first, are too old to keep.
+fn expired<T>(backups: &[T]) -> &[T] {
+    &backups[..backups.len().saturating_sub(BACKUPS)]
+}
+
+/// Does the upkeep on `todos`. Returns what was done, for the summary.
+pub fn maintain(todos: &mut Todos, today: NaiveDate, archive: bool) -> Result<Vec<String>, String> {
+    let mut done = Vec::new();
+
+    let path = backup_path(today);
+    std::fs::write(&path, serde_json::to_string(todos).unwrap())
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    done.push("backed up the list".to_string());
+
+    let backups = backups();
+    let expired = expired(&backups);
+    for backup in expired {
+        std::fs::remove_file(backup)
+            .map_err(|e| format!("Could not remove {}: {}", backup.display(), e))?;
+    }
+    match expired.len() {
+        0 => {}
+        1 => done.push("removed an old backup".to_string()),
+        count => done.push(format!("removed {} old backups", count)),
+    }
+
+    if archive {
+        match archive::archive_completed(todos)? {
+            0 => {}
+            1 => done.push("archived a completed item".to_string()),
+            count => done.push(format!("archived {} completed items", count)),
+        }
+    }
+
+    match trash::purge(today)? {
+        0 => {}
+        1 => done.push("emptied an old item from the trash".to_string()),
+        count => done.push(format!("emptied {} old items from the trash", count)),
+    }
+
+    Ok(done)
+}
+
+/// The line summing up what `maintain` did.
+pub fn summary(done: &[String]) -> String {
+    format!("Maintenance: {}", done.join(", "))
+}
+
+fn stamp_path() -> PathBuf {
+    store::sibling("maintained")
+}
+
+/// Does the day's upkeep unless it was done today already, or the clock is
+/// simulated. Returns the summary if it was done now.
+pub fn daily(today: NaiveDate) -> Result<Option<String>, String> {
+    if clock::is_simulated() {
+        return Ok(None);
+    }
+    let stamp = stamp_path();
+    let last: Option<NaiveDate> = std::fs::read_to_string(&stamp)
+        .ok()
+        .and_then(|content| content.trim().parse().ok());
+    if last.is_some_and(|last| last >= today) {
+        return Ok(None);
+    }
+
+    let mut todos = read_from_file()?;
+    let done = maintain(&mut todos, today, archive::auto())?;
+    write_to_file(&todos)?;
+    std::fs::write(&stamp, today.to_string())
+        .map_err(|e| format!("Could not write {}: {}", stamp.display(), e))?;
+    Ok(Some(summary(&done)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_week_of_backups_is_kept() {
+        let backups: Vec<usize> = (0..10).collect();
+        assert_eq!(expired(&backups), [0, 1, 2]);
+        assert!(expired(&backups[..5]).is_empty());
+    }
+}
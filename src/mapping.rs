@@ -0,0 +1,394 @@
+//! The small part of jq that `todo import --format json --map` takes, to
+//! turn another app's export into items:
+//!
+//! ```text
+//! .tasks[] | {name: .title, completed: .done, tags: [.labels[].name]}
+//! ```
+//!
+//! - `.`, `.field`, `."odd field"`, `.[0]` and `.[]`, chained as in `.a.b[]`
+//! - `a | b` to feed what `a` makes to `b`, and `a // b` for `b` when `a`
+//!   makes nothing but `null` or `false`
+//! - `{key: value, title}`, where `title` is short for `title: .title`, and
+//!   `[a]` collecting what `a` makes
+//! - strings, numbers, `true`, `false`, `null` and parentheses
+//!
+//! As in jq, each expression makes any number of values from its input:
+//! `.[]` makes one for each element, and an object makes one for each
+//! combination of what its values make.
+
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Identity,
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, i64),
+    Iterate(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Literal(Value),
+    Object(Vec<(String, Expr)>),
+    Array(Box<Expr>),
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.rest().chars().next()
+    }
+
+    /// Moves past `token` if it's next.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_space();
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.rest().chars().next() {
+            Some(c) => format!(
+                "Expected {} at {:?} in the map, column {}",
+                expected,
+                c,
+                self.position + 1
+            ),
+            None => format!("Expected {} at the end of the map", expected),
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        match self.eat(token) {
+            true => Ok(()),
+            false => Err(self.error(&format!("{:?}", token))),
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Expr, String> {
+        let mut expr = self.alternative()?;
+        while self.eat("|") {
+            expr = Expr::Pipe(Box::new(expr), Box::new(self.alternative()?));
+        }
+        Ok(expr)
+    }
+
+    fn alternative(&mut self) -> Result<Expr, String> {
+        let mut expr = self.postfix()?;
+        while self.eat("//") {
+            expr = Expr::Alternative(Box::new(expr), Box::new(self.postfix()?));
+        }
+        Ok(expr)
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        self.skip_space();
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if end == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        self.position += end;
+        Some(rest[..end].to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.skip_space();
+        let mut stream = serde_json::Deserializer::from_str(self.rest()).into_iter::<Value>();
+        match stream.next() {
+            Some(Ok(Value::String(text))) => {
+                self.position += stream.byte_offset();
+                Ok(text)
+            }
+            _ => Err(self.error("a string")),
+        }
+    }
+
+    fn number(&mut self) -> Option<serde_json::Number> {
+        self.skip_space();
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || "-+.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let number = serde_json::from_str(&rest[..end]).ok()?;
+        self.position += end;
+        Some(number)
+    }
+
+    /// What follows a `.`: a field name, a quoted one, or nothing.
+    fn field(&mut self, expr: Expr) -> Result<Expr, String> {
+        if self.rest().starts_with('"') {
+            return Ok(Expr::Field(Box::new(expr), self.string()?));
+        }
+        if self
+            .rest()
+            .starts_with(|c: char| c.is_alphabetic() || c == '_')
+        {
+            let name = self.identifier().expect("starts like one");
+            return Ok(Expr::Field(Box::new(expr), name));
+        }
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.term()?;
+        loop {
+            // `.a.b` and `.a[]`, but not `. .b`, which jq doesn't take either.
+            if self.rest().starts_with('.') && !self.rest().starts_with("..") {
+                self.position += 1;
+                expr = match self.field(expr.clone())? {
+                    same if same == expr => return Err(self.error("a field name")),
+                    field => field,
+                };
+            } else if self.rest().starts_with('[') {
+                self.position += 1;
+                expr = if self.eat("]") {
+                    Expr::Iterate(Box::new(expr))
+                } else if self.peek() == Some('"') {
+                    let name = self.string()?;
+                    self.expect("]")?;
+                    Expr::Field(Box::new(expr), name)
+                } else {
+                    let index = self
+                        .number()
+                        .and_then(|number| number.as_i64())
+                        .ok_or_else(|| self.error("an index"))?;
+                    self.expect("]")?;
+                    Expr::Index(Box::new(expr), index)
+                };
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some('.') => {
+                self.position += 1;
+                self.field(Expr::Identity)
+            }
+            Some('"') => Ok(Expr::Literal(Value::String(self.string()?))),
+            Some('(') => {
+                self.position += 1;
+                let expr = self.pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some('[') => {
+                self.position += 1;
+                if self.eat("]") {
+                    return Ok(Expr::Literal(Value::Array(Vec::new())));
+                }
+                let expr = self.pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Array(Box::new(expr)))
+            }
+            Some('{') => {
+                self.position += 1;
+                self.object()
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => self
+                .number()
+                .map(|number| Expr::Literal(Value::Number(number)))
+                .ok_or_else(|| self.error("a number")),
+            _ => match self.identifier().as_deref() {
+                Some("true") => Ok(Expr::Literal(Value::Bool(true))),
+                Some("false") => Ok(Expr::Literal(Value::Bool(false))),
+                Some("null") => Ok(Expr::Literal(Value::Null)),
+                _ => Err(self.error("., a value, [, { or (")),
+            },
+        }
+    }
+
+    fn object(&mut self) -> Result<Expr, String> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            let key = match self.peek() {
+                Some('"') => self.string()?,
+                _ => self.identifier().ok_or_else(|| self.error("a key"))?,
+            };
+            let value = match self.eat(":") {
+                true => self.alternative()?,
+                false => Expr::Field(Box::new(Expr::Identity), key.clone()),
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+/// Reads a map like `.tasks[] | {name: .title}`.
+pub fn parse(text: &str) -> Result<Expr, String> {
+    let mut parser = Parser { text, position: 0 };
+    let expr = parser.pipe()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err(parser.error("| or the end")),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// The values `expr` makes from `input`.
+pub fn run(expr: &Expr, input: &Value) -> Result<Vec<Value>, String> {
+    let each = |expr: &Expr, apply: &dyn Fn(Value) -> Result<Vec<Value>, String>| {
+        let mut values = Vec::new();
+        for value in run(expr, input)? {
+            values.extend(apply(value)?);
+        }
+        Ok(values)
+    };
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Field(expr, name) => each(expr, &|value| match value {
+            Value::Object(mut object) => Ok(vec![object.remove(name).unwrap_or(Value::Null)]),
+            Value::Null => Ok(vec![Value::Null]),
+            other => Err(format!("Can't take .{} of {}", name, kind(&other))),
+        }),
+        Expr::Index(expr, index) => each(expr, &|value| match value {
+            Value::Array(mut array) => {
+                let from_end = array.len() as i64 + index;
+                let index = if *index < 0 { from_end } else { *index };
+                Ok(vec![match (0..array.len() as i64).contains(&index) {
+                    true => array.swap_remove(index as usize),
+                    false => Value::Null,
+                }])
+            }
+            Value::Null => Ok(vec![Value::Null]),
+            other => Err(format!("Can't take .[{}] of {}", index, kind(&other))),
+        }),
+        Expr::Iterate(expr) => each(expr, &|value| match value {
+            Value::Array(array) => Ok(array),
+            Value::Object(object) => Ok(object.into_iter().map(|(_, value)| value).collect()),
+            other => Err(format!("Can't go through each of {}", kind(&other))),
+        }),
+        Expr::Pipe(first, then) => each(first, &|value| run(then, &value)),
+        Expr::Alternative(first, or) => {
+            let values: Vec<Value> = run(first, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|value| !matches!(value, Value::Null | Value::Bool(false)))
+                .collect();
+            match values.is_empty() {
+                true => run(or, input),
+                false => Ok(values),
+            }
+        }
+        Expr::Array(expr) => Ok(vec![Value::Array(run(expr, input)?)]),
+        Expr::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key, expr) in entries {
+                let values = run(expr, input)?;
+                objects = objects
+                    .into_iter()
+                    .flat_map(|object| {
+                        values.iter().map(move |value| {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            object
+                        })
+                    })
+                    .collect();
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn map(text: &str, input: Value) -> Result<Vec<Value>, String> {
+        run(&parse(text)?, &input)
+    }
+
+    #[test]
+    fn maps() {
+        let export = json!({
+            "tasks": [
+                {"title": "buy milk", "done": true, "labels": [{"name": "home"}]},
+                {"title": "call bob", "done": false, "labels": [], "due date": "2024-06-01"},
+            ]
+        });
+        assert_eq!(
+            map(
+                ".tasks[] | {name: .title, completed: .done}",
+                export.clone()
+            ),
+            Ok(vec![
+                json!({"name": "buy milk", "completed": true}),
+                json!({"name": "call bob", "completed": false}),
+            ])
+        );
+        assert_eq!(
+            map(
+                r#".tasks[-1] | {title, due: ."due date", tags: [.labels[].name], notes: .notes // "none"}"#,
+                export.clone()
+            ),
+            Ok(vec![json!({
+                "title": "call bob",
+                "due": "2024-06-01",
+                "tags": [],
+                "notes": "none",
+            })])
+        );
+        assert_eq!(
+            map(r#".tasks[0].labels[0]["name"]"#, export.clone()),
+            Ok(vec![json!("home")])
+        );
+        // An object is made for each combination of its values.
+        assert_eq!(
+            map("{a: (1 // 2), b: .[]}", json!([3, 4])),
+            Ok(vec![json!({"a": 1, "b": 3}), json!({"a": 1, "b": 4})])
+        );
+        assert_eq!(map(".missing.deeper", json!({})), Ok(vec![Value::Null]));
+    }
+
+    #[test]
+    fn bad_maps() {
+        assert!(parse(".tasks[").is_err());
+        assert!(parse("{name .title}").is_err());
+        assert!(parse(".tasks | ").is_err());
+        assert!(parse(". .title").is_err());
+        assert!(parse(".a..b").is_err());
+        assert!(map(".title", json!([1])).is_err());
+        assert!(map(".[]", json!(true)).is_err());
+    }
+}
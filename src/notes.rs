@@ -0,0 +1,43 @@
+//! Notes kept with an item, given with `--note` or written in the user's
+//! editor: `$VISUAL`, then `$EDITOR`, then `vi`.
+
+use std::process::Command;
+
+/// Notes as kept on an item: trailing whitespace trimmed, and none if
+/// there's nothing left.
+pub fn clean(notes: &str) -> Option<String> {
+    Some(notes.trim_end().to_string()).filter(|notes| !notes.trim().is_empty())
+}
+
+/// Opens `notes` in the user's editor and returns what they were left as.
+pub fn edit(notes: Option<&str>) -> Result<Option<String>, String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Editors are often set with arguments, like `code --wait`.
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let path = std::env::temp_dir().join(format!("todo-notes-{}.md", std::process::id()));
+    let text = notes
+        .map(|notes| format!("{}\n", notes))
+        .unwrap_or_default();
+    std::fs::write(&path, text)
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    let status = Command::new(program).args(words).arg(&path).status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => {
+            return Err(format!(
+                "{} exited with {}, notes left as they were",
+                editor, status
+            ))
+        }
+        Err(e) => return Err(format!("Could not run {}: {}", editor, e)),
+    }
+    let edited = edited.map_err(|e| format!("Could not read the notes back: {}", e))?;
+    Ok(clean(&edited))
+}
@@ -0,0 +1,300 @@
+//! `todo notify`: stays running and shows a desktop notification when an
+//! item falls due, using `notify-send` on Linux and `osascript` on macOS.
+//!
+//! Reminders can go elsewhere instead, by the item's tags, as set in the
+//! config:
+//!
+//! ```toml
+//! [notify]
+//! channel = "desktop"   # where reminders go unless a tag says otherwise
+//! [notify.tags]
+//! work = "slack:work-webhook"
+//! family = "ntfy:family-topic"
+//! ```
+//!
+//! An item goes by the first of its tags that has a channel. Slack and
+//! ntfy channels name a secret, set with `todo secret set`, as anyone with
+//! a webhook's URL or a topic can post to it or read it: the webhook's URL,
+//! or the ntfy topic, which is on ntfy.sh unless it's a whole URL. They're
+//! sent to with `curl`, given the URL on stdin, out of sight of other users.
+//!
+//! The list is read afresh on every check and only written by the day's
+//! maintenance, so it can be left running while the list is changed from
+//! other terminals.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::process::Command;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+use crate::{clock, config, curl, maintenance, read_from_file, relative, secret, Item, Todos};
+
+/// More items than this falling due at once get one notification between
+/// them, as happens when starting with a backlog of overdue items.
+const SEPARATE: usize = 3;
+
+/// The open items that fell due after `after`, or by `now` at all if there
+/// was no check before.
+fn falling_due(todos: &Todos, after: Option<DateTime<Local>>, now: DateTime<Local>) -> Vec<usize> {
+    (0..todos.len())
+        .filter(|&i| !todos[i].completed)
+        .filter(|&i| match todos[i].due {
+            Some(due) => due <= now && after.is_none_or(|after| due > after),
+            None => false,
+        })
+        .collect()
+}
+
+/// Where a reminder is sent.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String")]
+pub enum Channel {
+    Desktop,
+    /// The secret holding a Slack incoming webhook's URL.
+    Slack(String),
+    /// The secret holding an ntfy topic.
+    Ntfy(String),
+}
+
+impl TryFrom<String> for Channel {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        if text == "desktop" {
+            return Ok(Channel::Desktop);
+        }
+        let channel = if let Some(name) = text.strip_prefix("slack:") {
+            Channel::Slack(name.to_string())
+        } else if let Some(name) = text.strip_prefix("ntfy:") {
+            Channel::Ntfy(name.to_string())
+        } else {
+            return Err(format!(
+                "{:?} isn't a channel, expected desktop, slack:<secret> or ntfy:<secret>",
+                text
+            ));
+        };
+        match channel.secret() {
+            Some(name) if name.contains(['/', ':']) => Err(format!(
+                "{:?} has the address in the config, where anyone can read it: \
+                 store it with `todo secret set <name>` and give the name",
+                text
+            )),
+            _ => Ok(channel),
+        }
+    }
+}
+
+impl Channel {
+    /// The name of the secret with where to send to.
+    fn secret(&self) -> Option<&str> {
+        match self {
+            Channel::Desktop => None,
+            Channel::Slack(name) | Channel::Ntfy(name) => Some(name),
+        }
+    }
+
+    /// The URL to send to, from the secret.
+    fn url(&self) -> Result<String, String> {
+        let Some(name) = self.secret() else {
+            return Err(format!("{} has no URL", self));
+        };
+        let secret = secret::get(name)?.ok_or_else(|| {
+            format!(
+                "There's no secret called {}, set it with `todo secret set`",
+                name
+            )
+        })?;
+        Ok(match self {
+            Channel::Ntfy(_) if !secret.contains("://") => format!("https://ntfy.sh/{}", secret),
+            _ => secret,
+        })
+    }
+}
+
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Channel::Desktop => write!(f, "the desktop"),
+            Channel::Slack(name) => write!(f, "Slack ({})", name),
+            Channel::Ntfy(name) => write!(f, "ntfy ({})", name),
+        }
+    }
+}
+
+/// `[notify]` in the config.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Routes {
+    pub channel: Option<Channel>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, Channel>,
+}
+
+impl Routes {
+    /// Where the reminder for `item` goes.
+    fn channel(&self, item: &Item) -> Channel {
+        item.tags
+            .iter()
+            .find_map(|tag| self.tags.get(tag))
+            .or(self.channel.as_ref())
+            .cloned()
+            .unwrap_or(Channel::Desktop)
+    }
+}
+
+/// The items in `due` by the channel their reminders go to.
+fn routed(todos: &Todos, due: Vec<usize>, routes: &Routes) -> BTreeMap<Channel, Vec<usize>> {
+    let mut routed: BTreeMap<Channel, Vec<usize>> = BTreeMap::new();
+    for i in due {
+        routed.entry(routes.channel(&todos[i])).or_default().push(i);
+    }
+    routed
+}
+
+fn notify(channel: &Channel, title: &str, body: &str) {
+    println!("{}: {}", title, body);
+
+    let sent = match channel {
+        Channel::Desktop => return notify_desktop(title, body),
+        Channel::Slack(_) => channel.url().and_then(|url| {
+            let text = serde_json::json!({ "text": format!("{}: {}", title, body) });
+            let arguments = [
+                "-sSf",
+                "-o",
+                "/dev/null",
+                "-H",
+                "Content-Type: application/json",
+            ];
+            curl::run(
+                &arguments,
+                &[("url", &url), ("data-raw", &text.to_string())],
+            )
+        }),
+        Channel::Ntfy(_) => channel.url().and_then(|url| {
+            let title = format!("Title: {}", title);
+            let arguments = ["-sSf", "-o", "/dev/null", "-H", &title];
+            curl::run(&arguments, &[("url", &url), ("data-raw", body)])
+        }),
+    };
+    if let Err(e) = sent {
+        eprintln!("Could not send a notification to {}: {}", channel, e);
+    }
+}
+
+fn notify_desktop(title: &str, body: &str) {
+    let shown = match cfg!(target_os = "macos") {
+        true => Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification {:?} with title {:?}",
+                body, title
+            ))
+            .status(),
+        false => Command::new("notify-send").arg(title).arg(body).status(),
+    };
+    if let Err(e) = shown {
+        eprintln!("Could not show a notification: {}", e);
+    }
+}
+
+/// Reads how many minutes `watch` waits between checks. Not waiting at
+/// all would keep a core busy.
+pub fn parse_every(text: &str) -> Result<u64, String> {
+    match text.parse::<u64>() {
+        Ok(0) => Err("Wait at least a minute between checks".to_string()),
+        Ok(minutes) => Ok(minutes),
+        Err(_) => Err(format!("Couldn't understand {:?}, try 1 or 15", text)),
+    }
+}
+
+/// Checks the list every `minutes` until stopped.
+pub fn watch(minutes: u64) -> ! {
+    let mut last_check = None;
+    loop {
+        let now = clock::now();
+        // Stays up past midnight, so does the next day's maintenance.
+        match maintenance::daily(now.date_naive()) {
+            Ok(Some(summary)) => println!("{}", summary),
+            Ok(None) => {}
+            Err(e) => eprintln!("Maintenance failed: {}", e),
+        }
+        match read_from_file() {
+            Ok(todos) => {
+                let due = falling_due(&todos, last_check, now);
+                let routes = config::get().notify.as_ref();
+                for (channel, due) in routed(&todos, due, routes.unwrap_or(&Routes::default())) {
+                    if due.len() > SEPARATE {
+                        notify(&channel, "todo", &format!("{} items are due", due.len()));
+                        continue;
+                    }
+                    for i in due {
+                        let title = match todos[i].soft {
+                            true => "Aimed for now",
+                            false => "Due",
+                        };
+                        // Only items with a due date fall due.
+                        let due = relative::describe(todos[i].due.unwrap(), now);
+                        notify(&channel, title, &format!("{} ({})", todos[i].name, due));
+                    }
+                }
+                last_check = Some(now);
+            }
+            // Tried again at the next check, in case it's being fixed.
+            Err(e) => eprintln!("{}", e),
+        }
+        std::thread::sleep(Duration::from_secs(minutes * 60));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn items_fall_due_once() {
+        let todos = sample();
+        let before = Local.with_ymd_and_hms(2020, 1, 31, 16, 59, 0).unwrap();
+        let after = Local.with_ymd_and_hms(2020, 1, 31, 17, 1, 0).unwrap();
+
+        assert_eq!(falling_due(&todos, None, after), [0]);
+        assert_eq!(falling_due(&todos, Some(before), after), [0]);
+        assert!(falling_due(&todos, Some(after), after).is_empty());
+        assert!(falling_due(&todos, None, before).is_empty());
+    }
+
+    #[test]
+    fn reminders_go_by_tag() {
+        let routes: Routes =
+            toml::from_str("channel = \"ntfy:me\"\n[tags]\nwork = \"slack:work-webhook\"\n")
+                .unwrap();
+        let mut todos = sample();
+        // The first tag with a channel decides.
+        todos[3].tags = vec!["errand".to_string(), "work".to_string()];
+
+        let routed = routed(&todos, vec![0, 1, 3], &routes);
+        let slack = Channel::Slack("work-webhook".to_string());
+        let ntfy = Channel::Ntfy("me".to_string());
+        assert_eq!(
+            routed.into_iter().collect::<Vec<_>>(),
+            [(slack, vec![0, 3]), (ntfy, vec![1])]
+        );
+        assert_eq!(Routes::default().channel(&todos[0]), Channel::Desktop);
+        assert!(toml::from_str::<Routes>("channel = \"pager\"").is_err());
+        // Addresses belong in the keyring, not the config.
+        let url = "channel = \"slack:https://hooks.example.com/x\"";
+        assert!(toml::from_str::<Routes>(url).is_err());
+    }
+
+    #[test]
+    fn checks_are_at_least_a_minute_apart() {
+        assert_eq!(parse_every("15"), Ok(15));
+        assert!(parse_every("0").is_err());
+        assert!(parse_every("-1").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Listings for scripts rather than people: `todo list --format json` or
+//! `--format tsv` print the items the list would show, with their indices
+//! and everything known about them, and nothing else.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::screen::Screen;
+use crate::{date, Todos};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListFormat {
+    #[default]
+    Text,
+    Json,
+    Tsv,
+}
+
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ListFormat::Text),
+            "json" => Ok(ListFormat::Json),
+            "tsv" => Ok(ListFormat::Tsv),
+            _ => Err(format!(
+                "No format called {}, expected text, json or tsv",
+                s
+            )),
+        }
+    }
+}
+
+/// An array of items as stored in todo.json, each with its `index` added.
+pub fn print_json(screen: &mut Screen, todos: &Todos, indices: &[usize]) {
+    let items: Vec<Value> = indices
+        .iter()
+        .map(|&i| {
+            let mut item = serde_json::to_value(&todos[i]).unwrap();
+            item["index"] = Value::from(i);
+            item
+        })
+        .collect();
+    writeln!(screen, "{}", serde_json::to_string_pretty(&items).unwrap());
+}
+
+const TSV_COLUMNS: [&str; 6] = ["index", "completed", "priority", "due", "tags", "name"];
+
+/// One line per item after a header, the name last as it's free text.
+pub fn print_tsv(screen: &mut Screen, todos: &Todos, indices: &[usize]) {
+    writeln!(screen, "{}", TSV_COLUMNS.join("\t"));
+    for &i in indices {
+        let item = &todos[i];
+        let fields = [
+            i.to_string(),
+            item.completed.to_string(),
+            item.priority.map(|p| p.to_string()).unwrap_or_default(),
+            item.due
+                .map(|due| date::format_due(&due))
+                .unwrap_or_default(),
+            item.tags.join(","),
+            item.name.replace(['\t', '\n', '\r'], " "),
+        ];
+        writeln!(screen, "{}", fields.join("\t"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn tsv() {
+        let mut screen = Screen::plain();
+        print_tsv(&mut screen, &sample(), &[0, 1, 3]);
+        insta::assert_snapshot!(screen.contents());
+    }
+
+    #[test]
+    fn json_has_indices_and_items() {
+        let mut screen = Screen::plain();
+        print_json(&mut screen, &sample(), &[1, 4]);
+
+        let items: Vec<Value> = serde_json::from_str(screen.contents()).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["index"], 1);
+        assert_eq!(items[0]["priority"], "low");
+        assert_eq!(items[1]["name"], "book lunch");
+        assert_eq!(items[1]["waiting_on"], "alice");
+    }
+}
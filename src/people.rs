@@ -0,0 +1,103 @@
+//! `@alice` style mentions of people in item names.
+//!
+//! When an item is added with its details on one line, an `@` word that
+//! reads as a date, like `@friday` or `@tom`, is the due date instead; see
+//! `quickadd`. `@@tom` mentions Tom there.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::screen::Screen;
+use crate::{someday, tags, Todos};
+
+/// Returns the lowercased names of everyone mentioned as `@name`, in order
+/// of first mention.
+pub fn parse_mentions(name: &str) -> Vec<String> {
+    tags::prefixed_words(name, '@')
+}
+
+pub fn print_people(screen: &mut Screen, todos: &Todos) {
+    let mut by_person: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, item) in todos.iter().enumerate() {
+        if item.completed || someday::is_someday(item) {
+            continue;
+        }
+        for contact in &item.contacts {
+            by_person.entry(contact).or_default().push(i);
+        }
+    }
+
+    if by_person.is_empty() {
+        writeln!(screen, "[Nobody mentioned in open items]\n");
+        return;
+    }
+
+    for (person, indices) in by_person {
+        writeln!(screen, "{} ({})", person, indices.len());
+        for i in indices {
+            writeln!(screen, "  {} {}", i, todos[i]);
+        }
+        writeln!(screen);
+    }
+}
+
+/// Prints a Markdown agenda of the open items that mention `person`, plus
+/// the ones waiting on them.
+pub fn print_agenda(screen: &mut Screen, todos: &Todos, person: &str, today: NaiveDate) {
+    let person = person.trim_start_matches('@').to_lowercase();
+    let open = || {
+        todos
+            .iter()
+            .filter(|item| !item.completed && !someday::is_someday(item))
+    };
+
+    writeln!(screen, "## {} ({})\n", person, today);
+
+    let mentioned: Vec<_> = open()
+        .filter(|item| item.contacts.contains(&person))
+        .collect();
+    if mentioned.is_empty() {
+        writeln!(screen, "_Nothing to discuss._");
+    }
+    for item in mentioned {
+        writeln!(screen, "- [ ] {}", item.name);
+    }
+
+    let waiting: Vec<_> = open()
+        .filter(|item| {
+            item.waiting_on
+                .as_deref()
+                .is_some_and(|waiting_on| waiting_on.to_lowercase() == person)
+        })
+        .collect();
+    if !waiting.is_empty() {
+        writeln!(screen, "\n### Waiting on {}\n", person);
+        for item in waiting {
+            writeln!(screen, "- [ ] {}", item.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn people() {
+        let mut screen = Screen::plain();
+        print_people(&mut screen, &sample());
+        insta::assert_snapshot!(screen.contents());
+    }
+
+    #[test]
+    fn agenda() {
+        let mut screen = Screen::plain();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        print_agenda(&mut screen, &sample(), "@Alice", today);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
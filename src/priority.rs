@@ -0,0 +1,233 @@
+//! Priority levels and the orderings that use them.
+//!
+//! Besides `manual` and `priority`, the list can be ordered by a sort
+//! expression such as `"due asc, priority desc, created asc"`: each key
+//! decides between items the ones before it found equal. Items missing a
+//! key's value, like one without a due date, go after those that have it
+//! either way round.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{subtasks, Item, Todos};
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" | "l" | "1" => Ok(Priority::Low),
+            "medium" | "med" | "m" | "2" => Ok(Priority::Medium),
+            "high" | "h" | "3" => Ok(Priority::High),
+            _ => Err(format!(
+                "No priority called {}, expected low, medium or high",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(try_from = "String")]
+pub enum SortOrder {
+    /// The order items are stored in
+    #[default]
+    Manual,
+    /// Open items first, then by priority, highest first
+    Priority,
+    /// By each key in turn
+    Keys(Vec<SortKey>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortField {
+    Completed,
+    Due,
+    Priority,
+    Energy,
+    Created,
+    Starts,
+    Today,
+    Name,
+}
+
+impl FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "completed" | "done" => Ok(SortField::Completed),
+            "due" => Ok(SortField::Due),
+            "priority" => Ok(SortField::Priority),
+            "energy" => Ok(SortField::Energy),
+            "created" => Ok(SortField::Created),
+            "starts" => Ok(SortField::Starts),
+            "today" => Ok(SortField::Today),
+            "name" => Ok(SortField::Name),
+            _ => Err(format!(
+                "Can't sort by {}, expected completed, due, priority, energy, created, \
+                 starts, today or name",
+                s
+            )),
+        }
+    }
+}
+
+/// One key of a sort expression, e.g. `due asc`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    field: SortField,
+    descending: bool,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let (field, descending) = match words[..] {
+            [field] | [field, "asc"] => (field, false),
+            [field, "desc"] => (field, true),
+            _ => {
+                return Err(format!(
+                    "Can't sort by \"{}\", expected a key and asc or desc",
+                    s
+                ))
+            }
+        };
+        Ok(SortKey {
+            field: field.parse()?,
+            descending,
+        })
+    }
+}
+
+impl SortKey {
+    fn compare(self, a: &Item, b: &Item) -> Ordering {
+        match self.field {
+            SortField::Completed => self.order(Some(a.completed), Some(b.completed)),
+            SortField::Due => self.order(a.due, b.due),
+            SortField::Priority => self.order(a.priority, b.priority),
+            SortField::Energy => self.order(a.energy, b.energy),
+            SortField::Created => self.order(a.created, b.created),
+            SortField::Starts => self.order(a.starts_on, b.starts_on),
+            SortField::Today => self.order(a.my_day, b.my_day),
+            SortField::Name => self.order(Some(a.name.to_lowercase()), Some(b.name.to_lowercase())),
+        }
+    }
+
+    /// Missing values last, whichever way the present ones go.
+    fn order<T: Ord>(self, a: Option<T>, b: Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if self.descending => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "manual" => Ok(SortOrder::Manual),
+            "priority" => Ok(SortOrder::Priority),
+            keys => keys
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map(SortOrder::Keys),
+        }
+    }
+}
+
+impl TryFrom<String> for SortOrder {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Indices of `todos` in display order. Sorting is stable, so items that
+/// compare equal keep their manual order, and subtasks stay under their
+/// parent.
+pub fn display_order(todos: &Todos, sort: &SortOrder) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..todos.len()).collect();
+    let top = |i: usize| subtasks::parent(todos, i).unwrap_or(i);
+    match sort {
+        SortOrder::Manual => {}
+        SortOrder::Priority => order.sort_by_key(|&i| {
+            let top = top(i);
+            let item = &todos[top];
+            let priority = item.priority.unwrap_or(Priority::Medium);
+            (item.completed, std::cmp::Reverse(priority), top)
+        }),
+        SortOrder::Keys(keys) => order.sort_by(|&a, &b| {
+            let (a, b) = (top(a), top(b));
+            keys.iter()
+                .map(|key| key.compare(&todos[a], &todos[b]))
+                .fold(Ordering::Equal, Ordering::then)
+                .then(a.cmp(&b))
+        }),
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn sort_expressions() {
+        let todos = sample();
+        let order = |expression: &str| display_order(&todos, &expression.parse().unwrap());
+        assert_eq!(
+            order("completed asc, due asc, priority desc"),
+            [0, 2, 1, 4, 3]
+        );
+        assert_eq!(order("priority desc"), [0, 1, 2, 3, 4]);
+        assert_eq!(order("priority asc"), [1, 0, 2, 3, 4]);
+        assert_eq!(order("name desc"), [0, 2, 3, 1, 4]);
+
+        assert!("pinned desc".parse::<SortOrder>().is_err());
+        assert!("due sideways".parse::<SortOrder>().is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn priorities_parse_back(
+            priority in prop_oneof![Just(Priority::Low), Just(Priority::Medium), Just(Priority::High)],
+        ) {
+            prop_assert_eq!(priority.to_string().parse::<Priority>(), Ok(priority));
+        }
+    }
+}
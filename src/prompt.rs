@@ -0,0 +1,144 @@
+//! Typed questions for the interactive prompt.
+//!
+//! Every prompt behaves the same way: an answer that doesn't parse or
+//! fails a validator is explained and asked again, an empty answer takes
+//! the default (or is asked again when there is none), and `cancel`
+//! abandons the whole command.
+
+use chrono::{DateTime, Local};
+
+use crate::priority::Priority;
+use crate::screen::Screen;
+use crate::{clock, date, parse_indices};
+
+const CANCEL: &str = "cancel";
+
+type Parser<'a, T> = Box<dyn Fn(&str) -> Result<T, String> + 'a>;
+type Validator<'a, T> = Box<dyn Fn(&T) -> Result<(), String> + 'a>;
+
+pub struct Prompt<'a, T> {
+    question: String,
+    parse: Parser<'a, T>,
+    validators: Vec<Validator<'a, T>>,
+    default: Option<T>,
+}
+
+impl<'a, T> Prompt<'a, T> {
+    pub fn new(
+        question: impl Into<String>,
+        parse: impl Fn(&str) -> Result<T, String> + 'a,
+    ) -> Self {
+        Prompt {
+            question: question.into(),
+            parse: Box::new(parse),
+            validators: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// The answer to use when the user just presses enter.
+    pub fn default(mut self, default: T) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Rejects answers that parse but aren't acceptable. The error is shown
+    /// before asking again.
+    pub fn validate(mut self, validator: impl Fn(&T) -> Result<(), String> + 'a) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Asks until there's an acceptable answer. Returns `None` if the user
+    /// cancels.
+    pub fn ask(mut self, screen: &mut Screen) -> Option<T> {
+        writeln!(screen, "{}", self.question);
+        loop {
+            let line = screen.read_line();
+            if line == CANCEL {
+                return None;
+            }
+            if line.is_empty() {
+                match self.default.take() {
+                    Some(default) => return Some(default),
+                    None => {
+                        writeln!(screen, "\nAn answer is needed, or {} to stop", CANCEL);
+                        continue;
+                    }
+                }
+            }
+
+            let answer = (self.parse)(&line).and_then(|answer| {
+                self.validators
+                    .iter()
+                    .try_for_each(|validate| validate(&answer))
+                    .map(|()| answer)
+            });
+            match answer {
+                Ok(answer) => return Some(answer),
+                Err(e) => writeln!(screen, "\n{}. Try again", e),
+            }
+        }
+    }
+}
+
+impl<'a> Prompt<'a, String> {
+    pub fn text(question: impl Into<String>) -> Self {
+        Prompt::new(question, |line| Ok(line.to_string()))
+    }
+}
+
+impl<'a> Prompt<'a, usize> {
+    /// An index into a list of `len` items.
+    pub fn index(question: impl Into<String>, len: usize) -> Self {
+        Prompt::new(question, |line| {
+            line.parse::<usize>()
+                .map_err(|_| "Invalid input".to_string())
+        })
+        .validate(move |index| {
+            if *index >= len {
+                return Err(format!("No item at index {}", index));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<'a> Prompt<'a, Vec<usize>> {
+    /// One or more indices, like `3` or `1, 4-6`.
+    pub fn indices(question: impl Into<String>, len: usize) -> Self {
+        Prompt::new(question, move |line| parse_indices(line, len))
+    }
+}
+
+impl<'a> Prompt<'a, bool> {
+    pub fn confirm(question: impl Into<String>) -> Self {
+        Prompt::new(question, |line| match line.to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("Answer y or n".to_string()),
+        })
+    }
+}
+
+impl<'a> Prompt<'a, Option<DateTime<Local>>> {
+    /// A due date, where `none` means no due date.
+    pub fn due(question: impl Into<String>) -> Self {
+        Prompt::new(question, |line| match line {
+            "none" => Ok(None),
+            _ => date::parse_due(line, clock::now())
+                .map(Some)
+                .ok_or_else(|| "Couldn't understand that date".to_string()),
+        })
+    }
+}
+
+impl<'a> Prompt<'a, Option<Priority>> {
+    /// A priority, where `none` means no priority.
+    pub fn priority(question: impl Into<String>) -> Self {
+        Prompt::new(question, |line| match line {
+            "none" => Ok(None),
+            _ => line.parse::<Priority>().map(Some),
+        })
+    }
+}
@@ -0,0 +1,181 @@
+//! Items written on one line with their details, as `add` takes them:
+//!
+//! ```text
+//! buy milk +groceries !high @tomorrow
+//! ```
+//!
+//! `!priority` and `~energy` set those, and `@when` the due date: anything
+//! the due date parser takes, with `-` for spaces as in `@next-mon` or
+//! `@tomorrow-5pm`. They're taken out of the name. `+tags` stay in it, as
+//! do `@mentions` of people, which is what an `@` word that isn't a date
+//! is. A word that looks like a detail but doesn't parse, like `!urgent`,
+//! is just part of the name.
+//!
+//! Dates win over people: `@friday` and `@tom` (tomorrow) are due dates.
+//! Doubling the `@` makes a word a mention whatever it reads as, so
+//! `@@tom` mentions Tom and leaves `@tom` in the name.
+//!
+//! `line` writes an item back the same way, for editing it in place. A due
+//! date then reads `@2024-06-30`, or `@2024-06-30-17:00` with a time, and
+//! mentions that read as dates get their `@` doubled.
+
+use chrono::{DateTime, Local};
+
+use crate::energy::Energy;
+use crate::priority::Priority;
+use crate::{date, Item};
+
+/// The due date written as `@when`, if `word` is one.
+fn due(word: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let when = word.strip_prefix('@')?;
+    date::parse_due(when, now)
+        .or_else(|| date::parse_due(&when.replace('-', " "), now))
+        .or_else(|| {
+            let (day, time) = when.rsplit_once('-')?;
+            date::parse_due(&format!("{} {}", day, time), now)
+        })
+}
+
+/// A new item from `line`, with the details written in it.
+pub fn parse(line: &str, now: DateTime<Local>) -> Item {
+    let mut priority = None;
+    let mut energy = None;
+    let mut due_date = None;
+    let mut name = Vec::new();
+    for word in line.split_whitespace() {
+        if let Some(parsed) = word
+            .strip_prefix('!')
+            .and_then(|p| p.parse::<Priority>().ok())
+        {
+            priority = Some(parsed);
+        } else if let Some(parsed) = word
+            .strip_prefix('~')
+            .and_then(|e| e.parse::<Energy>().ok())
+        {
+            energy = Some(parsed);
+        } else if word.starts_with("@@") {
+            name.push(&word[1..]);
+        } else if let Some(parsed) = due(word, now) {
+            due_date = Some(parsed);
+        } else {
+            name.push(word);
+        }
+    }
+
+    let mut item = Item::new(name.join(" "));
+    item.priority = priority;
+    item.energy = energy;
+    item.due = due_date;
+    item
+}
+
+/// `item` written as a line `parse` reads back, with the details it takes.
+#[cfg(feature = "tui")]
+pub fn line(item: &Item) -> String {
+    let now = crate::clock::now();
+    let words: Vec<String> = item
+        .name
+        .split_whitespace()
+        .map(
+            |word| match word.starts_with("@@") || due(word, now).is_some() {
+                true => format!("@{}", word),
+                false => word.to_string(),
+            },
+        )
+        .collect();
+    let mut line = words.join(" ");
+    if let Some(priority) = item.priority {
+        line += &format!(" !{}", priority);
+    }
+    if let Some(energy) = item.energy {
+        line += &format!(" ~{}", energy);
+    }
+    if let Some(due) = item.due {
+        let format = match date::is_all_day(&due) {
+            true => " @%Y-%m-%d",
+            false => " @%Y-%m-%d-%H:%M",
+        };
+        line += &due.format(format).to_string();
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn details_are_taken_from_the_line() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+
+        let item = parse("buy milk +groceries !high @tomorrow", now);
+        assert_eq!(item.name, "buy milk +groceries");
+        assert_eq!(item.tags, ["groceries"]);
+        assert_eq!(item.priority, Some(Priority::High));
+        assert_eq!(
+            item.due,
+            Local.with_ymd_and_hms(2024, 5, 7, 23, 59, 59).single()
+        );
+
+        let item = parse("call @alice ~low @next-mon-9am", now);
+        assert_eq!(item.name, "call @alice");
+        assert_eq!(item.contacts, ["alice"]);
+        assert_eq!(item.energy, Some(Energy::Low));
+        assert_eq!(
+            item.due,
+            Local.with_ymd_and_hms(2024, 5, 13, 9, 0, 0).single()
+        );
+
+        // Dates win over people, unless the @ is doubled.
+        let item = parse("call @friday", now);
+        assert_eq!((item.name.as_str(), item.contacts.len()), ("call", 0));
+        assert_eq!(
+            item.due,
+            Local.with_ymd_and_hms(2024, 5, 10, 23, 59, 59).single()
+        );
+        let item = parse("call @tom @@tom", now);
+        assert_eq!(item.name, "call @tom");
+        assert_eq!(item.contacts, ["tom"]);
+        assert_eq!(
+            item.due,
+            Local.with_ymd_and_hms(2024, 5, 7, 23, 59, 59).single()
+        );
+
+        let item = parse("fix it !urgent", now);
+        assert_eq!(item.name, "fix it !urgent");
+        assert_eq!(item.priority, None);
+        assert_eq!(item.due, None);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn lines_read_back() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+        for typed in [
+            "call @alice ~low !high @next-mon-9am",
+            "plan +work @friday",
+            "lunch with @@friday",
+        ] {
+            let item = parse(typed, now);
+            let again = parse(&line(&item), now);
+            assert_eq!(
+                (
+                    &again.name,
+                    &again.contacts,
+                    again.priority,
+                    again.energy,
+                    again.due
+                ),
+                (
+                    &item.name,
+                    &item.contacts,
+                    item.priority,
+                    item.energy,
+                    item.due
+                )
+            );
+        }
+    }
+}
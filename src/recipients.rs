@@ -0,0 +1,114 @@
+//! `todo recipients`: the other people a list synced with `--age-identity`
+//! is encrypted for, so everyone sharing it can read it with their own key.
+//!
+//! They're kept one per line in `<list>.recipients.txt` next to the list,
+//! which is a recipients file as `age -R` reads it. The list is always
+//! encrypted for the identity syncing it as well.
+
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use crate::screen::Screen;
+use crate::store;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RecipientsCommand {
+    /// Encrypt the list for someone else too, given their age public key
+    /// (age1...) or SSH public key
+    Add { recipient: Vec<String> },
+    /// Stop encrypting the list for someone
+    Remove { recipient: Vec<String> },
+}
+
+pub fn path() -> PathBuf {
+    store::sibling("recipients.txt")
+}
+
+/// The recipients of the current list, as written.
+pub fn read() -> Result<Vec<String>, String> {
+    let path = path();
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+    }
+}
+
+fn write(recipients: &[String]) -> Result<(), String> {
+    let path = path();
+    let result = match recipients.is_empty() {
+        true => std::fs::remove_file(&path),
+        false => std::fs::write(&path, recipients.join("\n") + "\n"),
+    };
+    result.map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// Whether `recipient` looks like something `age` encrypts to.
+fn check(recipient: &str) -> Result<(), String> {
+    let ssh = ["ssh-ed25519 ", "ssh-rsa "]
+        .iter()
+        .any(|prefix| recipient.starts_with(prefix));
+    let age = recipient.starts_with("age1") && recipient.chars().all(|c| c.is_ascii_alphanumeric());
+    match ssh || age {
+        true => Ok(()),
+        false => Err(format!(
+            "{} isn't an age recipient, expected age1... or an SSH public key",
+            recipient
+        )),
+    }
+}
+
+pub fn run(screen: &mut Screen, command: Option<RecipientsCommand>) -> Result<(), String> {
+    let mut recipients = read()?;
+    match command {
+        None => {}
+        Some(RecipientsCommand::Add { recipient }) => {
+            let recipient = recipient.join(" ");
+            check(&recipient)?;
+            if !recipients.contains(&recipient) {
+                recipients.push(recipient);
+                write(&recipients)?;
+            }
+        }
+        Some(RecipientsCommand::Remove { recipient }) => {
+            let recipient = recipient.join(" ");
+            let before = recipients.len();
+            recipients.retain(|other| *other != recipient);
+            if recipients.len() == before {
+                return Err(format!("{} isn't a recipient", recipient));
+            }
+            write(&recipients)?;
+        }
+    }
+
+    match recipients.is_empty() {
+        true => writeln!(screen, "[Only encrypted for your own identity]"),
+        false => {
+            writeln!(screen, "Also encrypted for:");
+            for recipient in &recipients {
+                writeln!(screen, "  {}", recipient);
+            }
+        }
+    }
+    writeln!(screen);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipients_are_checked() {
+        assert!(check("age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p").is_ok());
+        assert!(check("ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAI alice@laptop").is_ok());
+        assert!(check("age1 not a key").is_err());
+        assert!(check("alice").is_err());
+    }
+}
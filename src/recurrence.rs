@@ -0,0 +1,219 @@
+//! Items that come back: checking off a recurring item adds a fresh copy
+//! due on the next occurrence.
+//!
+//! Rules are written the way they're shown, e.g. `daily`, `weekly`,
+//! `every 3 days`, `every 2 weeks` or `every mon, thu`, and stored the
+//! same way in todo.json.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{date, Item};
+
+const WORKDAYS: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Recurrence {
+    /// Every this many days
+    Days(u32),
+    /// Every this many weeks
+    Weeks(u32),
+    /// On these days of every week
+    Weekdays(Vec<Weekday>),
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "Couldn't understand the rule {:?}, try e.g. \"daily\", \"every 3 days\" or \"every mon, thu\"",
+                s
+            )
+        };
+
+        let input = s.trim().to_lowercase().replace(',', " ");
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let words = match words.as_slice() {
+            ["every", rest @ ..] => rest,
+            words => words,
+        };
+
+        let recurrence = match words {
+            ["daily" | "day"] => Recurrence::Days(1),
+            ["weekly" | "week"] => Recurrence::Weeks(1),
+            ["weekday" | "weekdays"] => Recurrence::Weekdays(WORKDAYS.to_vec()),
+            [count, unit] if count.chars().all(|c| c.is_ascii_digit()) => {
+                let count: u32 = count.parse().map_err(|_| error())?;
+                match unit.trim_end_matches('s') {
+                    _ if count == 0 => return Err(error()),
+                    "day" => Recurrence::Days(count),
+                    "week" => Recurrence::Weeks(count),
+                    _ => return Err(error()),
+                }
+            }
+            _ => weekdays(words).ok_or_else(error)?,
+        };
+
+        Ok(recurrence)
+    }
+}
+
+/// `mon thu` as a sorted set of weekdays.
+fn weekdays(words: &[&str]) -> Option<Recurrence> {
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut days = words
+        .iter()
+        .map(|word| word.trim_end_matches('s').parse::<Weekday>().ok())
+        .collect::<Option<Vec<Weekday>>>()?;
+    days.sort_by_key(Weekday::num_days_from_monday);
+    days.dedup();
+    Some(Recurrence::Weekdays(days))
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recurrence::Days(1) => write!(f, "daily"),
+            Recurrence::Days(days) => write!(f, "every {} days", days),
+            Recurrence::Weeks(1) => write!(f, "weekly"),
+            Recurrence::Weeks(weeks) => write!(f, "every {} weeks", weeks),
+            Recurrence::Weekdays(days) if days[..] == WORKDAYS => write!(f, "weekdays"),
+            Recurrence::Weekdays(days) => {
+                let days: Vec<String> = days
+                    .iter()
+                    .map(|day| day.to_string().to_lowercase())
+                    .collect();
+                write!(f, "every {}", days.join(", "))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+impl Recurrence {
+    /// The first occurrence after `from`, or `None` past the last date
+    /// there can be.
+    fn after(&self, from: NaiveDate) -> Option<NaiveDate> {
+        let days = match self {
+            Recurrence::Days(days) => i64::from(*days),
+            Recurrence::Weeks(weeks) => i64::from(*weeks) * 7,
+            Recurrence::Weekdays(days) => {
+                let mut weekday = from.weekday();
+                (1..=7)
+                    .find(|_| {
+                        weekday = weekday.succ();
+                        days.contains(&weekday)
+                    })
+                    .unwrap_or(7)
+            }
+        };
+        from.checked_add_signed(Duration::days(days))
+    }
+}
+
+/// The copy of a recurring item to add once it's checked off: open again
+/// and due on the first occurrence after both its due date and today, at
+/// the same time of day. `None` if it doesn't repeat, or would next be
+/// after the last date there can be.
+pub fn next(item: &Item, now: DateTime<Local>) -> Option<Item> {
+    let recurrence = item.recurrence.as_ref()?;
+    let today = now.date_naive();
+
+    let mut date = recurrence.after(item.due.map_or(today, |due| due.date_naive()))?;
+    while date <= today {
+        date = recurrence.after(date)?;
+    }
+    let time = item
+        .due
+        .filter(|due| !date::is_all_day(due))
+        .map(|due| due.time());
+
+    let mut next = item.clone();
+    next.completed = false;
+    next.completed_at = None;
+    next.hidden_until = None;
+    next.postponed.clear();
+    next.due = date::at(date, time);
+    next.created = Some(now);
+    next.my_day = None;
+    next.starts_on = None;
+    next.waiting_on = None;
+    next.waiting_since = None;
+    Some(next)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    pub(crate) fn rule() -> impl Strategy<Value = Recurrence> {
+        prop_oneof![
+            (1..100u32).prop_map(Recurrence::Days),
+            (1..10u32).prop_map(Recurrence::Weeks),
+            prop::collection::btree_set(0..7u8, 1..7).prop_map(|days| {
+                let days = days
+                    .into_iter()
+                    .map(|day| Weekday::try_from(day).unwrap())
+                    .collect();
+                Recurrence::Weekdays(days)
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn rules_parse_back(rule in rule()) {
+            prop_assert_eq!(rule.to_string().parse::<Recurrence>(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn next_weekday_after_due_date() {
+        let now = date::at(NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(), None).unwrap();
+        let mut item = Item::new("standup".to_string());
+        item.recurrence = Some("every mon, thu".parse().unwrap());
+        item.due = date::parse_due("2024-05-06 9:30", now);
+
+        let next = next(&item, now).unwrap();
+        assert_eq!(next.due, date::parse_due("2024-05-09 9:30", now));
+    }
+
+    #[test]
+    fn no_next_past_the_last_date() {
+        let now = Local::now();
+        let mut item = Item::new("someday".to_string());
+        item.recurrence = Some("every 4000000000 weeks".parse().unwrap());
+        item.due = Some(now);
+        assert_eq!(next(&item, now), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Times said relative to now, like "in 3 days" or "vor 2 Tagen", in the
+//! language the locale asks for (`LC_ALL`, `LC_TIME` or `LANG`): English,
+//! German, French or Spanish, and English for anything else.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl Language {
+    /// The language of a locale like `de_DE.UTF-8`.
+    fn of_locale(locale: &str) -> Language {
+        match locale.get(..2) {
+            Some("de") => Language::German,
+            Some("fr") => Language::French,
+            Some("es") => Language::Spanish,
+            _ => Language::English,
+        }
+    }
+
+    /// Whether `count` takes the plural. French counts 0 and 1 as one.
+    fn is_plural(self, count: i64) -> bool {
+        match self {
+            Language::French => count > 1,
+            _ => count != 1,
+        }
+    }
+
+    /// A unit's name, one and many. German has the dative, as after "in"
+    /// and "vor".
+    fn words(self, unit: Unit) -> (&'static str, &'static str) {
+        match (self, unit) {
+            (Language::English, Unit::Minute) => ("minute", "minutes"),
+            (Language::English, Unit::Hour) => ("hour", "hours"),
+            (Language::English, Unit::Day) => ("day", "days"),
+            (Language::English, Unit::Week) => ("week", "weeks"),
+            (Language::English, Unit::Month) => ("month", "months"),
+            (Language::English, Unit::Year) => ("year", "years"),
+            (Language::German, Unit::Minute) => ("Minute", "Minuten"),
+            (Language::German, Unit::Hour) => ("Stunde", "Stunden"),
+            (Language::German, Unit::Day) => ("Tag", "Tagen"),
+            (Language::German, Unit::Week) => ("Woche", "Wochen"),
+            (Language::German, Unit::Month) => ("Monat", "Monaten"),
+            (Language::German, Unit::Year) => ("Jahr", "Jahren"),
+            (Language::French, Unit::Minute) => ("minute", "minutes"),
+            (Language::French, Unit::Hour) => ("heure", "heures"),
+            (Language::French, Unit::Day) => ("jour", "jours"),
+            (Language::French, Unit::Week) => ("semaine", "semaines"),
+            (Language::French, Unit::Month) => ("mois", "mois"),
+            (Language::French, Unit::Year) => ("an", "ans"),
+            (Language::Spanish, Unit::Minute) => ("minuto", "minutos"),
+            (Language::Spanish, Unit::Hour) => ("hora", "horas"),
+            (Language::Spanish, Unit::Day) => ("día", "días"),
+            (Language::Spanish, Unit::Week) => ("semana", "semanas"),
+            (Language::Spanish, Unit::Month) => ("mes", "meses"),
+            (Language::Spanish, Unit::Year) => ("año", "años"),
+        }
+    }
+
+    fn now(self) -> &'static str {
+        match self {
+            Language::English => "now",
+            Language::German => "jetzt",
+            Language::French => "maintenant",
+            Language::Spanish => "ahora",
+        }
+    }
+
+    fn future(self, amount: &str) -> String {
+        match self {
+            Language::English => format!("in {}", amount),
+            Language::German => format!("in {}", amount),
+            Language::French => format!("dans {}", amount),
+            Language::Spanish => format!("dentro de {}", amount),
+        }
+    }
+
+    fn past(self, amount: &str) -> String {
+        match self {
+            Language::English => format!("{} ago", amount),
+            Language::German => format!("vor {}", amount),
+            Language::French => format!("il y a {}", amount),
+            Language::Spanish => format!("hace {}", amount),
+        }
+    }
+}
+
+/// The language from the environment, looked up once.
+pub fn language() -> Language {
+    static LANGUAGE: OnceLock<Language> = OnceLock::new();
+    *LANGUAGE.get_or_init(|| {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .map_or(Language::English, |locale| Language::of_locale(&locale))
+    })
+}
+
+/// The largest unit that fits into `minutes`, and how many of it.
+fn amount(minutes: i64) -> Option<(i64, Unit)> {
+    let days = minutes / (60 * 24);
+    match minutes {
+        0 => None,
+        _ if minutes < 60 => Some((minutes, Unit::Minute)),
+        _ if days < 1 => Some((minutes / 60, Unit::Hour)),
+        _ if days < 14 => Some((days, Unit::Day)),
+        _ if days < 60 => Some((days / 7, Unit::Week)),
+        _ if days < 365 => Some((days / 30, Unit::Month)),
+        _ => Some((days / 365, Unit::Year)),
+    }
+}
+
+/// `time` as said from `now` in `language`.
+pub fn describe_in(language: Language, time: DateTime<Local>, now: DateTime<Local>) -> String {
+    let minutes = (time - now).num_minutes();
+    let Some((count, unit)) = amount(minutes.abs()) else {
+        return language.now().to_string();
+    };
+    let (one, many) = language.words(unit);
+    let words = if language.is_plural(count) { many } else { one };
+    let amount = format!("{} {}", count, words);
+    match minutes > 0 {
+        true => language.future(&amount),
+        false => language.past(&amount),
+    }
+}
+
+/// `time` as said from `now` in the locale's language.
+pub fn describe(time: DateTime<Local>, now: DateTime<Local>) -> String {
+    describe_in(language(), time, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn relative_times() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+        let say = |language, minutes| describe_in(language, now + Duration::minutes(minutes), now);
+        let day = 60 * 24;
+
+        assert_eq!(say(Language::English, 0), "now");
+        assert_eq!(say(Language::English, 1), "in 1 minute");
+        assert_eq!(say(Language::English, 3 * day), "in 3 days");
+        assert_eq!(say(Language::English, -day), "1 day ago");
+        assert_eq!(say(Language::English, -400 * day), "1 year ago");
+        assert_eq!(say(Language::German, -2 * day), "vor 2 Tagen");
+        assert_eq!(say(Language::German, day), "in 1 Tag");
+        assert_eq!(say(Language::German, 3 * 60), "in 3 Stunden");
+        assert_eq!(say(Language::French, 21 * day), "dans 3 semaines");
+        assert_eq!(say(Language::French, -90), "il y a 1 heure");
+        assert_eq!(say(Language::Spanish, -90 * day), "hace 3 meses");
+        assert_eq!(say(Language::Spanish, 2 * 365 * day), "dentro de 2 años");
+    }
+
+    #[test]
+    fn locales() {
+        assert_eq!(Language::of_locale("de_DE.UTF-8"), Language::German);
+        assert_eq!(Language::of_locale("fr"), Language::French);
+        assert_eq!(Language::of_locale("C.UTF-8"), Language::English);
+        assert_eq!(Language::of_locale(""), Language::English);
+    }
+}
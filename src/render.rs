@@ -0,0 +1,325 @@
+//! How an item is drawn in a list: its checkbox, name and details, styled
+//! on terminals that show colors. How completed, overdue and high priority
+//! items stand out depends on the theme, see `theme`.
+//!
+//! The checkboxes can be changed in the config:
+//!
+//! ```toml
+//! [checkbox]
+//! open = "☐"
+//! done = "☑"
+//! cancelled = "☒"
+//! ```
+//!
+//! Colors are left out with `--no-color`, `NO_COLOR`, `color = false` in
+//! the config, or when output isn't a terminal.
+//!
+//! On a terminal the list fits its width. Where every item fits on a line,
+//! the details are lined up in a column after the names, like a table.
+//! Otherwise names too long for the terminal wrap with the following lines
+//! lined up under the name, except on narrow terminals, like a phone's,
+//! where each item keeps to one line and is cut short with `…`.
+//!
+//! With `--redact` names and tags have their letters and digits masked, so
+//! a list can be shown on a shared screen: how many items there are, their
+//! checkboxes, priorities and due dates still show.
+
+use chrono::{DateTime, Local};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::priority::Priority;
+use crate::screen::{Screen, Style};
+use crate::{clock, config, date, defer, relative, someday, tags, theme, Item};
+
+/// Below this many columns items keep to one line each, as wrapping would
+/// leave too little room for the name.
+const NARROW: usize = 50;
+
+/// How items are laid out for the terminal's width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// Each item on one line however long: output isn't a terminal.
+    Plain,
+    /// Details lined up after names padded to end at this column.
+    Table(usize),
+    /// Long names wrapped to this width.
+    Wrapped(usize),
+    /// Each item on one line, cut short to this width.
+    Compact(usize),
+}
+
+pub fn checkbox(item: &Item) -> &'static str {
+    let checkbox = config::get().checkbox.as_ref();
+    let [open, done, cancelled] = theme::get().checkboxes;
+    let (configured, default) = match (item.cancelled, item.completed) {
+        (true, _) => (checkbox.and_then(|c| c.cancelled.as_deref()), cancelled),
+        (false, true) => (checkbox.and_then(|c| c.done.as_deref()), done),
+        (false, false) => (checkbox.and_then(|c| c.open.as_deref()), open),
+    };
+    configured.unwrap_or(default)
+}
+
+/// `text` with its letters and digits masked.
+pub fn redact(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_alphanumeric() { '•' } else { c })
+        .collect()
+}
+
+/// What's shown after an open item's name: tags not written in it, the
+/// quantity, priority, dates and so on. Tags are masked if `redacted`.
+pub fn details(item: &Item, redacted: bool) -> String {
+    let mut details = String::new();
+    if item.completed {
+        return details;
+    }
+
+    let named_tags = tags::parse_tags(&item.name);
+    for tag in item.tags.iter().filter(|tag| !named_tags.contains(tag)) {
+        match redacted {
+            true => details += &format!(" +{}", redact(tag)),
+            false => details += &format!(" +{}", tag),
+        }
+    }
+    if let Some(quantity) = &item.quantity {
+        details += &format!(" ({})", quantity);
+    }
+    if let Some(priority) = item.priority {
+        details += &format!(" !{}", priority);
+    }
+    if let Some(energy) = item.energy {
+        details += &format!(" ~{}", energy);
+    }
+    if let Some(due) = &item.due {
+        let soft = if item.soft { ", soft" } else { "" };
+        let due = match config::get().relative_dates {
+            Some(true) => relative::describe(*due, clock::now()),
+            _ => date::show_due(due),
+        };
+        details += &format!(" (due {}{})", due, soft);
+    }
+    if let Some(expires) = &item.expires {
+        details += &format!(" (expires {})", date::show_due(expires));
+    }
+    if let Some(recurrence) = &item.recurrence {
+        details += &format!(" ({})", recurrence);
+    }
+    if !defer::is_started(item) {
+        details += &format!(" (starts {})", item.starts_on.unwrap());
+    }
+    if let Some(until) = item
+        .hidden_until
+        .filter(|_| defer::is_snoozed(item, clock::now()))
+    {
+        details += &format!(" (snoozed until {})", until.format("%Y-%m-%d %H:%M"));
+    }
+    if someday::is_someday(item) {
+        details += " (someday)";
+    }
+    details
+}
+
+/// The item as one line of plain text.
+pub fn text(item: &Item) -> String {
+    head(item, false) + &details(item, false)
+}
+
+/// The item as one line of plain text, with what it's about masked.
+pub fn redacted(item: &Item) -> String {
+    head(item, true) + &details(item, true)
+}
+
+/// How the item stands out in a list at `now`.
+pub fn style(item: &Item, now: DateTime<Local>) -> Style {
+    let theme = theme::get();
+    if item.completed {
+        return theme.done;
+    }
+    let mut style = Style::default();
+    if !item.soft && item.due.is_some_and(|due| due < now) {
+        style = style.and(theme.overdue);
+    }
+    if item.priority == Some(Priority::High) {
+        style = style.and(theme.high);
+    }
+    style
+}
+
+/// Splits `text` at spaces into lines of at most `first` characters for the
+/// first line and `rest` for the others. Words longer than that get a line
+/// of their own.
+fn wrap(text: &str, first: usize, rest: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split(' ') {
+        let width = if lines.len() == 1 { first } else { rest };
+        let line = lines.last_mut().unwrap();
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.chars().count() > width {
+            lines.push(word.to_string());
+        } else {
+            if line_len > 0 {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    lines
+}
+
+/// `text` cut short with `…` to at most `width` columns, between graphemes.
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut cut = String::new();
+    for grapheme in text.graphemes(true) {
+        if cut.width() + grapheme.width() + 1 > width {
+            break;
+        }
+        cut.push_str(grapheme);
+    }
+    cut + "…"
+}
+
+/// The checkbox and name of `item`, the first part of its line.
+fn head(item: &Item, redact: bool) -> String {
+    match redact {
+        true => format!("{} {}", checkbox(item), self::redact(&item.name)),
+        false => format!("{} {}", checkbox(item), item.name),
+    }
+}
+
+/// The layout for `rows` of items, each after its indent, on a terminal
+/// `width` columns wide if it is one.
+pub fn layout(width: Option<usize>, rows: &[(usize, &Item)], redact: bool) -> Layout {
+    let Some(width) = width else {
+        return Layout::Plain;
+    };
+    let column = rows
+        .iter()
+        .map(|(indent, item)| indent + head(item, redact).width())
+        .max()
+        .unwrap_or(0);
+    let details = rows
+        .iter()
+        .map(|(_, item)| details(item, redact).width())
+        .max()
+        .unwrap_or(0);
+    match width {
+        _ if column + details <= width => Layout::Table(column),
+        _ if width < NARROW => Layout::Compact(width),
+        _ => Layout::Wrapped(width),
+    }
+}
+
+/// Writes the item in `style` after `indent` columns already written on
+/// the line, laid out by `layout`.
+pub fn write_item(
+    screen: &mut Screen,
+    item: &Item,
+    indent: usize,
+    style: Style,
+    redact: bool,
+    layout: Layout,
+) {
+    let head = head(item, redact);
+    let text = match redact {
+        true => redacted(item),
+        false => text(item),
+    };
+    let hanging = indent + checkbox(item).width() + 1;
+    let lines = match layout {
+        Layout::Plain => vec![text],
+        Layout::Table(column) => {
+            let padding = (column - indent).saturating_sub(head.width());
+            let details = details(item, redact);
+            match details.is_empty() {
+                true => vec![head],
+                false => vec![format!(
+                    "{}{:padding$}{}",
+                    head,
+                    "",
+                    details,
+                    padding = padding
+                )],
+            }
+        }
+        Layout::Wrapped(width) => wrap(&text, width - indent, width - hanging),
+        Layout::Compact(width) => vec![truncate(&text, width.saturating_sub(indent))],
+    };
+
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            write!(screen, "\n{:hanging$}", "", hanging = hanging);
+        }
+        screen.write_styled(style, line);
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn long_names_wrap() {
+        assert_eq!(
+            wrap("[ ] send the quarterly report to bob", 16, 12),
+            ["[ ] send the", "quarterly", "report to", "bob"]
+        );
+        assert_eq!(wrap("[ ] short", 16, 12), ["[ ] short"]);
+        assert_eq!(
+            wrap("[ ] a-very-long-word", 8, 8),
+            ["[ ]", "a-very-long-word"]
+        );
+    }
+
+    #[test]
+    fn truncated_between_graphemes() {
+        assert_eq!(truncate("[ ] buy milk", 20), "[ ] buy milk");
+        assert_eq!(truncate("[ ] buy milk", 8), "[ ] buy…");
+        // Wide characters take two columns, and accents stay on their letter.
+        assert_eq!(truncate("日本語の本", 6), "日本…");
+        assert_eq!(truncate("cafe\u{301} au lait", 5), "cafe\u{301}…");
+    }
+
+    #[test]
+    fn layouts_fit_the_width() {
+        let todos = sample();
+        let rows: Vec<(usize, &Item)> = todos.iter().map(|item| (2, item)).collect();
+        assert_eq!(layout(None, &rows, false), Layout::Plain);
+        assert_eq!(layout(Some(200), &rows, false), Layout::Table(31));
+        assert_eq!(layout(Some(60), &rows, false), Layout::Wrapped(60));
+        assert_eq!(layout(Some(40), &rows, false), Layout::Compact(40));
+    }
+
+    #[test]
+    fn redacted_items_keep_their_details() {
+        let mut item = sample()[0].clone();
+        item.tags.push("q2".to_string());
+        item.due = None;
+        assert_eq!(redacted(&item), "[ ] •••• •••••• •• @••• +•••• +•• !high");
+    }
+
+    #[test]
+    fn styles() {
+        let now = chrono::Local::now();
+        let todos = sample();
+        assert_eq!(
+            style(&todos[0], now),
+            Style {
+                color: Some(Color::Red),
+                bold: true,
+                dim: false,
+                underline: false,
+            }
+        );
+        assert_eq!(style(&todos[1], now), Style::default());
+        assert!(style(&todos[3], now).dim);
+    }
+}
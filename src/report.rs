@@ -0,0 +1,84 @@
+//! `todo report procrastination`: the open items put off most often, by
+//! snoozing, deferring or moving their due date, so they can be done or
+//! dropped rather than pushed back again. `todo report late-nights` is in
+//! `burnout`.
+
+use chrono::{DateTime, Local};
+use clap::Subcommand;
+
+use crate::screen::Screen;
+use crate::Todos;
+
+/// How many items the report lists.
+const LIMIT: usize = 10;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ReportCommand {
+    /// The open items put off most often
+    Procrastination,
+    /// When items get checked off, and how many late nights that took
+    LateNights,
+}
+
+pub fn print_procrastination(screen: &mut Screen, todos: &Todos, now: DateTime<Local>) {
+    let mut put_off: Vec<usize> = (0..todos.len())
+        .filter(|&i| !todos[i].completed && !todos[i].postponed.is_empty())
+        .collect();
+    if put_off.is_empty() {
+        writeln!(screen, "[Nothing has been put off]\n");
+        return;
+    }
+    // Most often first, then the ones first put off longest ago.
+    put_off.sort_by_key(|&i| {
+        (
+            std::cmp::Reverse(todos[i].postponed.len()),
+            todos[i].postponed.iter().min().copied(),
+        )
+    });
+
+    writeln!(screen, "Put off most often\n");
+    let width = todos.len().saturating_sub(1).to_string().len();
+    for &i in put_off.iter().take(LIMIT) {
+        let item = &todos[i];
+        let first = item.postponed.iter().min().unwrap();
+        writeln!(
+            screen,
+            "{:>3}× {:>width$} {}, first put off {} days ago",
+            item.postponed.len(),
+            i,
+            item,
+            (now - *first).num_days(),
+            width = width
+        );
+    }
+    if put_off.len() > LIMIT {
+        writeln!(screen, "     and {} more", put_off.len() - LIMIT);
+    }
+    writeln!(
+        screen,
+        "\nDo them, or let them go with `todo remove {}`\n",
+        put_off[0]
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn procrastination() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+        let mut todos = sample();
+        todos[1].postponed = vec![now - Duration::days(3)];
+        todos[2].postponed = (1..4).map(|days| now - Duration::days(days * 7)).collect();
+        todos[3].postponed = vec![now - Duration::days(1)];
+
+        let mut screen = Screen::plain();
+        print_procrastination(&mut screen, &todos, now);
+        print_procrastination(&mut screen, &Vec::new(), now);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,394 @@
+//! Importing `TODO:`/`FIXME:` markers as items.
+
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::{self, Operation};
+use crate::screen::Screen;
+use crate::{clock, tags, Item, Todos};
+
+const MARKERS: [&str; 2] = ["TODO:", "FIXME:"];
+const COMMENT_MARKERS: [&str; 2] = ["// TODO", "// FIXME"];
+const RECENT_COMMITS: &str = "20";
+const SKIPPED_DIRS: [&str; 2] = ["target", "node_modules"];
+
+/// Where a scanned item came from: the file and line of its comment.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Source {
+    file: String,
+    /// Counted from 1, and 0 for items tracked before lines were kept.
+    #[serde(default)]
+    line: usize,
+    text: String,
+}
+
+/// Tracks `// TODO` comments under `root` as items: comments not seen before
+/// are added, and open items whose comment has disappeared are completed. A
+/// comment is the same one when it's at the same line, or failing that has
+/// the same text in the same file, so edits elsewhere that move it don't
+/// import it again. All of it is one change to undo.
+pub fn scan_source(todos: &mut Todos, root: &str, screen: &mut Screen) -> Result<(), String> {
+    let root = normalize(Path::new(root));
+    let walk_root = if root.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &root
+    };
+    let mut found = Vec::new();
+    walk(walk_root, &mut found);
+
+    let tracked: Vec<usize> = (0..todos.len())
+        .filter(|&i| {
+            let source = todos[i].source.as_ref();
+            source.is_some_and(|source| Path::new(&source.file).starts_with(&root))
+        })
+        .collect();
+    let mut seen = vec![false; todos.len()];
+    let mut moved = Vec::new();
+    for source in &found {
+        let same = tracked
+            .iter()
+            .find(|&&i| !seen[i] && todos[i].source.as_ref() == Some(source));
+        match same {
+            Some(&i) => seen[i] = true,
+            None => moved.push(source),
+        }
+    }
+    let mut operations = Vec::new();
+    let mut added = Vec::new();
+    for source in moved {
+        let same_text = tracked.iter().find(|&&i| {
+            let tracked = todos[i].source.as_ref().expect("only items with a source");
+            !seen[i] && tracked.file == source.file && tracked.text == source.text
+        });
+        match same_text {
+            Some(&i) => {
+                seen[i] = true;
+                let mut after = todos[i].clone();
+                after.source = Some(source.clone());
+                operations.push(Operation::replace(todos, i, after));
+            }
+            None => {
+                let mut item = Item::new(source.text.clone());
+                item.source = Some(source.clone());
+                added.push(item);
+            }
+        }
+    }
+
+    let mut completed = 0;
+    for i in tracked {
+        if !seen[i] && !todos[i].completed {
+            let mut after = todos[i].clone();
+            after.completed = true;
+            after.completed_at = Some(clock::now());
+            operations.push(Operation::replace(todos, i, after));
+            completed += 1;
+        }
+    }
+
+    let new = added.len();
+    for (i, item) in added.into_iter().enumerate() {
+        let index = todos.len() + i;
+        operations.push(Operation::Insert { index, item });
+    }
+    if !operations.is_empty() {
+        history::perform(todos, Operation::Group { operations })?;
+    }
+
+    writeln!(
+        screen,
+        "Scanned {}: {} new, {} completed, {} tracked",
+        walk_root.display(),
+        new,
+        completed,
+        found.len()
+    );
+    Ok(())
+}
+
+/// Scans the files under `dir`. Links aren't followed, as one pointing back
+/// up would never end.
+fn walk(dir: &Path, found: &mut Vec<Source>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return scan_file(dir, found),
+    };
+
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => walk(&path, found),
+            Ok(kind) if kind.is_file() => scan_file(&path, found),
+            _ => {}
+        }
+    }
+}
+
+fn scan_file(path: &Path, found: &mut Vec<Source>) {
+    // Binary and non-UTF-8 files are skipped.
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+
+    let file = normalize(path).to_string_lossy().into_owned();
+    for (number, line) in content.lines().enumerate() {
+        if let Some(text) = extract_comment(line) {
+            found.push(Source {
+                file: file.clone(),
+                line: number + 1,
+                text,
+            });
+        }
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+fn extract_comment(line: &str) -> Option<String> {
+    COMMENT_MARKERS.iter().find_map(|marker| {
+        let start = line.find(marker)? + marker.len();
+        let text = line[start..]
+            .trim_start_matches(':')
+            .trim()
+            .trim_end_matches("*/")
+            .trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    })
+}
+
+/// Collects markers from recent commit messages and the tracked files of the
+/// git repository in the current directory, then asks which ones to import.
+pub fn scan_git(todos: &mut Todos, screen: &mut Screen) -> Result<(), String> {
+    let toplevel = match git(&["rev-parse", "--show-toplevel"]) {
+        Some(toplevel) => toplevel,
+        None => {
+            writeln!(screen, "Not inside a git repository\n");
+            return Ok(());
+        }
+    };
+    let repo_name = Path::new(toplevel.trim())
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut found: Vec<String> = Vec::new();
+    let tag = tags::normalize(&repo_name);
+    let sources = [
+        git(&["log", "-n", RECENT_COMMITS, "--format=%B"]),
+        git(&["grep", "-h", "-I", "-E", "(TODO|FIXME):"]),
+    ];
+    for output in sources.iter().flatten() {
+        for name in output.lines().filter_map(extract_marker) {
+            let known = todos
+                .iter()
+                .any(|item| item.name == name && item.tags.contains(&tag));
+            if !found.contains(&name) && !known {
+                found.push(name);
+            }
+        }
+    }
+
+    import_confirmed(todos, found, &tag, screen)
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn extract_marker(line: &str) -> Option<String> {
+    MARKERS.iter().find_map(|marker| {
+        let start = line.find(marker)? + marker.len();
+        let text = line[start..].trim().trim_end_matches("*/").trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    })
+}
+
+/// Asks which of the `candidates` to import and adds them, tagged `tag`,
+/// as one change to undo.
+fn import_confirmed(
+    todos: &mut Todos,
+    candidates: Vec<String>,
+    tag: &str,
+    screen: &mut Screen,
+) -> Result<(), String> {
+    if candidates.is_empty() {
+        writeln!(screen, "No new TODO/FIXME markers found\n");
+        return Ok(());
+    }
+
+    writeln!(
+        screen,
+        "Found {} new markers in +{}:",
+        candidates.len(),
+        tag
+    );
+    for (i, name) in candidates.iter().enumerate() {
+        writeln!(screen, "{} {}", i, name);
+    }
+    writeln!(
+        screen,
+        "\nImport which? (a)ll, (n)one, or indices separated by spaces"
+    );
+
+    let selected = selection(&screen.read_line(), candidates.len());
+    writeln!(screen);
+    match imports(todos, candidates, &selected, tag) {
+        Some(operation) => history::perform(todos, operation),
+        None => Ok(()),
+    }
+}
+
+/// The indices picked by the answer `line` out of `count` candidates.
+/// Anything that isn't one of them is left out.
+fn selection(line: &str, count: usize) -> Vec<usize> {
+    match line {
+        "a" | "all" => (0..count).collect(),
+        "" | "n" | "none" => Vec::new(),
+        _ => line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(|part| part.parse::<usize>().ok())
+            .filter(|i| *i < count)
+            .collect(),
+    }
+}
+
+/// The change adding the `selected` candidates tagged `tag`, or `None` if
+/// none are.
+fn imports(
+    todos: &Todos,
+    candidates: Vec<String>,
+    selected: &[usize],
+    tag: &str,
+) -> Option<Operation> {
+    let operations: Vec<Operation> = candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected.contains(i))
+        .enumerate()
+        .map(|(n, (_, name))| {
+            let mut item = Item::new(name);
+            if !item.tags.iter().any(|existing| existing == tag) {
+                item.tags.push(tag.to_string());
+            }
+            Operation::Insert {
+                index: todos.len() + n,
+                item,
+            }
+        })
+        .collect();
+    match operations.is_empty() {
+        true => None,
+        false => Some(Operation::Group { operations }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_are_told_apart_by_line() {
+        let _store = crate::tests::temp_store();
+        let dir = std::env::temp_dir().join(format!("todo-scan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("main.rs");
+        std::fs::write(&file, "// TODO: test\nfn a() {}\n// TODO: test\n").unwrap();
+        // A link back up, which would be walked forever if followed.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+        let root = dir.to_string_lossy();
+        let mut todos = Todos::new();
+        let mut screen = Screen::plain();
+        scan_source(&mut todos, &root, &mut screen).unwrap();
+        assert_eq!(todos.len(), 2);
+
+        // Moved by a line added above: still the same two.
+        std::fs::write(&file, "\n// TODO: test\nfn a() {}\n// TODO: test\n").unwrap();
+        scan_source(&mut todos, &root, &mut screen).unwrap();
+        assert_eq!(todos.len(), 2);
+        assert!(todos.iter().all(|item| !item.completed));
+
+        let before = todos.clone();
+        std::fs::write(&file, "// TODO: test\n").unwrap();
+        scan_source(&mut todos, &root, &mut screen).unwrap();
+        assert_eq!(todos.iter().filter(|item| item.completed).count(), 1);
+        assert!(screen
+            .contents()
+            .ends_with("0 new, 1 completed, 1 tracked\n"));
+
+        // The whole scan is undone at once.
+        history::undo(&mut todos).unwrap();
+        assert_eq!(todos, before);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn markers_are_found_in_lines() {
+        assert_eq!(
+            extract_marker("    // TODO: handle errors"),
+            Some("handle errors".to_string())
+        );
+        assert_eq!(
+            extract_marker("/* FIXME: off by one */"),
+            Some("off by one".to_string())
+        );
+        assert_eq!(extract_marker("// TODO:   "), None);
+        assert_eq!(extract_marker("// TODO handle errors"), None);
+        assert_eq!(extract_marker("nothing to do"), None);
+    }
+
+    #[test]
+    fn selections_are_parsed() {
+        assert_eq!(selection("all", 3), [0, 1, 2]);
+        assert_eq!(selection("a", 2), [0, 1]);
+        assert!(selection("none", 3).is_empty());
+        assert!(selection("", 3).is_empty());
+        assert_eq!(selection("0 2", 3), [0, 2]);
+        assert_eq!(selection("2,0, 7 x", 3), [2, 0]);
+    }
+
+    #[test]
+    fn selected_markers_are_imported_as_one_change() {
+        let mut todos = crate::tests::sample();
+        let candidates = vec!["fix tests".to_string(), "write docs".to_string()];
+        assert!(imports(&todos, candidates.clone(), &[], "todo-rs").is_none());
+
+        let operation = imports(&todos, candidates, &[1], "todo-rs").unwrap();
+        assert!(matches!(operation, Operation::Group { ref operations } if operations.len() == 1));
+        operation.apply(&mut todos).unwrap();
+        assert_eq!(todos.len(), 6);
+        assert_eq!(todos[5].name, "write docs");
+        assert_eq!(todos[5].tags, ["todo-rs"]);
+    }
+}
@@ -0,0 +1,286 @@
+//! Output for the interactive prompt. Everything printed for a frame is
+//! queued and written to the terminal in one go right before waiting for
+//! input, so the screen never shows a half-drawn list after a clear.
+//!
+//! All terminal control goes through crossterm, which uses escape codes
+//! where the terminal understands them and the console API on legacy
+//! Windows consoles that don't.
+//!
+//! When input or output isn't a terminal, or `TERM=dumb`, the prompt is
+//! line-oriented instead: nothing is cleared or colored and the command
+//! menu isn't shown, so it can be driven by a script.
+
+use std::fmt::Display;
+use std::io::{IsTerminal, Write};
+
+use crossterm::cursor::MoveTo;
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use serde::Deserialize;
+
+use crate::{autosave, config};
+
+/// How text stands out, where the terminal can show it. Written in the
+/// config as e.g. `"red bold"`, see `theme`.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    /// This style with `other`'s added, its color winning.
+    pub fn and(self, other: Style) -> Style {
+        Style {
+            color: other.color.or(self.color),
+            bold: self.bold || other.bold,
+            dim: self.dim || other.dim,
+            underline: self.underline || other.underline,
+        }
+    }
+}
+
+pub struct Screen {
+    buffer: String,
+    clear_pending: bool,
+    interactive: bool,
+    /// Whether frames are cleared and drawn on the alternate screen, which
+    /// `clear_screen = false` in the config turns off.
+    clears: bool,
+    colors: bool,
+    /// The terminal's width, when writing to one.
+    width: Option<usize>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+        let stdout = std::io::stdout().is_terminal() && !dumb;
+        let config = config::get();
+        // https://no-color.org: set and not empty.
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+        Screen {
+            buffer: String::new(),
+            clear_pending: false,
+            interactive: stdout && std::io::stdin().is_terminal(),
+            clears: config.clear_screen != Some(false),
+            colors: stdout && supports_ansi() && config.color != Some(false) && !no_color,
+            width: match stdout {
+                true => crossterm::terminal::size()
+                    .ok()
+                    .map(|(width, _)| width as usize),
+                false => None,
+            },
+        }
+    }
+
+    /// Whether a person is at the prompt rather than a script.
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Switches to the terminal's alternate screen so clearing frames
+    /// doesn't wipe the user's scrollback, and makes sure Ctrl-C switches
+    /// back. Does nothing when the prompt isn't interactive.
+    pub fn enter_alternate_screen(&mut self) {
+        if !self.interactive || !self.clears {
+            return;
+        }
+
+        // Staying on the main screen is only untidier, so failures are let go.
+        let _ = execute!(std::io::stdout(), EnterAlternateScreen);
+        let _ = ctrlc::set_handler(|| {
+            let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+            autosave::exit(130);
+        });
+    }
+
+    /// Restores the user's terminal; anything still queued is dropped
+    /// along with the alternate screen.
+    pub fn leave_alternate_screen(&mut self) {
+        self.buffer.clear();
+        self.clear_pending = false;
+        if self.interactive && self.clears {
+            let _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+        }
+    }
+
+    /// Starts a new frame. Anything queued for the previous one would be
+    /// wiped by the clear anyway, so it is dropped rather than written.
+    /// Line-oriented output is never cleared.
+    pub fn clear(&mut self) {
+        if !self.interactive || !self.clears {
+            return;
+        }
+        self.buffer.clear();
+        self.clear_pending = true;
+    }
+
+    /// Lets `write!`/`writeln!` queue text without a `Result` to handle,
+    /// since appending to the buffer can't fail.
+    pub fn write_fmt(&mut self, args: std::fmt::Arguments) {
+        std::fmt::Write::write_fmt(&mut self.buffer, args).unwrap();
+    }
+
+    /// Queues `text` in `style` when the terminal can show it, plainly
+    /// otherwise.
+    pub fn write_styled(&mut self, style: Style, text: impl Display) {
+        if !self.colors || style == Style::default() {
+            return write!(self, "{}", text);
+        }
+
+        use crossterm::Command;
+        if let Some(color) = style.color {
+            SetForegroundColor(color)
+                .write_ansi(&mut self.buffer)
+                .unwrap();
+        }
+        if style.bold {
+            SetAttribute(Attribute::Bold)
+                .write_ansi(&mut self.buffer)
+                .unwrap();
+        }
+        if style.dim {
+            SetAttribute(Attribute::Dim)
+                .write_ansi(&mut self.buffer)
+                .unwrap();
+        }
+        if style.underline {
+            SetAttribute(Attribute::Underlined)
+                .write_ansi(&mut self.buffer)
+                .unwrap();
+        }
+        write!(self, "{}", text);
+        SetAttribute(Attribute::Reset)
+            .write_ansi(&mut self.buffer)
+            .unwrap();
+    }
+
+    /// How many columns there are to write in, if output is a terminal.
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    pub fn flush(&mut self) {
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        let mut result = Ok(());
+        if self.clear_pending {
+            result = queue!(out, Clear(ClearType::All), MoveTo(0, 0));
+            self.clear_pending = false;
+        }
+        let result = result
+            .and_then(|_| out.write_all(self.buffer.as_bytes()))
+            .and_then(|_| out.flush());
+        self.buffer.clear();
+        if let Err(e) = result {
+            drop(out);
+            self.fail("write the output", e);
+        }
+    }
+
+    /// Shows the frame and waits for a line of input. The end of input
+    /// ends the session, which is how a script driving the prompt stops.
+    pub fn read_line(&mut self) -> String {
+        self.flush();
+
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) => {
+                self.leave_alternate_screen();
+                autosave::exit(0);
+            }
+            Ok(_) => line.trim().to_string(),
+            Err(e) => self.fail("read the input", e),
+        }
+    }
+
+    /// Waits for a line of input that isn't shown as it's typed, like a
+    /// password. Input that isn't from a terminal is read as any other.
+    pub fn read_hidden(&mut self) -> String {
+        if !self.interactive {
+            return self.read_line();
+        }
+        self.flush();
+
+        let mut line = String::new();
+        if let Err(e) = crossterm::terminal::enable_raw_mode() {
+            self.fail("hide the input", e);
+        }
+        let result = loop {
+            match crossterm::event::read() {
+                Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => match key.code {
+                    KeyCode::Enter => break Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        let _ = crossterm::terminal::disable_raw_mode();
+                        self.leave_alternate_screen();
+                        autosave::exit(130);
+                    }
+                    KeyCode::Char(c) => line.push(c),
+                    KeyCode::Backspace => {
+                        line.pop();
+                    }
+                    _ => {}
+                },
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            }
+        };
+        let _ = crossterm::terminal::disable_raw_mode();
+        if let Err(e) = result {
+            self.fail("read the input", e);
+        }
+        println!();
+        line.trim().to_string()
+    }
+
+    /// Ends the session when the terminal itself stops working. Output
+    /// piped into something that stopped reading, as `todo list | head`
+    /// does, isn't an error.
+    fn fail(&mut self, doing: &str, error: std::io::Error) -> ! {
+        self.leave_alternate_screen();
+        if error.kind() == std::io::ErrorKind::BrokenPipe {
+            autosave::exit(0);
+        }
+        eprintln!("Could not {}: {}", doing, error);
+        autosave::exit(1);
+    }
+}
+
+/// Colors are queued as escape codes along with the text, so they are
+/// only used on terminals that understand them.
+#[cfg(windows)]
+fn supports_ansi() -> bool {
+    crossterm::ansi_support::supports_ansi()
+}
+
+#[cfg(not(windows))]
+fn supports_ansi() -> bool {
+    true
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl Screen {
+    /// A screen that only collects output, whatever stdout is, so views
+    /// can be compared as text.
+    pub fn plain() -> Self {
+        Screen {
+            buffer: String::new(),
+            clear_pending: false,
+            interactive: false,
+            clears: false,
+            colors: false,
+            width: None,
+        }
+    }
+
+    pub fn contents(&self) -> &str {
+        &self.buffer
+    }
+}
@@ -0,0 +1,163 @@
+//! Finding items by name. A query matches names that contain it, ignoring
+//! case; fuzzy matching also accepts its letters spread out in order, so
+//! `bmlk` finds "buy milk", with the tightest matches first.
+
+use crate::app::record;
+use crate::history::Operation;
+use crate::prompt::Prompt;
+use crate::screen::Screen;
+use crate::{trash, Todos};
+
+/// The indices of the items matching `query`, best match first.
+pub fn find(todos: &Todos, query: &str, fuzzy: bool) -> Vec<usize> {
+    let query = query.to_lowercase();
+    let mut matches: Vec<(usize, usize)> = todos
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| {
+            let name = item.name.to_lowercase();
+            match fuzzy {
+                false => name.contains(&query).then_some((0, i)),
+                true => spread(&name, &query).map(|spread| (spread, i)),
+            }
+        })
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, i)| i).collect()
+}
+
+/// How many other characters lie between the first and last letter of
+/// `query` when its letters are found in order in `name`, or `None` if
+/// they aren't all there.
+fn spread(name: &str, query: &str) -> Option<usize> {
+    let positions = positions(name, query, true)?;
+    let (first, last) = (positions.first().copied(), positions.last().copied());
+    Some((last.unwrap_or(0) + 1).saturating_sub(first.unwrap_or(0) + positions.len()))
+}
+
+/// Where in `name`, counting characters, the letters matching `query` are:
+/// where it's contained, or with `fuzzy` where its letters are found in
+/// order. Both are already lowercase.
+fn positions(name: &str, query: &str, fuzzy: bool) -> Option<Vec<usize>> {
+    if let Some(start) = name.find(query) {
+        let start = name[..start].chars().count();
+        return Some((start..start + query.chars().count()).collect());
+    }
+    if !fuzzy {
+        return None;
+    }
+
+    let mut letters = query.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut positions = Vec::new();
+    for (position, c) in name.chars().enumerate() {
+        if letters.peek() == Some(&c) {
+            letters.next();
+            positions.push(position);
+        }
+    }
+    match letters.peek() {
+        Some(_) => None,
+        None => Some(positions),
+    }
+}
+
+/// Where in `name` the letters `find` matched `query` by are, to highlight
+/// them.
+#[cfg(feature = "tui")]
+pub fn matched(name: &str, query: &str, fuzzy: bool) -> Vec<usize> {
+    positions(&name.to_lowercase(), &query.to_lowercase(), fuzzy).unwrap_or_default()
+}
+
+pub fn print_matches(screen: &mut Screen, todos: &Todos, matches: &[usize]) {
+    if matches.is_empty() {
+        writeln!(screen, "[No matches]");
+    }
+    for &i in matches {
+        writeln!(screen, "{} {}", i, todos[i]);
+    }
+    writeln!(screen);
+}
+
+enum Pick {
+    Check(usize),
+    Remove(usize),
+}
+
+/// Asks for a query, shows what matches, and offers to check or remove one
+/// of the results.
+pub fn search_todo(todos: &mut Todos, screen: &mut Screen) {
+    let query = match Prompt::text("Search for? (add ~ in front for fuzzy)").ask(screen) {
+        Some(query) => query,
+        None => return,
+    };
+    let matches = match query.strip_prefix('~') {
+        Some(query) => find(todos, query, true),
+        None => find(todos, &query, false),
+    };
+
+    screen.clear();
+    print_matches(screen, todos, &matches);
+    if matches.is_empty() {
+        return;
+    }
+
+    let pick = Prompt::new(
+        "(c)heck or (r)emove a result, e.g. c 3 (empty to leave)",
+        |line| {
+            let (action, index) = line.split_once(' ').ok_or("Answer e.g. c 3 or r 3")?;
+            let index: usize = index.trim().parse().map_err(|_| "Invalid input")?;
+            match action {
+                "c" | "check" => Ok(Some(Pick::Check(index))),
+                "r" | "remove" => Ok(Some(Pick::Remove(index))),
+                _ => Err("Answer e.g. c 3 or r 3".to_string()),
+            }
+        },
+    )
+    .validate(|pick| match pick {
+        Some(Pick::Check(index) | Pick::Remove(index)) if !matches.contains(index) => {
+            Err(format!("{} isn't one of the results", index))
+        }
+        _ => Ok(()),
+    })
+    .default(None)
+    .ask(screen)
+    .flatten();
+
+    screen.clear();
+    match pick {
+        Some(Pick::Check(index)) => record(todos, Operation::toggle(todos, index), screen),
+        Some(Pick::Remove(index)) => {
+            if let Err(e) = trash::remove(todos, &[index]) {
+                writeln!(screen, "{}\n", e);
+            }
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn substrings_ignore_case() {
+        assert_eq!(find(&sample(), "REPORT", false), vec![0]);
+        assert_eq!(find(&sample(), "bmlk", false), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn tightest_fuzzy_matches_come_first() {
+        // "book lunch" spreads the letters less than "buy milk".
+        assert_eq!(find(&sample(), "bl", true), vec![4, 1]);
+        assert_eq!(find(&sample(), "bmlk", true), vec![1]);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn matched_letters() {
+        assert_eq!(matched("Buy milk", "MIL", false), [4, 5, 6]);
+        assert_eq!(matched("buy milk", "bmlk", true), [0, 4, 6, 7]);
+        assert_eq!(matched("buy milk", "bmlk", false), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,156 @@
+//! `todo secret set <name>`: passwords and tokens kept in the system's
+//! keyring rather than in plain text next to the list or in the config.
+//!
+//! On macOS they go in the login keychain, through `security`. Elsewhere
+//! they go to whatever provides the Secret Service, like GNOME Keyring or
+//! KWallet, through `secret-tool` from libsecret. Either way they're stored
+//! under the service `todo-rs`, with the secret's name as the account.
+//!
+//! The secrets used are:
+//!
+//! - `http-token`: sent as a bearer token by `todo sync --http`.
+//! - any named by a Slack or ntfy channel in `[notify]`: the webhook's URL,
+//!   or the topic.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use clap::Subcommand;
+
+use crate::screen::Screen;
+
+const SERVICE: &str = "todo-rs";
+
+/// The secret `todo sync --http` authenticates with.
+pub const HTTP_TOKEN: &str = "http-token";
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretCommand {
+    /// Store a secret, typed at the prompt or read from stdin
+    Set { name: String },
+    /// Remove a stored secret
+    Remove { name: String },
+}
+
+#[cfg(target_os = "macos")]
+const PROGRAM: &str = "security";
+#[cfg(not(target_os = "macos"))]
+const PROGRAM: &str = "secret-tool";
+
+/// Runs the keyring's tool with `arguments`, giving it `input`, and returns
+/// whether it succeeded along with what it printed.
+fn keyring(arguments: &[&str], input: &str) -> std::io::Result<(bool, String)> {
+    let mut child = Command::new(PROGRAM)
+        .args(arguments)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    Ok((output.status.success(), stdout))
+}
+
+fn describe(error: std::io::Error) -> String {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => format!(
+            "Could not run {}, which keeps secrets in the keyring: is it installed?",
+            PROGRAM
+        ),
+        _ => format!("Could not run {}: {}", PROGRAM, error),
+    }
+}
+
+/// Quotes `word` for a command given to `security -i`.
+#[cfg(target_os = "macos")]
+fn quote(word: &str) -> String {
+    format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The secret stored as `name`, if there is one. Without a keyring there
+/// are no secrets, rather than an error for everything that might use one.
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    #[cfg(target_os = "macos")]
+    let arguments = ["find-generic-password", "-s", SERVICE, "-a", name, "-w"];
+    #[cfg(not(target_os = "macos"))]
+    let arguments = ["lookup", "service", SERVICE, "name", name];
+
+    match keyring(&arguments, "") {
+        Ok((true, secret)) => Ok(Some(secret.trim_end_matches('\n').to_string())),
+        Ok((false, _)) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(describe(e)),
+    }
+}
+
+/// Stores `secret` as `name`, replacing what was stored before. It's given
+/// to the keyring's tool on stdin, where other users can't see it.
+fn set(name: &str, secret: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let stored = keyring(
+        &["-i"],
+        &format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            SERVICE,
+            quote(name),
+            quote(secret)
+        ),
+    );
+    #[cfg(not(target_os = "macos"))]
+    let stored = keyring(
+        &[
+            "store",
+            "--label",
+            &format!("{} {}", SERVICE, name),
+            "service",
+            SERVICE,
+            "name",
+            name,
+        ],
+        secret,
+    );
+
+    match stored.map_err(describe)? {
+        (true, _) => Ok(()),
+        (false, _) => Err(format!("The keyring didn't take {}", name)),
+    }
+}
+
+fn remove(name: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let arguments = ["delete-generic-password", "-s", SERVICE, "-a", name];
+    #[cfg(not(target_os = "macos"))]
+    let arguments = ["clear", "service", SERVICE, "name", name];
+
+    // secret-tool is happy to clear what isn't there, so check first.
+    if get(name)?.is_none() {
+        return Err(format!("There's no secret called {}", name));
+    }
+    match keyring(&arguments, "").map_err(describe)? {
+        (true, _) => Ok(()),
+        (false, _) => Err(format!("The keyring didn't remove {}", name)),
+    }
+}
+
+pub fn run(screen: &mut Screen, command: SecretCommand) -> Result<(), String> {
+    match command {
+        SecretCommand::Set { name } => {
+            if screen.is_interactive() {
+                write!(screen, "{}: ", name);
+            }
+            let secret = screen.read_hidden();
+            if secret.is_empty() {
+                return Err(format!("{} wasn't stored, as it's empty", name));
+            }
+            set(&name, &secret)?;
+            writeln!(screen, "Stored {} in the keyring", name);
+        }
+        SecretCommand::Remove { name } => {
+            remove(&name)?;
+            writeln!(screen, "Removed {} from the keyring", name);
+        }
+    }
+    screen.flush();
+    Ok(())
+}
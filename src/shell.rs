@@ -0,0 +1,208 @@
+//! First-run setup for people installing a prebuilt binary: shell
+//! completions, the config directory and an optional prompt hook.
+
+use std::path::{Path, PathBuf};
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::app::Cli;
+use crate::Todos;
+
+const APP_DIR: &str = "todo-rs";
+
+/// Mark the lines added to a shell's startup file, so running
+/// `init-shell` again doesn't add them twice and they can be taken out.
+const HOOK_START: &str = "# added by todo init-shell";
+const HOOK_END: &str = "# end of todo init-shell";
+
+pub fn init(shell: Option<Shell>, prompt_hook: bool, remove_hook: bool) -> Result<(), String> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => detect_shell()?,
+    };
+    let home = dirs::home_dir().ok_or("Couldn't find the home directory")?;
+    if remove_hook {
+        return remove_prompt_hook(shell, &home);
+    }
+
+    let completions = completions_path(shell, &home)?;
+    create_parent(&completions)?;
+    let mut file = std::fs::File::create(&completions)
+        .map_err(|e| format!("Could not write {}: {}", completions.display(), e))?;
+    clap_complete::generate(shell, &mut Cli::command(), "todo", &mut file);
+    println!("Installed completions in {}", completions.display());
+    if shell == Shell::Zsh {
+        println!(
+            "  (make sure {} is in $fpath)",
+            completions.parent().unwrap().display()
+        );
+    }
+
+    let config = dirs::config_dir()
+        .ok_or("Couldn't find a config directory")?
+        .join(APP_DIR);
+    std::fs::create_dir_all(&config)
+        .map_err(|e| format!("Could not create {}: {}", config.display(), e))?;
+    println!("Created {}", config.display());
+
+    if prompt_hook {
+        add_prompt_hook(shell, &home)?;
+    }
+
+    Ok(())
+}
+
+/// The shell's name from `$SHELL`, e.g. `/bin/zsh`.
+fn detect_shell() -> Result<Shell, String> {
+    let path = std::env::var("SHELL").unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    name.parse::<Shell>()
+        .map_err(|_| "Couldn't tell which shell this is, pass --shell".to_string())
+}
+
+/// Where each shell picks up completions for a single user without
+/// further setup.
+fn completions_path(shell: Shell, home: &Path) -> Result<PathBuf, String> {
+    let data = dirs::data_dir().ok_or("Couldn't find a data directory")?;
+    let config = dirs::config_dir().ok_or("Couldn't find a config directory")?;
+
+    match shell {
+        Shell::Bash => Ok(data.join("bash-completion/completions/todo")),
+        Shell::Zsh => Ok(home.join(".zfunc/_todo")),
+        Shell::Fish => Ok(config.join("fish/completions/todo.fish")),
+        Shell::Elvish => Ok(config.join("elvish/lib/todo.elv")),
+        Shell::PowerShell => Ok(config.join("powershell/todo.ps1")),
+        _ => Err(format!("No completions for {}", shell)),
+    }
+}
+
+/// The startup file the prompt hook goes in for `shell`, and the hook.
+fn prompt_hook(shell: Shell, home: &Path) -> Result<(PathBuf, &'static str), String> {
+    Ok(match shell {
+        Shell::Bash => (home.join(".bashrc"), r#"PS1='$(todo prompt)'"$PS1""#),
+        Shell::Zsh => (
+            home.join(".zshrc"),
+            "setopt PROMPT_SUBST\nPROMPT='$(todo prompt)'\"$PROMPT\"",
+        ),
+        Shell::Fish => (
+            dirs::config_dir()
+                .ok_or("Couldn't find a config directory")?
+                .join("fish/conf.d/todo.fish"),
+            "functions -c fish_prompt __todo_fish_prompt\nfunction fish_prompt\n    todo prompt\n    __todo_fish_prompt\nend",
+        ),
+        _ => return Err(format!("No prompt hook for {}", shell)),
+    })
+}
+
+/// `content` with `hook` added at the end, or `None` if it has it already.
+fn with_hook(content: &str, hook: &str) -> Option<String> {
+    if content.contains(HOOK_START) {
+        return None;
+    }
+    Some(format!(
+        "{}\n{}\n{}\n{}\n",
+        content, HOOK_START, hook, HOOK_END
+    ))
+}
+
+/// `content` without the hook `with_hook` added, or `None` if it has none.
+fn without_hook(content: &str) -> Option<String> {
+    let start = content.find(&format!("\n{}\n", HOOK_START))?;
+    let end = content[start..].find(&format!("{}\n", HOOK_END))? + start;
+    Some(format!(
+        "{}{}",
+        &content[..start],
+        &content[end + HOOK_END.len() + 1..]
+    ))
+}
+
+/// Shows the number of open items in front of the shell prompt.
+fn add_prompt_hook(shell: Shell, home: &Path) -> Result<(), String> {
+    let (rc, hook) = prompt_hook(shell, home)?;
+    let existing = std::fs::read_to_string(&rc).unwrap_or_default();
+    let Some(content) = with_hook(&existing, hook) else {
+        println!("The prompt hook is already in {}", rc.display());
+        return Ok(());
+    };
+
+    create_parent(&rc)?;
+    std::fs::write(&rc, content)
+        .map_err(|e| format!("Could not update {}: {}", rc.display(), e))?;
+    println!("Added the prompt hook to {}", rc.display());
+    Ok(())
+}
+
+/// Takes out what `add_prompt_hook` added, leaving the rest of the file.
+fn remove_prompt_hook(shell: Shell, home: &Path) -> Result<(), String> {
+    let (rc, _) = prompt_hook(shell, home)?;
+    let existing = std::fs::read_to_string(&rc).unwrap_or_default();
+    let Some(content) = without_hook(&existing) else {
+        println!("There's no prompt hook in {}", rc.display());
+        return Ok(());
+    };
+
+    std::fs::write(&rc, content)
+        .map_err(|e| format!("Could not update {}: {}", rc.display(), e))?;
+    println!("Removed the prompt hook from {}", rc.display());
+    Ok(())
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+    let dir = path.parent().unwrap();
+    std::fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))
+}
+
+/// What the prompt hook shows: the number of open items, or nothing when
+/// there are none.
+pub fn print_prompt(todos: &Todos) {
+    let open = todos.iter().filter(|item| !item.completed).count();
+    if open > 0 {
+        print!("[{}] ", open);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_hook_goes_in_once_and_comes_out_cleanly() {
+        let rc = "export EDITOR=vi\nalias ll='ls -l'\n";
+        let (_, hook) = prompt_hook(Shell::Zsh, Path::new("/home/me")).unwrap();
+
+        let installed = with_hook(rc, hook).unwrap();
+        assert!(installed.starts_with(rc) && installed.contains(hook));
+        assert_eq!(with_hook(&installed, hook), None);
+        assert_eq!(installed.matches(HOOK_START).count(), 1);
+        assert_eq!(installed.matches(HOOK_END).count(), 1);
+
+        let edited = format!("{}export PATH=~/bin:$PATH\n", installed);
+        assert_eq!(
+            without_hook(&edited).unwrap(),
+            format!("{}export PATH=~/bin:$PATH\n", rc)
+        );
+        assert_eq!(without_hook(&installed).unwrap(), rc);
+        assert_eq!(without_hook(rc), None);
+    }
+
+    #[test]
+    fn installing_twice_leaves_one_hook() {
+        let home = std::env::temp_dir().join(format!("todo-home-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&home);
+        std::fs::create_dir_all(&home).unwrap();
+        let rc = home.join(".bashrc");
+        std::fs::write(&rc, "export EDITOR=vi\n").unwrap();
+
+        add_prompt_hook(Shell::Bash, &home).unwrap();
+        add_prompt_hook(Shell::Bash, &home).unwrap();
+        let installed = std::fs::read_to_string(&rc).unwrap();
+        assert_eq!(installed.matches("todo prompt").count(), 1);
+
+        remove_prompt_hook(Shell::Bash, &home).unwrap();
+        assert_eq!(std::fs::read_to_string(&rc).unwrap(), "export EDITOR=vi\n");
+        remove_prompt_hook(Shell::Bash, &home).unwrap();
+
+        std::fs::remove_dir_all(&home).unwrap();
+    }
+}
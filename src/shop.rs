@@ -0,0 +1,169 @@
+//! Shopping lists: items with a quantity, like `--qty "2 kg"`, are things
+//! to buy. `todo shop` shows the open ones grouped by the store they're
+//! tagged with and checks them off one after another while counting down
+//! what's left.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::record;
+use crate::history::Operation;
+use crate::prompt::Prompt;
+use crate::screen::Screen;
+use crate::{write_to_file, Todos};
+
+/// How much of something to get, with an optional unit: `2`, `1.5 l` or
+/// `3 cans`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Quantity {
+    amount: f64,
+    unit: Option<String>,
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (amount, unit) = s.split_at(split);
+
+        let amount: f64 = amount
+            .parse()
+            .ok()
+            .filter(|amount: &f64| *amount > 0.0)
+            .ok_or_else(|| format!("{:?} isn't a quantity, try e.g. 2 or \"500 g\"", s))?;
+        let unit = Some(unit.trim().to_string()).filter(|unit| !unit.is_empty());
+
+        Ok(Quantity { amount, unit })
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.amount)?;
+        if let Some(unit) = &self.unit {
+            write!(f, " {}", unit)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Quantity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Quantity> for String {
+    fn from(quantity: Quantity) -> Self {
+        quantity.to_string()
+    }
+}
+
+/// Open items to buy, grouped by their first tag, which names the store.
+fn by_store(todos: &Todos) -> BTreeMap<Option<&str>, Vec<usize>> {
+    let mut stores: BTreeMap<Option<&str>, Vec<usize>> = BTreeMap::new();
+    for (i, item) in todos.iter().enumerate() {
+        if !item.completed && item.quantity.is_some() {
+            let store = item.tags.first().map(String::as_str);
+            stores.entry(store).or_default().push(i);
+        }
+    }
+    stores
+}
+
+pub fn print_shopping(screen: &mut Screen, todos: &Todos) {
+    let stores = by_store(todos);
+    if stores.is_empty() {
+        writeln!(
+            screen,
+            "[Nothing to buy, add items with --qty to put them on the shopping list]\n"
+        );
+        return;
+    }
+
+    for (store, indices) in &stores {
+        match store {
+            Some(store) => writeln!(screen, "+{}", store),
+            None => writeln!(screen, "anywhere"),
+        }
+        for &i in indices {
+            writeln!(screen, "  {} {}", i, todos[i]);
+        }
+    }
+    let left: usize = stores.values().map(Vec::len).sum();
+    writeln!(screen, "\n{} left\n", left);
+}
+
+/// Shows the shopping list and checks off items as they're named, saving
+/// after each so closing the terminal in the shop loses nothing.
+pub fn shop(screen: &mut Screen, todos: &mut Todos) -> Result<(), String> {
+    loop {
+        print_shopping(screen, todos);
+        let left: Vec<usize> = by_store(todos).into_values().flatten().collect();
+        if left.is_empty() {
+            return Ok(());
+        }
+
+        let index = Prompt::new("Got which? (index, empty to stop)", |line| {
+            line.parse::<usize>()
+                .map(Some)
+                .map_err(|_| "Invalid input".to_string())
+        })
+        .validate(|index| match index {
+            Some(index) if !left.contains(index) => {
+                Err(format!("{} isn't on the shopping list", index))
+            }
+            _ => Ok(()),
+        })
+        .default(None)
+        .ask(screen)
+        .flatten();
+
+        match index {
+            Some(index) => {
+                record(todos, Operation::toggle(todos, index), screen);
+                write_to_file(todos)?;
+                screen.clear();
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn quantities_parse_back() {
+        for text in ["2", "1.5 l", "500 g", "3 cans"] {
+            assert_eq!(text.parse::<Quantity>().unwrap().to_string(), text);
+        }
+        assert_eq!("500g".parse::<Quantity>().unwrap().to_string(), "500 g");
+        assert!("some".parse::<Quantity>().is_err());
+        assert!("0".parse::<Quantity>().is_err());
+    }
+
+    #[test]
+    fn shopping() {
+        let mut todos = sample();
+        todos[1].quantity = "2 l".parse().ok();
+        todos[2].quantity = "1".parse().ok();
+        todos[3].quantity = "4".parse().ok();
+
+        let mut screen = Screen::plain();
+        print_shopping(&mut screen, &todos);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,131 @@
+//! `todo show <index>`: everything known about one item, with its notes,
+//! where the list only has room for the name.
+
+use chrono::{DateTime, Local};
+
+use crate::screen::Screen;
+use crate::{date, relative, subtasks, Todos};
+
+fn format_time(time: &DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// What `todo show` says about the item at `index`, label by label.
+pub fn fields(todos: &Todos, index: usize, now: DateTime<Local>) -> Vec<(&'static str, String)> {
+    let item = &todos[index];
+    let mut fields = Vec::new();
+
+    let status = match (item.completed, item.cancelled, item.completed_at) {
+        (_, true, _) => "cancelled".to_string(),
+        (true, _, Some(at)) => format!("completed {}", format_time(&at)),
+        (true, _, None) => "completed".to_string(),
+        (false, ..) if item.someday.is_some() => "someday".to_string(),
+        (false, ..) => "open".to_string(),
+    };
+    fields.push(("Status", status));
+    if let Some(parent) = subtasks::parent(todos, index) {
+        fields.push(("Under", format!("{} {}", parent, todos[parent].name)));
+    }
+    let children = subtasks::children(todos, index);
+    if !children.is_empty() {
+        let done = children.clone().filter(|&i| todos[i].completed).count();
+        fields.push(("Subtasks", format!("{} of {} done", done, children.len())));
+    }
+    if let Some(due) = item.due {
+        let overdue = match !item.completed && due < now {
+            _ if item.soft => " (soft)",
+            true => " (overdue)",
+            false => "",
+        };
+        let relative = relative::describe(due, now);
+        fields.push((
+            "Due",
+            format!("{}, {}{}", date::show_due(&due), relative, overdue),
+        ));
+    }
+    if let Some(expires) = item.expires {
+        fields.push(("Expires", date::show_due(&expires)));
+    }
+    if let Some(starts_on) = item.starts_on {
+        fields.push(("Starts", starts_on.to_string()));
+    }
+    if let Some(recurrence) = &item.recurrence {
+        fields.push(("Repeats", recurrence.to_string()));
+    }
+    if let Some(priority) = item.priority {
+        fields.push(("Priority", priority.to_string()));
+    }
+    if let Some(energy) = item.energy {
+        fields.push(("Energy", energy.to_string()));
+    }
+    if let Some(quantity) = &item.quantity {
+        fields.push(("Buy", quantity.to_string()));
+    }
+    if !item.tags.is_empty() {
+        let tags: Vec<String> = item.tags.iter().map(|tag| format!("+{}", tag)).collect();
+        fields.push(("Tags", tags.join(" ")));
+    }
+    if !item.contacts.is_empty() {
+        let people: Vec<String> = item.contacts.iter().map(|p| format!("@{}", p)).collect();
+        fields.push(("People", people.join(" ")));
+    }
+    if let Some(person) = &item.waiting_on {
+        let since = match item.waiting_since {
+            Some(since) => format!(" since {}", since),
+            None => String::new(),
+        };
+        fields.push(("Waiting on", format!("{}{}", person, since)));
+    }
+    match item.postponed.len() {
+        0 => {}
+        1 => fields.push(("Put off", "once".to_string())),
+        times => fields.push(("Put off", format!("{} times", times))),
+    }
+    if item.my_day == Some(now.date_naive()) {
+        fields.push(("Today", "picked for today".to_string()));
+    }
+    if let Some(created) = item.created {
+        fields.push(("Created", format_time(&created)));
+    }
+    fields
+}
+
+pub fn print_item(screen: &mut Screen, todos: &Todos, index: usize, now: DateTime<Local>) {
+    let item = &todos[index];
+    writeln!(screen, "{} {}\n", index, item.name);
+    for (label, value) in fields(todos, index, now) {
+        writeln!(screen, "  {:<11}{}", label, value);
+    }
+    if let Some(notes) = &item.notes {
+        writeln!(screen, "\nNotes\n");
+        for line in notes.lines() {
+            writeln!(screen, "  {}", line);
+        }
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn show() {
+        let now = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+        let mut todos = sample();
+        for item in &mut todos {
+            item.created = Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).single();
+        }
+        todos[0].notes =
+            Some("numbers are in the shared drive\nhttps://example.com/q2".to_string());
+        todos[1].subtask = true;
+
+        let mut screen = Screen::plain();
+        print_item(&mut screen, &todos, 0, now);
+        print_item(&mut screen, &todos, 4, now);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,116 @@
+//! The someday/maybe list: ideas that aren't commitments yet. They stay
+//! out of the default views and come back a few at a time in
+//! `todo review`, least recently reviewed first, to be kept for later,
+//! made active or dropped.
+
+use chrono::NaiveDate;
+
+use crate::app::record;
+use crate::history::{self, Operation};
+use crate::prompt::Prompt;
+use crate::screen::Screen;
+use crate::{check_index, trash, Item, Todos};
+
+pub fn is_someday(item: &Item) -> bool {
+    item.someday.is_some()
+}
+
+/// Moves an item to the someday list, or back to the active list if it's
+/// already there.
+pub fn toggle(todos: &mut Todos, index: usize, today: NaiveDate) -> Result<String, String> {
+    check_index(todos, index)?;
+
+    let mut item = todos[index].clone();
+    item.someday = match item.someday {
+        Some(_) => None,
+        None => Some(today),
+    };
+    let message = match item.someday {
+        Some(_) => format!("{} is on the someday list", item.name),
+        None => format!("{} is active again", item.name),
+    };
+    history::perform(todos, Operation::replace(todos, index, item))?;
+
+    Ok(message)
+}
+
+/// The open someday items to bring up next, least recently reviewed first.
+fn resurfacing(todos: &Todos, count: usize) -> Vec<usize> {
+    let mut picks: Vec<usize> = (0..todos.len())
+        .filter(|&i| !todos[i].completed && is_someday(&todos[i]))
+        .collect();
+    picks.sort_by_key(|&i| todos[i].someday);
+    picks.truncate(count);
+    picks
+}
+
+enum Decision {
+    Keep,
+    Activate,
+    Remove,
+}
+
+/// Goes through up to `count` someday items asking what to do with each.
+/// Kept items go to the back of the queue for the next review.
+pub fn review(screen: &mut Screen, todos: &mut Todos, count: usize, today: NaiveDate) {
+    let picks = resurfacing(todos, count);
+    if picks.is_empty() {
+        writeln!(screen, "[Nothing on the someday list]\n");
+        return;
+    }
+
+    let mut removed = Vec::new();
+    for index in picks {
+        writeln!(screen, "{} {}", index, todos[index]);
+        let decision = Prompt::new(
+            "(k)eep for someday, (a)ctivate or (r)emove?",
+            |line| match line.to_lowercase().as_str() {
+                "k" | "keep" => Ok(Decision::Keep),
+                "a" | "activate" => Ok(Decision::Activate),
+                "r" | "remove" => Ok(Decision::Remove),
+                _ => Err("Answer k, a or r".to_string()),
+            },
+        )
+        .default(Decision::Keep)
+        .ask(screen);
+
+        let mut item = todos[index].clone();
+        match decision {
+            None => break,
+            Some(Decision::Keep) => item.someday = Some(today),
+            Some(Decision::Activate) => item.someday = None,
+            Some(Decision::Remove) => {
+                removed.push(index);
+                continue;
+            }
+        }
+        let operation = Operation::replace(todos, index, item);
+        record(todos, operation, screen);
+    }
+
+    removed.sort_unstable();
+    if let Err(e) = trash::remove(todos, &removed) {
+        writeln!(screen, "{}\n", e);
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn least_recently_reviewed_resurface_first() {
+        let mut todos = sample();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 5, d);
+        todos[0].someday = date(6);
+        todos[1].someday = date(1);
+        todos[3].someday = date(2);
+        todos[4].someday = date(3);
+
+        // The completed one (3) isn't brought up again.
+        assert_eq!(resurfacing(&todos, 2), vec![1, 4]);
+        assert_eq!(resurfacing(&todos, 5), vec![1, 4, 0]);
+    }
+}
@@ -0,0 +1,228 @@
+//! Lists kept in SQLite instead of a JSON file, for lists long enough that
+//! rewriting a whole file on every change gets slow. Built with the
+//! `sqlite` feature and picked with `--backend sqlite` or
+//! `TODO_BACKEND=sqlite`; `todo migrate sqlite` copies the JSON list over.
+//!
+//! Each item is a row holding the item as JSON, so every field, including
+//! ones added by other tools, is kept as it is in todo.json.
+//!
+//! Every version of every item is kept as well, with when it was current,
+//! so `todo asof 2024-05-01` can show the list as it was on a past day.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, Transaction};
+
+use crate::{sync, Item, TodoStore, Todos};
+
+/// A list kept in an SQLite database, like todo.db.
+pub struct SqliteStore {
+    path: PathBuf,
+}
+
+impl SqliteStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        SqliteStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn connect(&self) -> Result<Connection, String> {
+        let connection = Connection::open(&self.path)
+            .map_err(|e| format!("Could not open {}: {}", self.path.display(), e))?;
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS items (
+                    position INTEGER PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    completed INTEGER NOT NULL,
+                    item TEXT NOT NULL
+                )",
+                [],
+            )
+            .and_then(|_| {
+                connection.execute(
+                    "CREATE TABLE IF NOT EXISTS versions (
+                        key TEXT NOT NULL,
+                        position INTEGER NOT NULL,
+                        item TEXT NOT NULL,
+                        valid_from INTEGER NOT NULL,
+                        valid_to INTEGER
+                    )",
+                    [],
+                )
+            })
+            .map_err(|e| format!("Could not set up {}: {}", self.path.display(), e))?;
+        Ok(connection)
+    }
+
+    /// The list as it was at `time`.
+    pub fn as_of(&self, time: DateTime<Local>) -> Result<Todos, String> {
+        let error = |e: rusqlite::Error| format!("Could not read {}: {}", self.path.display(), e);
+        let connection = self.connect()?;
+        let mut statement = connection
+            .prepare(
+                "SELECT item FROM versions
+                WHERE valid_from <= ?1 AND (valid_to IS NULL OR valid_to > ?1)
+                ORDER BY position",
+            )
+            .map_err(error)?;
+        let rows = statement
+            .query_map([time.timestamp()], |row| row.get::<_, String>(0))
+            .map_err(error)?;
+
+        rows.map(|json| {
+            let json = json.map_err(error)?;
+            serde_json::from_str::<Item>(&json)
+                .map_err(|e| format!("{} is damaged: {}", self.path.display(), e))
+        })
+        .collect()
+    }
+}
+
+/// Ends the versions of items that changed, moved or went away, and starts
+/// new ones for the list as it is now.
+fn record_versions(transaction: &Transaction, todos: &Todos) -> rusqlite::Result<()> {
+    let now = Local::now().timestamp();
+    let mut current: HashMap<String, (i64, i64, String)> = HashMap::new();
+    {
+        let mut statement = transaction
+            .prepare("SELECT rowid, key, position, item FROM versions WHERE valid_to IS NULL")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get(1)?, (row.get(0)?, row.get(2)?, row.get(3)?)))
+        })?;
+        for row in rows {
+            let (key, version) = row?;
+            current.insert(key, version);
+        }
+    }
+
+    // Items are told apart as `todo sync` does, and by how many came before
+    // with the same key.
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (position, item) in todos.iter().enumerate() {
+        let id = sync::key(item);
+        let count = seen.entry(id.clone()).or_default();
+        let key = format!("{}#{}", id, count);
+        *count += 1;
+
+        let json = serde_json::to_string(item).unwrap();
+        match current.remove(&key) {
+            Some((_, old_position, old_json))
+                if old_position == position as i64 && old_json == json => {}
+            old => {
+                if let Some((rowid, _, _)) = old {
+                    transaction.execute(
+                        "UPDATE versions SET valid_to = ?1 WHERE rowid = ?2",
+                        params![now, rowid],
+                    )?;
+                }
+                transaction.execute(
+                    "INSERT INTO versions (key, position, item, valid_from) VALUES (?1, ?2, ?3, ?4)",
+                    params![key, position as i64, json, now],
+                )?;
+            }
+        }
+    }
+    for (rowid, _, _) in current.into_values() {
+        transaction.execute(
+            "UPDATE versions SET valid_to = ?1 WHERE rowid = ?2",
+            params![now, rowid],
+        )?;
+    }
+    Ok(())
+}
+
+impl TodoStore for SqliteStore {
+    fn load(&self) -> Result<Todos, String> {
+        let error = |e: rusqlite::Error| format!("Could not read {}: {}", self.path.display(), e);
+        let connection = self.connect()?;
+        let mut statement = connection
+            .prepare("SELECT item FROM items ORDER BY position")
+            .map_err(error)?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(error)?;
+
+        rows.map(|json| {
+            let json = json.map_err(error)?;
+            serde_json::from_str::<Item>(&json)
+                .map_err(|e| format!("{} is damaged: {}", self.path.display(), e))
+        })
+        .collect()
+    }
+
+    /// Replaces every row in one transaction, so the list is either saved
+    /// whole or not at all, and records what changed.
+    fn save(&self, todos: &Todos) -> Result<(), String> {
+        let error = |e: rusqlite::Error| format!("Could not save {}: {}", self.path.display(), e);
+        let mut connection = self.connect()?;
+        let transaction = connection.transaction().map_err(error)?;
+        transaction
+            .execute("DELETE FROM items", [])
+            .map_err(error)?;
+        for (position, item) in todos.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO items (position, name, completed, item) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        position as i64,
+                        item.name,
+                        item.completed,
+                        serde_json::to_string(item).unwrap()
+                    ],
+                )
+                .map_err(error)?;
+        }
+        record_versions(&transaction, todos).map_err(error)?;
+        transaction.commit().map_err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn sqlite_store_keeps_items() {
+        let dir = std::env::temp_dir().join(format!("todo-sqlite-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = SqliteStore::new(dir.join("todo.db"));
+
+        assert_eq!(store.load(), Ok(Vec::new()));
+        let todos = sample();
+        store.save(&todos).unwrap();
+        assert_eq!(store.load(), Ok(todos));
+        assert_eq!(store.add(Item::new("more".to_string())), Ok(5));
+        assert_eq!(store.check(5), Ok(true));
+        assert_eq!(store.load().unwrap().len(), 6);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn earlier_versions_are_kept() {
+        let dir = std::env::temp_dir().join(format!("todo-sqlite-asof-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = SqliteStore::new(dir.join("todo.db"));
+        let before = Local::now() - chrono::Duration::seconds(1);
+
+        let todos = sample();
+        store.save(&todos).unwrap();
+        let saved = Local::now();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        store.check(0).unwrap();
+        store.remove(1).unwrap();
+
+        assert_eq!(store.as_of(before), Ok(Vec::new()));
+        assert_eq!(store.as_of(saved), Ok(todos));
+        assert_eq!(store.as_of(Local::now()), store.load());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+//! `todo stats`: how much is open and done, how many items were completed
+//! each of the last few days and weeks, how long they took, and a
+//! burn-down of the open items over the last two weeks.
+//!
+//! Archived items count towards what was completed. Items checked off
+//! before completion times were kept only count towards the totals.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+
+use crate::archive::Done;
+use crate::screen::Screen;
+use crate::Todos;
+
+const DAYS: i64 = 7;
+const WEEKS: i64 = 4;
+const BURN_DOWN_DAYS: i64 = 14;
+/// The longest bar drawn, the others are scaled to it.
+const BAR_WIDTH: usize = 40;
+
+/// When an item on the list or in the archive was made and finished.
+struct Span {
+    created: Option<DateTime<Local>>,
+    completed: Option<DateTime<Local>>,
+    closed: bool,
+}
+
+fn spans(todos: &Todos, done: &[Done]) -> Vec<Span> {
+    let listed = todos.iter().map(|item| Span {
+        created: item.created,
+        completed: item
+            .completed_at
+            .filter(|_| item.completed && !item.cancelled),
+        closed: item.completed,
+    });
+    let archived = done.iter().map(|done| Span {
+        created: done.item.created,
+        // Archived before completion times were kept, so when it was
+        // archived is as close as there is.
+        completed: Some(done.item.completed_at.unwrap_or(done.completed))
+            .filter(|_| !done.item.cancelled),
+        closed: true,
+    });
+    listed.chain(archived).collect()
+}
+
+/// How many of `spans` were completed on each day from `from` to `to`.
+fn completions(spans: &[Span], from: NaiveDate, to: NaiveDate) -> usize {
+    spans
+        .iter()
+        .filter_map(|span| span.completed)
+        .filter(|completed| (from..=to).contains(&completed.date_naive()))
+        .count()
+}
+
+/// How many of `spans` were open at the end of `day`.
+fn open_on(spans: &[Span], day: NaiveDate) -> usize {
+    spans
+        .iter()
+        .filter(|span| {
+            span.created
+                .is_none_or(|created| created.date_naive() <= day)
+        })
+        .filter(|span| match span.completed {
+            Some(completed) => completed.date_naive() > day,
+            // Closed at some unknown time, so left out throughout.
+            None => !span.closed,
+        })
+        .count()
+}
+
+fn average_time(spans: &[Span]) -> Option<(Duration, usize)> {
+    let times: Vec<Duration> = spans
+        .iter()
+        .filter_map(|span| Some(span.completed? - span.created?))
+        .filter(|time| *time >= Duration::zero())
+        .collect();
+    match times.len() {
+        0 => None,
+        count => Some((times.iter().sum::<Duration>() / count as i32, count)),
+    }
+}
+
+fn format_duration(time: Duration) -> String {
+    match (
+        time.num_days(),
+        time.num_hours() % 24,
+        time.num_minutes() % 60,
+    ) {
+        (0, 0, minutes) => format!("{}m", minutes),
+        (0, hours, minutes) => format!("{}h {}m", hours, minutes),
+        (days, hours, _) => format!("{}d {}h", days, hours),
+    }
+}
+
+fn print_bars(screen: &mut Screen, rows: &[(String, usize)]) {
+    let most = rows
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (label, count) in rows {
+        let width = (count * BAR_WIDTH).div_ceil(most);
+        writeln!(
+            screen,
+            "  {}  {:<width$} {}",
+            label,
+            "#".repeat(width),
+            count,
+            width = BAR_WIDTH
+        );
+    }
+}
+
+pub fn print_stats(screen: &mut Screen, todos: &Todos, done: &[Done], now: DateTime<Local>) {
+    let today = now.date_naive();
+    let open = todos.iter().filter(|item| !item.completed).count();
+    let cancelled = todos.iter().filter(|item| item.cancelled).count();
+    let completed = todos.len() - open - cancelled;
+    writeln!(
+        screen,
+        "{} open, {} completed, {} cancelled, {} archived\n",
+        open,
+        completed,
+        cancelled,
+        done.len()
+    );
+
+    let spans = spans(todos, done);
+
+    writeln!(screen, "Completed per day");
+    let days: Vec<(String, usize)> = (0..DAYS)
+        .rev()
+        .map(|ago| {
+            let day = today - Duration::days(ago);
+            (
+                day.format("%a %m-%d").to_string(),
+                completions(&spans, day, day),
+            )
+        })
+        .collect();
+    print_bars(screen, &days);
+
+    writeln!(screen, "\nCompleted per week");
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let weeks: Vec<(String, usize)> = (0..WEEKS)
+        .rev()
+        .map(|ago| {
+            let start = monday - Duration::weeks(ago);
+            let end = start + Duration::days(6);
+            (
+                start.format("wk %m-%d").to_string(),
+                completions(&spans, start, end),
+            )
+        })
+        .collect();
+    print_bars(screen, &weeks);
+
+    match average_time(&spans) {
+        Some((time, count)) => writeln!(
+            screen,
+            "\nAverage time to complete: {} over {} item{}",
+            format_duration(time),
+            count,
+            if count == 1 { "" } else { "s" }
+        ),
+        None => writeln!(screen, "\nAverage time to complete: nothing completed yet"),
+    }
+
+    writeln!(screen, "\nOpen items, last {} days", BURN_DOWN_DAYS);
+    let burn_down: Vec<(String, usize)> = (0..BURN_DOWN_DAYS)
+        .rev()
+        .map(|ago| {
+            let day = today - Duration::days(ago);
+            (day.format("%a %m-%d").to_string(), open_on(&spans, day))
+        })
+        .collect();
+    print_bars(screen, &burn_down);
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn stats() {
+        let now = Local.with_ymd_and_hms(2024, 5, 8, 18, 0, 0).unwrap();
+        let mut todos = sample();
+        for (i, item) in todos.iter_mut().enumerate() {
+            item.created = Some(now - Duration::days(10 - i as i64));
+        }
+        todos[1].completed = true;
+        todos[1].completed_at = Some(now - Duration::hours(30));
+        todos[3].completed_at = Some(now - Duration::days(3));
+        let mut report = todos[0].clone();
+        report.completed = true;
+        let done = vec![Done {
+            completed: now - Duration::days(9),
+            item: report,
+        }];
+
+        let mut screen = Screen::plain();
+        print_stats(&mut screen, &todos, &done, now);
+        insta::assert_snapshot!(screen.contents());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::minutes(5)), "5m");
+        assert_eq!(format_duration(Duration::minutes(125)), "2h 5m");
+        assert_eq!(format_duration(Duration::hours(50)), "2d 2h");
+    }
+}
@@ -0,0 +1,177 @@
+//! Newline-delimited JSON session over stdin/stdout, so editor plugins can
+//! drive the list without scraping the interactive prompt.
+//!
+//! Each request is a single line such as
+//! `{"id": 1, "method": "toggle", "params": {"index": 0}}` and is answered by
+//! a single line carrying the same `id` and either a `result` or an `error`.
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::history::{self, Operation};
+use crate::{read_from_file, write_to_file, Item, Todos};
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct Response {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct IndexedItem<'a> {
+    index: usize,
+    #[serde(flatten)]
+    item: &'a Item,
+}
+
+pub fn run_session() {
+    session(std::io::stdin().lock(), std::io::stdout().lock());
+}
+
+/// Answers each request read from `input` on `out`, until either ends.
+fn session(input: impl BufRead, mut out: impl Write) {
+    for line in input.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let id = request.id.clone();
+                match handle_request(request) {
+                    Ok(result) => Response {
+                        id,
+                        result: Some(result),
+                        error: None,
+                    },
+                    Err(error) => Response {
+                        id,
+                        result: None,
+                        error: Some(error),
+                    },
+                }
+            }
+            Err(e) => Response {
+                id: Value::Null,
+                result: None,
+                error: Some(format!("Invalid request: {}", e)),
+            },
+        };
+
+        let serialized = serde_json::to_string(&response).unwrap();
+        if writeln!(out, "{}", serialized)
+            .and_then(|_| out.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+fn handle_request(request: Request) -> Result<Value, String> {
+    // Re-read on every request so edits made from another terminal are seen.
+    let mut todos = read_from_file()?;
+
+    match request.method.as_str() {
+        "list" => {}
+        "add" => {
+            let name = request.params["name"]
+                .as_str()
+                .ok_or("add requires a string \"name\" param")?;
+            let operation = Operation::add(&todos, Item::new(name.to_string()));
+            history::perform(&mut todos, operation)?;
+            write_to_file(&todos)?;
+        }
+        "toggle" => {
+            let index = get_index(&request.params, &todos)?;
+            let operation = Operation::toggle(&todos, index);
+            history::perform(&mut todos, operation)?;
+            write_to_file(&todos)?;
+        }
+        method => return Err(format!("Unknown method {}", method)),
+    }
+
+    Ok(list_result(&todos))
+}
+
+fn get_index(params: &Value, todos: &Todos) -> Result<usize, String> {
+    let index = params["index"]
+        .as_u64()
+        .ok_or("toggle requires a numeric \"index\" param")? as usize;
+    if index >= todos.len() {
+        return Err(format!("No item at index {}", index));
+    }
+
+    Ok(index)
+}
+
+fn list_result(todos: &Todos) -> Value {
+    let items: Vec<IndexedItem> = todos
+        .iter()
+        .enumerate()
+        .map(|(index, item)| IndexedItem { index, item })
+        .collect();
+
+    serde_json::to_value(items).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_answered_in_turn() {
+        let _store = crate::tests::temp_store();
+        let requests = [
+            r#"{"id": 1, "method": "list"}"#,
+            r#"{"id": 2, "method": "add", "params": {"name": "buy milk"}}"#,
+            "",
+            r#"{"id": "3", "method": "toggle", "params": {"index": 0}}"#,
+            r#"{"id": 4, "method": "toggle", "params": {"index": 5}}"#,
+            r#"{"id": 5, "method": "remove"}"#,
+            "not json",
+        ];
+        let mut out = Vec::new();
+        session(requests.join("\n").as_bytes(), &mut out);
+
+        let replies: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 6);
+        assert_eq!(replies[0]["result"], serde_json::json!([]));
+        assert_eq!(replies[1]["id"], 2);
+        assert_eq!(replies[1]["result"][0]["index"], 0);
+        assert_eq!(replies[1]["result"][0]["name"], "buy milk");
+        assert_eq!(replies[1]["result"][0]["completed"], false);
+        assert_eq!(replies[2]["id"], "3");
+        assert_eq!(replies[2]["result"][0]["completed"], true);
+        assert_eq!(replies[3]["error"], "No item at index 5");
+        assert!(replies[3].get("result").is_none());
+        assert_eq!(replies[4]["error"], "Unknown method remove");
+        assert_eq!(replies[5]["id"], Value::Null);
+        assert!(replies[5]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid request"));
+    }
+}
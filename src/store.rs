@@ -0,0 +1,449 @@
+//! Where the list is kept. By default that's one file in the user's data
+//! directory (`$XDG_DATA_HOME/todo-rs/todo.json` on Linux), so every
+//! terminal sees the same list wherever it was started. `--file` or
+//! `TODO_FILE` point somewhere else, e.g. a list per project.
+//!
+//! Named lists are further files in the same directory, `work.json` for
+//! the list called work, so the file's directory is the set of lists.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::history::Operation;
+use crate::{check_index, Item, Todos};
+
+/// Somewhere a todo list is kept. Only loading and saving are needed, the
+/// changes are made in terms of them.
+pub trait TodoStore {
+    fn load(&self) -> Result<Todos, String>;
+
+    fn save(&self, todos: &Todos) -> Result<(), String>;
+
+    /// Adds an item at the end of the list and returns its index.
+    fn add(&self, item: Item) -> Result<usize, String> {
+        let mut todos = self.load()?;
+        todos.push(item);
+        self.save(&todos)?;
+        Ok(todos.len() - 1)
+    }
+
+    /// Toggles whether an item is completed and returns whether it now is.
+    /// Recurring items and subtasks work as they do for `todo check`.
+    fn check(&self, index: usize) -> Result<bool, String> {
+        let mut todos = self.load()?;
+        check_index(&todos, index)?;
+        Operation::toggle(&todos, index).apply(&mut todos)?;
+        self.save(&todos)?;
+        Ok(todos[index].completed)
+    }
+
+    /// Removes an item along with its subtasks and returns it.
+    fn remove(&self, index: usize) -> Result<Item, String> {
+        let mut todos = self.load()?;
+        check_index(&todos, index)?;
+        let item = todos[index].clone();
+        Operation::remove(&todos, index).apply(&mut todos)?;
+        self.save(&todos)?;
+        Ok(item)
+    }
+
+    /// Makes several changes at once, all or none of them: if one fails,
+    /// nothing is saved. Each change's index is into the list as the
+    /// changes before it left it.
+    fn batch(&self, changes: Vec<Change>) -> Result<(), String> {
+        let mut todos = self.load()?;
+        for (i, change) in changes.into_iter().enumerate() {
+            change
+                .apply(&mut todos)
+                .map_err(|e| format!("Change {} failed, nothing was changed: {}", i, e))?;
+        }
+        self.save(&todos)
+    }
+}
+
+/// One of the changes [`TodoStore::batch`] makes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Add(Item),
+    Check(usize),
+    Remove(usize),
+    Replace(usize, Item),
+}
+
+impl Change {
+    fn apply(self, todos: &mut Todos) -> Result<(), String> {
+        let operation = match self {
+            Change::Add(item) => Operation::add(todos, item),
+            Change::Check(index) => {
+                check_index(todos, index)?;
+                Operation::toggle(todos, index)
+            }
+            Change::Remove(index) => {
+                check_index(todos, index)?;
+                Operation::remove(todos, index)
+            }
+            Change::Replace(index, item) => {
+                check_index(todos, index)?;
+                Operation::replace(todos, index, item)
+            }
+        };
+        operation.apply(todos)
+    }
+}
+
+/// A list kept as a JSON array in a file, like todo.json.
+pub struct JsonFileStore {
+    path: PathBuf,
+}
+
+impl JsonFileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        JsonFileStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl TodoStore for JsonFileStore {
+    /// A missing file is an empty list, but a damaged one is an error, so
+    /// that saving can't replace it with an empty list.
+    fn load(&self) -> Result<Todos, String> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Could not read {}: {}", self.path.display(), e)),
+        };
+        if content.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str(&content).map_err(|e| {
+            format!(
+                "{} is damaged: {}\nRun `todo validate` to see what's wrong; the previous version is in {}",
+                self.path.display(),
+                e,
+                with_extension(&self.path, "bak").display()
+            )
+        })
+    }
+
+    /// Never leaves a half-written file behind: the list is written to a
+    /// temporary file that then replaces the old one, and the previous
+    /// version is kept with a `.bak` extension added.
+    fn save(&self, todos: &Todos) -> Result<(), String> {
+        let write = || -> std::io::Result<()> {
+            let temp = with_extension(&self.path, "tmp");
+            let mut writer = BufWriter::new(File::create(&temp)?);
+            serde_json::to_writer(&mut writer, todos)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+
+            if self.path.exists() {
+                std::fs::copy(&self.path, with_extension(&self.path, "bak"))?;
+            }
+            std::fs::rename(temp, &self.path)
+        };
+
+        write().map_err(|e| format!("Could not save {}: {}", self.path.display(), e))
+    }
+}
+
+/// How lists are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A JSON file, todo.json.
+    #[default]
+    Json,
+    /// An SQLite database next to where the JSON file would be, todo.db.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Backend::Json),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Backend::Sqlite),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err("This todo was built without the sqlite feature".to_string()),
+            _ => Err(format!("No backend called {}, expected json or sqlite", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Json => write!(f, "json"),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+const FILE_NAME: &str = "todo.json";
+pub(crate) const APP_DIR: &str = "todo-rs";
+
+static PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+static BACKEND: Mutex<Backend> = Mutex::new(Backend::Json);
+
+/// Decides which file and backend to use, and then switches to the list
+/// called `list` next to it if one is given. A todo.json left in the
+/// current directory by older versions is moved to the data directory the
+/// first time the default location is used.
+pub fn init(
+    file: Option<PathBuf>,
+    list: Option<String>,
+    backend: Option<Backend>,
+) -> Result<(), String> {
+    let backend = match backend {
+        Some(backend) => backend,
+        None => match std::env::var("TODO_BACKEND") {
+            Ok(backend) => backend.parse()?,
+            Err(_) => Backend::Json,
+        },
+    };
+    *BACKEND.lock().unwrap() = backend;
+
+    let path = match file.or_else(|| std::env::var_os("TODO_FILE").map(PathBuf::from)) {
+        Some(path) => path,
+        None => {
+            let path = default_path()?;
+            migrate(Path::new(FILE_NAME), &path)?;
+            path
+        }
+    };
+
+    *PATH.lock().unwrap() = Some(path);
+    match list {
+        Some(list) => switch(&list),
+        None => Ok(()),
+    }
+}
+
+/// Where the current list is kept, with the backend in use.
+pub fn open() -> Box<dyn TodoStore> {
+    open_with(backend())
+}
+
+/// Where the current list is kept with `backend`.
+pub fn open_with(backend: Backend) -> Box<dyn TodoStore> {
+    open_at(&path(), backend)
+}
+
+pub fn backend() -> Backend {
+    *BACKEND.lock().unwrap()
+}
+
+/// Where the list called `name` is kept.
+pub fn open_list(name: &str) -> Box<dyn TodoStore> {
+    open_at(&list_path(name), backend())
+}
+
+fn open_at(path: &Path, backend: Backend) -> Box<dyn TodoStore> {
+    match backend {
+        Backend::Json => Box::new(JsonFileStore::new(path)),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Box::new(crate::SqliteStore::new(path.with_extension("db"))),
+    }
+}
+
+/// The current list's database with the sqlite backend.
+#[cfg(feature = "sqlite")]
+pub fn sqlite() -> crate::SqliteStore {
+    crate::SqliteStore::new(path().with_extension("db"))
+}
+
+/// Whether `init` has been called, so there's a list to speak of.
+pub fn is_initialized() -> bool {
+    PATH.lock().is_ok_and(|path| path.is_some())
+}
+
+/// The current list's file, or where it would be with the JSON backend.
+/// Only valid after `init`.
+pub fn path() -> PathBuf {
+    PATH.lock()
+        .unwrap()
+        .clone()
+        .expect("store::init wasn't called")
+}
+
+/// The name of the current list, which is its file name without `.json`.
+pub fn list_name() -> String {
+    path()
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Makes `name` the current list. Its file is created on the first save.
+pub fn switch(name: &str) -> Result<(), String> {
+    check_list_name(name)?;
+    let path = list_path(name);
+    *PATH.lock().unwrap() = Some(path);
+    Ok(())
+}
+
+/// The current list's file with another extension added, e.g.
+/// `todo.json.bak` for `bak`.
+pub fn path_with(extension: &str) -> PathBuf {
+    with_extension(&path(), extension)
+}
+
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// The file of the list called `name`, next to the current one.
+pub fn list_path(name: &str) -> PathBuf {
+    path().with_file_name(format!("{}.json", name))
+}
+
+pub fn check_list_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "{:?} can't be a list name, use letters, digits, - and _",
+            name
+        ));
+    }
+
+    Ok(())
+}
+
+/// The names of every list next to the current one, in order, including
+/// the current one even if it hasn't been saved yet.
+pub fn list_names() -> Vec<String> {
+    let current = list_name();
+    let dir = match path().parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stem = name
+                .strip_suffix(".json")
+                .or_else(|| name.strip_suffix(".db"))?;
+            // Skip siblings like todo.history.json.
+            check_list_name(stem).ok().map(|()| stem.to_string())
+        })
+        .collect();
+    if !names.contains(&current) {
+        names.push(current);
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// A file kept next to the list, e.g. `todo.history.json` for `todo.json`.
+pub fn sibling(suffix: &str) -> PathBuf {
+    path().with_file_name(format!("{}.{}", list_name(), suffix))
+}
+
+fn default_path() -> Result<PathBuf, String> {
+    let dir = dirs::data_dir()
+        .ok_or("Couldn't find a data directory, use --file or TODO_FILE")?
+        .join(APP_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    Ok(dir.join(FILE_NAME))
+}
+
+fn migrate(old: &Path, new: &Path) -> Result<(), String> {
+    if new.exists() || !old.is_file() {
+        return Ok(());
+    }
+
+    move_file(old, new)?;
+    let old_history = old.with_file_name("todo.history.json");
+    if old_history.is_file() {
+        move_file(&old_history, &new.with_file_name("todo.history.json"))?;
+    }
+
+    eprintln!("Moved {} to {}", old.display(), new.display());
+    Ok(())
+}
+
+/// Renames, or copies and removes when the data directory is on another
+/// file system.
+fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    let error = |e: std::io::Error| {
+        format!(
+            "Could not move {} to {}: {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    };
+
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).map_err(error)?;
+        std::fs::remove_file(from).map_err(error)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_file_store_changes_items() {
+        let dir = std::env::temp_dir().join(format!("todo-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = JsonFileStore::new(dir.join("todo.json"));
+
+        assert_eq!(store.load(), Ok(Vec::new()));
+        assert_eq!(store.add(Item::new("first".to_string())), Ok(0));
+        assert_eq!(store.add(Item::new("second".to_string())), Ok(1));
+        assert_eq!(store.check(1), Ok(true));
+        assert_eq!(
+            store.remove(0).map(|item| item.name),
+            Ok("first".to_string())
+        );
+        assert!(store.check(1).is_err());
+
+        let todos = store.load().unwrap();
+        assert_eq!(todos.len(), 1);
+        assert!(todos[0].completed);
+        assert!(dir.join("todo.json.bak").exists());
+
+        let failing = vec![
+            Change::Add(Item::new("third".to_string())),
+            Change::Check(5),
+        ];
+        assert!(store.batch(failing).is_err());
+        assert_eq!(store.load().unwrap(), todos);
+        let changes = vec![
+            Change::Add(Item::new("third".to_string())),
+            Change::Remove(0),
+        ];
+        assert_eq!(store.batch(changes), Ok(()));
+        assert_eq!(store.load().unwrap()[0].name, "third");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
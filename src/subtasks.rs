@@ -0,0 +1,128 @@
+//! Subtasks: items broken down into smaller steps. A subtask is kept
+//! right after its parent in the list, so indices stay plain numbers and
+//! every command works on subtasks as on any other item. An item marked
+//! as a subtask belongs to the closest item above it that isn't one.
+
+use std::ops::Range;
+
+use crate::{check_index, Item, Todos};
+
+/// The index of the item `index` is a subtask of, if it is one.
+pub fn parent(todos: &Todos, index: usize) -> Option<usize> {
+    if !todos[index].subtask {
+        return None;
+    }
+
+    (0..index).rev().find(|&i| !todos[i].subtask)
+}
+
+/// The indices of the subtasks of the item at `index`, none if it's a
+/// subtask itself.
+pub fn children(todos: &Todos, index: usize) -> Range<usize> {
+    if todos[index].subtask {
+        return index + 1..index + 1;
+    }
+    let end = (index + 1..todos.len())
+        .find(|&i| !todos[i].subtask)
+        .unwrap_or(todos.len());
+    index + 1..end
+}
+
+/// Where to insert `item` as the last subtask of the item at `parent`.
+pub fn position_under(todos: &Todos, parent: usize, item: &mut Item) -> Result<usize, String> {
+    check_index(todos, parent)?;
+    if todos[parent].subtask {
+        return Err(format!(
+            "{} is a subtask itself, add to its parent instead",
+            parent
+        ));
+    }
+
+    item.subtask = true;
+    Ok(children(todos, parent).end)
+}
+
+/// Whether every subtask of `parent` is done once the one at `index` is
+/// `completed`.
+pub fn all_done(todos: &Todos, parent: usize, index: usize, completed: bool) -> bool {
+    children(todos, parent).all(|i| match i == index {
+        true => completed,
+        false => todos[i].completed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Operation;
+    use crate::priority::{self, SortOrder};
+    use crate::Priority;
+
+    fn book() -> Todos {
+        let mut todos = vec![Item::new("write a book".to_string())];
+        for name in ["write intro", "write outro"] {
+            let mut item = Item::new(name.to_string());
+            let index = position_under(&todos, 0, &mut item).unwrap();
+            todos.insert(index, item);
+        }
+        let mut urgent = Item::new("pay rent".to_string());
+        urgent.priority = Some(Priority::High);
+        todos.push(urgent);
+        todos
+    }
+
+    #[test]
+    fn removing_a_subtask_leaves_the_others() {
+        let mut todos = book();
+        Operation::remove(&todos, 1).apply(&mut todos).unwrap();
+        assert_eq!(todos[1].name, "write outro");
+        Operation::remove(&todos, 0).apply(&mut todos).unwrap();
+        assert_eq!(todos.len(), 1);
+    }
+
+    #[test]
+    fn moving_keeps_subtasks_together() {
+        let names =
+            |todos: &Todos| -> Vec<String> { todos.iter().map(|item| item.name.clone()).collect() };
+        let mut todos = book();
+
+        Operation::move_to(&todos, 3, 1).apply(&mut todos).unwrap();
+        assert_eq!(
+            names(&todos),
+            ["write a book", "write intro", "write outro", "pay rent"]
+        );
+        Operation::move_to(&todos, 0, usize::MAX)
+            .apply(&mut todos)
+            .unwrap();
+        assert_eq!(
+            names(&todos),
+            ["pay rent", "write a book", "write intro", "write outro"]
+        );
+        Operation::move_to(&todos, 3, 0).apply(&mut todos).unwrap();
+        assert_eq!(
+            names(&todos),
+            ["pay rent", "write a book", "write outro", "write intro"]
+        );
+    }
+
+    #[test]
+    fn checking_the_last_subtask_completes_the_parent() {
+        let mut todos = book();
+
+        Operation::toggle(&todos, 1).apply(&mut todos).unwrap();
+        assert!(!todos[0].completed);
+        Operation::toggle(&todos, 2).apply(&mut todos).unwrap();
+        assert!(todos[0].completed);
+        Operation::toggle(&todos, 2).apply(&mut todos).unwrap();
+        assert!(!todos[0].completed);
+    }
+
+    #[test]
+    fn subtasks_stay_under_their_parent_when_sorting() {
+        let todos = book();
+        assert_eq!(
+            priority::display_order(&todos, &SortOrder::Priority),
+            vec![3, 0, 1, 2]
+        );
+    }
+}
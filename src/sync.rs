@@ -0,0 +1,705 @@
+//! `todo sync --ssh user@host`: keeps the list in step with a todo-rs
+//! installation on another machine, using nothing but SSH.
+//!
+//! The other machine's list is fetched with `todo sync --send`, merged with
+//! this one, and the result sent back with `todo sync --receive`.
+//!
+//! `todo sync --s3 s3://bucket/path` does the same through S3 or anything
+//! speaking its API, like MinIO or B2 with `--endpoint`: the list is kept
+//! there as `<path>/<list>.json`, read and written with the `aws` command,
+//! which brings its own credentials and configuration. With
+//! `--age-identity key.txt` it's encrypted with `age` before it leaves the
+//! machine, so the storage only ever sees ciphertext. A list shared that
+//! way is also encrypted for the keys added with `todo recipients add`.
+//!
+//! `todo sync --git <repository>` keeps `<list>.json` in a git repository,
+//! cloned next to the list, with a commit for every sync that changed
+//! something. `todo sync --http <url>` keeps it at `<url>/<list>.json` on
+//! any web server that takes PUT, using `curl`.
+//!
+//! Merging is three-way: the list as it was after the last sync with that
+//! host is kept next to the list, so a change made on one side wins over an
+//! unchanged item on the other, and removals aren't undone by the other
+//! side still having the item. Items are told apart by their ids, or by
+//! when they were created if they're from before there were ids, or else
+//! by their names and how many of the same name come before. The merged
+//! list gives every item an id, so that's needed only once. When both
+//! sides changed the same item, this side's version is kept and the
+//! conflict reported.
+//!
+//! Before anything is written, the changes the merge makes to this list
+//! and to the other side's are listed. At a terminal, the ones coming here
+//! can be taken all, none or some at a time; a change left out here is
+//! undone on the other side too, so both end up with the same list. With
+//! `--yes`, or when not at a terminal, they're all taken.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::prompt::Prompt;
+use crate::screen::Screen;
+use crate::{curl, parse_indices, recipients, secret, store, Item, Todos};
+
+/// What identifies an item across machines.
+pub(crate) fn key(item: &Item) -> String {
+    match (item.id, item.created) {
+        (Some(id), _) => id.to_string(),
+        (None, Some(created)) => created.to_rfc3339(),
+        (None, None) => item.name.clone(),
+    }
+}
+
+/// `key` for each of `todos`, with `#1`, `#2` and so on for the ones an
+/// earlier item already has, so that items sharing a name stay apart.
+fn keys(todos: &Todos) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    todos
+        .iter()
+        .map(|item| {
+            let key = key(item);
+            let count = seen.entry(key.clone()).or_default();
+            *count += 1;
+            match *count {
+                1 => key,
+                n => format!("{}#{}", key, n - 1),
+            }
+        })
+        .collect()
+}
+
+/// Merges `local` and `remote`, both changed from `base`. Returns the
+/// merged list, in local order followed by items only added remotely, and
+/// a message for each conflict.
+pub(crate) fn merge(base: &Todos, local: &Todos, remote: &Todos) -> (Todos, Vec<String>) {
+    let index = |todos: &Todos| -> HashMap<String, Item> {
+        keys(todos).into_iter().zip(todos.iter().cloned()).collect()
+    };
+    let (base_items, remote_items) = (index(base), index(remote));
+    let local_keys = keys(local);
+
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    for (key, item) in local_keys.iter().zip(local) {
+        match (base_items.get(key), remote_items.get(key)) {
+            (_, Some(theirs)) if theirs == item => merged.push(item.clone()),
+            (Some(base), Some(theirs)) if base == item => merged.push(theirs.clone()),
+            (Some(base), Some(theirs)) if base == theirs => merged.push(item.clone()),
+            (_, Some(_)) => {
+                conflicts.push(format!(
+                    "\"{}\" changed on both sides, kept this side's version",
+                    item.name
+                ));
+                merged.push(item.clone());
+            }
+            // Removed on the other side, unless it was changed here since.
+            (Some(base), None) if base == item => {}
+            (_, None) => merged.push(item.clone()),
+        }
+    }
+    for (key, item) in keys(remote).into_iter().zip(remote) {
+        if local_keys.contains(&key) {
+            continue;
+        }
+        match base_items.get(&key) {
+            // Removed here, unless it was changed on the other side since.
+            Some(base) if base == item => {}
+            _ => merged.push(item.clone()),
+        }
+    }
+
+    (merged, conflicts)
+}
+
+/// How one version of the list differs from another.
+#[derive(Debug, PartialEq)]
+enum Difference {
+    Added {
+        key: String,
+        item: Item,
+    },
+    Changed {
+        key: String,
+        before: Box<Item>,
+        after: Box<Item>,
+    },
+    /// Was at `index` in the earlier version.
+    Removed {
+        index: usize,
+        item: Item,
+    },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::Added { item, .. } => write!(f, "+ {}", item),
+            Difference::Changed { before, after, .. } if before.name != after.name => {
+                write!(f, "~ {} (was \"{}\")", after, before.name)
+            }
+            Difference::Changed { after, .. } => write!(f, "~ {}", after),
+            Difference::Removed { item, .. } => write!(f, "- {}", item),
+        }
+    }
+}
+
+/// What changes `from` into `to`: added and changed items in the order of
+/// `to`, then the removed ones.
+fn differences(from: &Todos, to: &Todos) -> Vec<Difference> {
+    let before: HashMap<String, &Item> = keys(from).into_iter().zip(from).collect();
+    let kept: HashSet<String> = keys(to).into_iter().collect();
+
+    let mut differences = Vec::new();
+    for (key, item) in keys(to).into_iter().zip(to) {
+        match before.get(&key) {
+            None => differences.push(Difference::Added {
+                key,
+                item: item.clone(),
+            }),
+            Some(&before) if before != item => differences.push(Difference::Changed {
+                key,
+                before: Box::new(before.clone()),
+                after: Box::new(item.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (index, (key, item)) in keys(from).into_iter().zip(from).enumerate() {
+        if !kept.contains(&key) {
+            differences.push(Difference::Removed {
+                index,
+                item: item.clone(),
+            });
+        }
+    }
+    differences
+}
+
+/// Undoes the `differences` made to `merged` that weren't `taken`.
+fn reject(merged: &mut Todos, differences: Vec<Difference>, taken: &[usize]) {
+    // Keys as they were in `merged`, before anything is taken out of it.
+    let merged_keys = keys(merged);
+    let position = |key: &str| merged_keys.iter().position(|other| other == key);
+    let mut dropped = Vec::new();
+    let mut removed = Vec::new();
+    for (i, difference) in differences.into_iter().enumerate() {
+        if taken.contains(&i) {
+            continue;
+        }
+        match difference {
+            Difference::Added { key, .. } => dropped.extend(position(&key)),
+            Difference::Changed { key, before, .. } => {
+                if let Some(i) = position(&key) {
+                    merged[i] = *before;
+                }
+            }
+            Difference::Removed { index, item } => removed.push((index, item)),
+        }
+    }
+    let mut i = 0;
+    merged.retain(|_| {
+        i += 1;
+        !dropped.contains(&(i - 1))
+    });
+    // In the order they were in, so each goes back close to where it was.
+    for (index, item) in removed {
+        merged.insert(index.min(merged.len()), item);
+    }
+}
+
+/// Asks which of the `incoming` changes to take. Returns their indices, or
+/// `None` if the user cancels the sync.
+fn ask_which(screen: &mut Screen, count: usize) -> Option<Vec<usize>> {
+    Prompt::new(
+        "\nTake which changes? all, none, or some like 0 2-3; empty for all, cancel to stop",
+        move |line| match line {
+            "all" | "a" => Ok((0..count).collect()),
+            "none" | "n" => Ok(Vec::new()),
+            _ => parse_indices(line, count),
+        },
+    )
+    .default((0..count).collect())
+    .ask(screen)
+}
+
+/// Where the list is synced with.
+pub enum Remote {
+    /// `todo` on another machine, run over SSH.
+    Ssh { host: String, command: String },
+    /// An object in S3-compatible storage.
+    S3 {
+        url: String,
+        endpoint: Option<String>,
+        age_identity: Option<PathBuf>,
+    },
+    /// A file in a git repository, committed on every sync.
+    Git { url: String },
+    /// A document at a URL, read with GET and written with PUT.
+    Http { url: String },
+}
+
+impl Remote {
+    fn name(&self) -> &str {
+        match self {
+            Remote::Ssh { host, .. } => host,
+            Remote::S3 { url, .. } | Remote::Git { url } | Remote::Http { url } => url,
+        }
+    }
+
+    /// The remote's name as part of a file name.
+    fn file_name(&self) -> String {
+        match self {
+            Remote::Ssh { host, .. } => host.clone(),
+            Remote::S3 { url, .. } => format!("s3-{}", url.trim_start_matches("s3://")),
+            Remote::Git { url } => format!("git-{}", url),
+            Remote::Http { url } => format!(
+                "http-{}",
+                url.split_once("://").map_or(&**url, |(_, rest)| rest)
+            ),
+        }
+        .chars()
+        .map(|c| match c.is_alphanumeric() || "@.-_".contains(c) {
+            true => c,
+            false => '_',
+        })
+        .collect()
+    }
+
+    /// Where the list as of the last sync with this remote is kept.
+    fn base_path(&self) -> PathBuf {
+        store::sibling(&format!("sync-{}.json", self.file_name()))
+    }
+
+    /// Where a git remote is cloned.
+    fn clone_path(&self) -> PathBuf {
+        store::sibling(&format!("sync-{}", self.file_name()))
+    }
+
+    /// The list on the other side, or nothing if it has none yet.
+    fn fetch(&self) -> Result<Option<Todos>, String> {
+        let content = match self {
+            Remote::Ssh { host, command } => Some(ssh(host, command, &["--send"], "")?),
+            Remote::S3 {
+                url,
+                endpoint,
+                age_identity,
+            } => {
+                let object = list_url(url);
+                // Listing a missing object fails without printing anything,
+                // unlike other failures which are left to the copy.
+                let listing = aws(endpoint, &["s3", "ls", &object])
+                    .output()
+                    .map_err(|e| format!("Could not run aws: {}", e))?;
+                if listing.stdout.is_empty() && !listing.status.success() {
+                    None
+                } else {
+                    let content = run(aws(endpoint, &["s3", "cp", &object, "-"]), &[])?;
+                    match age_identity {
+                        Some(identity) => Some(age(identity, "-d", &content)?),
+                        None => Some(content),
+                    }
+                }
+            }
+            Remote::Git { url } => {
+                let clone = self.clone_path();
+                if !clone.exists() {
+                    // A repository starting with `-` isn't taken for an option.
+                    let mut git = Command::new("git");
+                    git.args(["clone", "-q", "--", url]).arg(&clone);
+                    run(git, &[])?;
+                }
+                // The clone follows the remote exactly; changes made here
+                // are in the list and get merged in.
+                if !git(&clone, &["ls-remote", "--heads", "origin"])?.is_empty() {
+                    git(&clone, &["fetch", "-q", "origin", "HEAD"])?;
+                    git(&clone, &["reset", "-q", "--hard", "FETCH_HEAD"])?;
+                }
+                std::fs::read(clone.join(list_file())).ok()
+            }
+            Remote::Http { url } => {
+                let output = curl(&["-sS", "-w", "\n%{http_code}", &list_url(url)], None)?;
+                let output = String::from_utf8(output).map_err(|e| e.to_string())?;
+                let (body, status) = output.rsplit_once('\n').unwrap_or(("", &output));
+                match status {
+                    "200" => Some(body.as_bytes().to_vec()),
+                    "404" => None,
+                    _ => return Err(format!("{} answered with {}", url, status)),
+                }
+            }
+        };
+        let Some(content) = content else {
+            return Ok(None);
+        };
+
+        let content = String::from_utf8(content).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("{} has a list that couldn't be read: {}", self.name(), e))
+    }
+
+    /// Leaves the other side with `json`.
+    fn push(&self, json: &str) -> Result<(), String> {
+        match self {
+            Remote::Ssh { host, command } => {
+                ssh(host, command, &["--receive"], json)?;
+            }
+            Remote::S3 {
+                url,
+                endpoint,
+                age_identity,
+            } => {
+                let content = match age_identity {
+                    Some(identity) => age(identity, "-e", json.as_bytes())?,
+                    None => json.as_bytes().to_vec(),
+                };
+                run(aws(endpoint, &["s3", "cp", "-", &list_url(url)]), &content)?;
+            }
+            Remote::Git { .. } => {
+                let clone = self.clone_path();
+                let file = list_file();
+                std::fs::write(clone.join(&file), json)
+                    .map_err(|e| format!("Could not write to {}: {}", clone.display(), e))?;
+                if !git(&clone, &["status", "--porcelain"])?.is_empty() {
+                    git(&clone, &["add", &file])?;
+                    let message = format!("Sync {}", store::list_name());
+                    git(&clone, &["commit", "-q", "-m", &message])?;
+                }
+                git(&clone, &["push", "-q", "origin", "HEAD"])?;
+            }
+            Remote::Http { url } => {
+                let url = list_url(url);
+                let arguments = ["-sS", "--fail", "-X", "PUT"];
+                let content_type = ["-H", "Content-Type: application/json", &url];
+                curl(&[&arguments[..], &content_type].concat(), Some(json))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `command`, sending it `input`, and returns what it prints.
+fn run(mut command: Command, input: &[u8]) -> Result<Vec<u8>, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run {}: {}", program, e))?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| format!("Could not talk to {}", program))?
+        .write_all(input)
+        .map_err(|e| format!("Could not talk to {}: {}", program, e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Could not talk to {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed", program));
+    }
+    Ok(output.stdout)
+}
+
+/// `word` quoted for a POSIX shell, so it stays one word whatever is in it.
+fn shell_quoted(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// Runs `todo` on `host` with `arguments`, sending it `input`, and returns
+/// what it prints.
+fn ssh(host: &str, command: &str, arguments: &[&str], input: &str) -> Result<Vec<u8>, String> {
+    let ssh = ssh_command(host, command, &store::list_name(), arguments);
+    run(ssh, input.as_bytes()).map_err(|e| format!("{}, syncing with {}", e, host))
+}
+
+/// The ssh command running `todo` for `list` on `host`. ssh hands the
+/// command to the remote shell, so `command` is taken as written there,
+/// and the rest is quoted. The host comes after `--`, so one starting with
+/// `-` isn't taken for an option.
+fn ssh_command(host: &str, command: &str, list: &str, arguments: &[&str]) -> Command {
+    let mut remote_command = command.to_string();
+    for argument in ["--list", list, "sync"].iter().chain(arguments) {
+        remote_command.push(' ');
+        remote_command.push_str(&shell_quoted(argument));
+    }
+    let mut ssh = Command::new("ssh");
+    ssh.arg("--").arg(host).arg(remote_command);
+    ssh
+}
+
+/// The list's file name on the other side.
+fn list_file() -> String {
+    format!("{}.json", store::list_name())
+}
+
+/// Where under `url`, like a bucket or a web server, the list is kept.
+fn list_url(url: &str) -> String {
+    format!("{}/{}", url.trim_end_matches('/'), list_file())
+}
+
+fn git(clone: &Path, arguments: &[&str]) -> Result<Vec<u8>, String> {
+    let mut git = Command::new("git");
+    git.arg("-C").arg(clone).args(arguments);
+    run(git, &[])
+}
+
+/// Runs `curl` with `arguments`, sending `body` and the `http-token`
+/// secret as a bearer token if there is one. Both go on stdin, as the
+/// command line is visible to every user.
+fn curl(arguments: &[&str], body: Option<&str>) -> Result<Vec<u8>, String> {
+    let token = secret::get(secret::HTTP_TOKEN)?;
+    let header = token.map(|token| format!("Authorization: Bearer {}", token));
+    let mut options = Vec::new();
+    if let Some(header) = &header {
+        options.push(("header", header.as_str()));
+    }
+    if let Some(body) = body {
+        options.push(("data-raw", body));
+    }
+    curl::run(arguments, &options)
+}
+
+fn aws(endpoint: &Option<String>, arguments: &[&str]) -> Command {
+    let mut aws = Command::new("aws");
+    if let Some(endpoint) = endpoint {
+        aws.arg("--endpoint-url").arg(endpoint);
+    }
+    aws.args(arguments);
+    aws
+}
+
+/// Encrypts (`-e`) or decrypts (`-d`) `input` with the key in `identity`.
+fn age(identity: &Path, mode: &str, input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut age = Command::new("age");
+    age.arg(mode).arg("-i").arg(identity);
+    // Shared lists are encrypted for everyone's keys as well as ours.
+    if mode == "-e" && !recipients::read()?.is_empty() {
+        age.arg("-R").arg(recipients::path());
+    }
+    run(age, input)
+}
+
+/// Merges the list with the same list on `remote`, leaving both with the
+/// result. The changes are shown first and, if `ask`, the ones coming here
+/// can be picked. Returns what to tell the user.
+pub fn sync(
+    todos: &mut Todos,
+    remote: &Remote,
+    screen: &mut Screen,
+    ask: bool,
+) -> Result<Vec<String>, String> {
+    let theirs = remote.fetch()?.unwrap_or_default();
+    let base_path = remote.base_path();
+    let base: Todos = match std::fs::read_to_string(&base_path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    let (mut merged, mut messages) = merge(&base, todos, &theirs);
+
+    let incoming = differences(todos, &merged);
+    let outgoing = differences(&theirs, &merged);
+    if !outgoing.is_empty() {
+        writeln!(screen, "Changes going to {}:", remote.name());
+        for difference in &outgoing {
+            writeln!(screen, "    {}", difference);
+        }
+    }
+    if !incoming.is_empty() {
+        writeln!(screen, "Changes coming from {}:", remote.name());
+        let width = (incoming.len() - 1).to_string().len();
+        for (i, difference) in incoming.iter().enumerate() {
+            writeln!(screen, "{:>width$}   {}", i, difference, width = width);
+        }
+        let taken = match ask {
+            true => {
+                ask_which(screen, incoming.len()).ok_or("Sync cancelled, nothing was changed")?
+            }
+            false => (0..incoming.len()).collect(),
+        };
+        if taken.len() < incoming.len() {
+            messages.push(format!(
+                "Left out {} of the changes from {}",
+                incoming.len() - taken.len(),
+                remote.name()
+            ));
+        }
+        reject(&mut merged, incoming, &taken);
+    }
+    screen.flush();
+
+    // Items from before there were ids get them now, on both sides.
+    for item in merged.iter_mut().filter(|item| item.id.is_none()) {
+        item.id = Some(uuid::Uuid::new_v4());
+    }
+
+    let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+    remote.push(&json)?;
+    std::fs::write(&base_path, &json)
+        .map_err(|e| format!("Could not write {}: {}", base_path.display(), e))?;
+
+    messages.push(format!(
+        "Synced {} items with {}",
+        merged.len(),
+        remote.name()
+    ));
+    *todos = merged;
+    Ok(messages)
+}
+
+/// The other machine's side of `sync`: prints the list.
+pub fn send(todos: &Todos) -> Result<(), String> {
+    println!(
+        "{}",
+        serde_json::to_string(todos).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
+
+/// The other machine's side of `sync`: replaces the list with the merged
+/// one read from stdin.
+pub fn receive(todos: &mut Todos) -> Result<(), String> {
+    *todos = serde_json::from_reader(std::io::stdin())
+        .map_err(|e| format!("The merged list couldn't be read: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn changes_can_be_left_out() {
+        let local = sample();
+        let mut merged = local.clone();
+        merged[1].completed = true;
+        merged.remove(3);
+        merged.push(Item::new("new there".to_string()));
+
+        let incoming = differences(&local, &merged);
+        assert!(matches!(incoming[0], Difference::Changed { .. }));
+        assert!(matches!(incoming[1], Difference::Added { .. }));
+        assert!(matches!(incoming[2], Difference::Removed { index: 3, .. }));
+
+        let mut taken = merged.clone();
+        reject(&mut taken, differences(&local, &merged), &[0, 1, 2]);
+        assert_eq!(taken, merged);
+
+        reject(&mut merged, incoming, &[1]);
+        let mut expected = local.clone();
+        expected.push(merged[5].clone());
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn changes_from_both_sides_are_kept() {
+        let base = sample();
+        let mut local = base.clone();
+        let mut remote = base.clone();
+
+        local[1].completed = true;
+        local.remove(0);
+        remote[2].name = "do taxes early".to_string();
+        remote.push(Item::new("new there".to_string()));
+
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        let names: Vec<&str> = merged.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "buy milk",
+                "do taxes early",
+                "call @alice about +work",
+                "book lunch",
+                "new there"
+            ]
+        );
+        assert!(merged[0].completed);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn conflicts_keep_this_side() {
+        let base = sample();
+        let mut local = base.clone();
+        let mut remote = base.clone();
+        local[1].name = "buy oat milk".to_string();
+        remote[1].name = "buy soy milk".to_string();
+
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(merged[1].name, "buy oat milk");
+        assert_eq!(conflicts.len(), 1);
+    }
+
+    #[test]
+    fn items_made_at_the_same_time_are_told_apart() {
+        let base = sample();
+        let (mut local, mut remote) = (base.clone(), base.clone());
+        let then = Local.with_ymd_and_hms(2024, 5, 6, 9, 0, 0).unwrap();
+        for (todos, name) in [(&mut local, "added here"), (&mut remote, "added there")] {
+            let mut item = Item::new(name.to_string());
+            item.created = Some(then);
+            todos.push(item);
+        }
+
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(merged.len(), base.len() + 2);
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn items_from_before_ids_go_by_when_they_were_made() {
+        let mut item = Item::new("old".to_string());
+        assert_eq!(key(&item), item.id.unwrap().to_string());
+        item.id = None;
+        let created = item.created.unwrap();
+        assert_eq!(key(&item), created.to_rfc3339());
+    }
+
+    #[test]
+    fn items_without_ids_or_dates_go_by_name_and_order() {
+        let mut base = sample();
+        for item in base.iter_mut() {
+            item.id = None;
+            item.created = None;
+        }
+        base.push(base[1].clone());
+        assert_eq!(keys(&base)[5], "buy milk#1");
+
+        let (mut local, remote) = (base.clone(), base.clone());
+        local[5].completed = true;
+        let (merged, conflicts) = merge(&base, &local, &remote);
+        assert_eq!(merged, local);
+        assert!(conflicts.is_empty());
+
+        let incoming = differences(&base, &merged);
+        assert_eq!(incoming.len(), 1);
+        let mut rejected = merged.clone();
+        reject(&mut rejected, incoming, &[]);
+        assert_eq!(rejected, base);
+    }
+
+    #[test]
+    fn remote_arguments_stay_one_word() {
+        assert_eq!(shell_quoted("work"), "'work'");
+        assert_eq!(shell_quoted("a b; rm -rf ~"), "'a b; rm -rf ~'");
+        assert_eq!(shell_quoted("it's"), "'it'\\''s'");
+
+        let ssh = ssh_command("-oProxyCommand=x", "todo", "work", &["--send"]);
+        let arguments: Vec<_> = ssh.get_args().collect();
+        assert_eq!(
+            arguments,
+            [
+                "--",
+                "-oProxyCommand=x",
+                "todo '--list' 'work' 'sync' '--send'"
+            ]
+        );
+    }
+}
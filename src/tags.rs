@@ -0,0 +1,31 @@
+//! `+work` style tags in item names.
+
+/// Returns the lowercased words written as `<prefix>word` in `name`, in
+/// order of first use. Trailing punctuation is not part of the word.
+pub fn prefixed_words(name: &str, prefix: char) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for word in name.split_whitespace() {
+        let word = match word.strip_prefix(prefix) {
+            Some(rest) => rest
+                .trim_end_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase(),
+            None => continue,
+        };
+
+        if !word.is_empty() && !words.contains(&word) {
+            words.push(word);
+        }
+    }
+
+    words
+}
+
+pub fn parse_tags(name: &str) -> Vec<String> {
+    prefixed_words(name, '+')
+}
+
+/// Normalises a tag given on its own, e.g. to `--tag`, so `+Work` and
+/// `work` select the same items.
+pub fn normalize(tag: &str) -> String {
+    tag.trim().trim_start_matches('+').to_lowercase()
+}
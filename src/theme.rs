@@ -0,0 +1,366 @@
+//! How items stand out in lists, for the prompt, `todo print` and `todo tui`
+//! alike. `theme` in the config picks one of the bundled themes: `default`,
+//! `high-contrast`, which keeps the terminal's colors, doesn't dim and
+//! underlines what's urgent, or `mono` without colors. `[styles]` changes parts of it:
+//!
+//! ```toml
+//! theme = "high-contrast"
+//!
+//! [styles]
+//! overdue = "magenta bold"  # past their due date
+//! high = "bold"             # high priority
+//! done = "dim"              # completed and cancelled
+//! match = "yellow underline" # what a filter matched in `todo tui`
+//! ```
+//!
+//! A style is a color (black, red, green, yellow, blue, magenta, cyan,
+//! white or grey, and the darker `dark-red` and so on) and any of bold, dim
+//! and underline, or `plain`. Each
+//! theme has its own checkboxes too, which `[checkbox]` in the config
+//! overrides.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crossterm::style::Color;
+use serde::Deserialize;
+
+use crate::config;
+use crate::screen::{Screen, Style};
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    HighContrast,
+    Mono,
+}
+
+/// Changes to the theme's styles from the config.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Styles {
+    pub overdue: Option<Style>,
+    pub high: Option<Style>,
+    pub done: Option<Style>,
+    #[serde(rename = "match")]
+    pub matched: Option<Style>,
+}
+
+pub struct Theme {
+    pub overdue: Style,
+    pub high: Style,
+    pub done: Style,
+    pub matched: Style,
+    /// Open, done and cancelled.
+    pub checkboxes: [&'static str; 3],
+}
+
+fn bundled(name: ThemeName) -> Theme {
+    let style = |color, bold, dim, underline| Style {
+        color,
+        bold,
+        dim,
+        underline,
+    };
+    match name {
+        ThemeName::Default => Theme {
+            overdue: style(Some(Color::Red), false, false, false),
+            high: style(None, true, false, false),
+            done: style(None, false, true, false),
+            matched: style(Some(Color::Yellow), true, false, false),
+            checkboxes: ["[ ]", "[x]", "[-]"],
+        },
+        // No color reads well on both dark and light backgrounds, so this
+        // one keeps the terminal's own and marks with bold and underline.
+        ThemeName::HighContrast => Theme {
+            overdue: style(None, true, false, true),
+            high: style(None, true, false, false),
+            done: Style::default(),
+            matched: style(None, false, false, true),
+            checkboxes: ["[ ]", "[✓]", "[✗]"],
+        },
+        ThemeName::Mono => Theme {
+            overdue: style(None, true, false, true),
+            high: style(None, true, false, false),
+            done: style(None, false, true, false),
+            matched: style(None, false, false, true),
+            checkboxes: ["☐", "☑", "☒"],
+        },
+    }
+}
+
+/// The theme the config asks for.
+pub fn get() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        let config = config::get();
+        let mut theme = bundled(config.theme.unwrap_or_default());
+        if let Some(styles) = &config.styles {
+            theme.overdue = styles.overdue.unwrap_or(theme.overdue);
+            theme.high = styles.high.unwrap_or(theme.high);
+            theme.done = styles.done.unwrap_or(theme.done);
+            theme.matched = styles.matched.unwrap_or(theme.matched);
+        }
+        theme
+    })
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::default();
+        for word in text.split_whitespace() {
+            let color = match word.to_lowercase().as_str() {
+                "plain" => continue,
+                "bold" => {
+                    style.bold = true;
+                    continue;
+                }
+                "dim" => {
+                    style.dim = true;
+                    continue;
+                }
+                "underline" => {
+                    style.underline = true;
+                    continue;
+                }
+                name => match COLORS
+                    .iter()
+                    .find(|(color, ..)| *color == name.replace("gray", "grey"))
+                {
+                    Some((_, color, _)) => *color,
+                    None => {
+                        return Err(format!(
+                            "{:?} isn't a color or bold, dim or underline",
+                            word
+                        ))
+                    }
+                },
+            };
+            style.color = Some(color);
+        }
+        Ok(style)
+    }
+}
+
+impl TryFrom<String> for Style {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+/// WCAG's contrast ratio for normal text at level AA.
+const AA: f64 = 4.5;
+
+/// The backgrounds a theme is checked against, as terminals' dark and light
+/// profiles usually have them.
+const BACKGROUNDS: [(&str, Rgb); 2] = [("dark", (0, 0, 0)), ("light", (255, 255, 255))];
+
+type Rgb = (u8, u8, u8);
+
+/// The colors a style can name, as xterm shows them.
+const COLORS: [(&str, Color, Rgb); 16] = [
+    ("black", Color::Black, (0, 0, 0)),
+    ("red", Color::Red, (255, 0, 0)),
+    ("green", Color::Green, (0, 255, 0)),
+    ("yellow", Color::Yellow, (255, 255, 0)),
+    ("blue", Color::Blue, (92, 92, 255)),
+    ("magenta", Color::Magenta, (255, 0, 255)),
+    ("cyan", Color::Cyan, (0, 255, 255)),
+    ("white", Color::White, (255, 255, 255)),
+    ("grey", Color::Grey, (229, 229, 229)),
+    ("dark-red", Color::DarkRed, (205, 0, 0)),
+    ("dark-green", Color::DarkGreen, (0, 205, 0)),
+    ("dark-yellow", Color::DarkYellow, (205, 205, 0)),
+    ("dark-blue", Color::DarkBlue, (0, 0, 238)),
+    ("dark-magenta", Color::DarkMagenta, (205, 0, 205)),
+    ("dark-cyan", Color::DarkCyan, (0, 205, 205)),
+    ("dark-grey", Color::DarkGrey, (127, 127, 127)),
+];
+
+/// How a style's text looks on `background`: its color, or the terminal's
+/// own text color, which is the opposite of the background, halfway to the
+/// background when dim.
+fn shown(style: Style, background: Rgb) -> Rgb {
+    let opposite = match luminance(background) < 0.5 {
+        true => (255, 255, 255),
+        false => (0, 0, 0),
+    };
+    let color = match style.color {
+        Some(Color::Rgb { r, g, b }) => (r, g, b),
+        Some(color) => COLORS
+            .iter()
+            .find(|(_, named, _)| *named == color)
+            .map_or(opposite, |(.., rgb)| *rgb),
+        None => opposite,
+    };
+    let mix = |text: u8, back: u8| ((u16::from(text) + u16::from(back)) / 2) as u8;
+    match style.dim {
+        true => (
+            mix(color.0, background.0),
+            mix(color.1, background.1),
+            mix(color.2, background.2),
+        ),
+        false => color,
+    }
+}
+
+/// WCAG's relative luminance.
+fn luminance((r, g, b): Rgb) -> f64 {
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        match c <= 0.04045 {
+            true => c / 12.92,
+            false => ((c + 0.055) / 1.055).powf(2.4),
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG's contrast ratio between two colors, from 1 to 21.
+fn contrast(a: Rgb, b: Rgb) -> f64 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// A style as it's written in the config.
+fn describe(style: Style) -> String {
+    let color = style.color.and_then(|color| {
+        COLORS
+            .iter()
+            .find(|(_, named, _)| *named == color)
+            .map(|(name, ..)| name.to_string())
+    });
+    let words: Vec<String> = color
+        .into_iter()
+        .chain(style.bold.then(|| "bold".to_string()))
+        .chain(style.dim.then(|| "dim".to_string()))
+        .chain(style.underline.then(|| "underline".to_string()))
+        .collect();
+    match words.is_empty() {
+        true => "plain".to_string(),
+        false => words.join(" "),
+    }
+}
+
+/// A style like `style` that's readable on `background`: the same without
+/// dim if that's enough, or else the closest color that is, keeping to
+/// colors rather than greys if it has one.
+fn suggest(style: Style, background: Rgb) -> Option<Style> {
+    let undimmed = Style {
+        dim: false,
+        ..style
+    };
+    if contrast(shown(undimmed, background), background) >= AA {
+        return Some(undimmed);
+    }
+    let now = shown(style, background);
+    let distance = |(r, g, b): Rgb| {
+        let d = |x: u8, y: u8| (i32::from(x) - i32::from(y)).pow(2);
+        d(r, now.0) + d(g, now.1) + d(b, now.2)
+    };
+    let grey = |(r, g, b): Rgb| r == g && g == b;
+    COLORS
+        .iter()
+        .filter(|(.., rgb)| contrast(*rgb, background) >= AA)
+        .min_by_key(|(.., rgb)| (grey(*rgb) != grey(now), distance(*rgb)))
+        .map(|(_, color, _)| Style {
+            color: Some(*color),
+            ..undimmed
+        })
+}
+
+/// `todo --check-theme`: how readable each of `theme`'s styles is on a dark
+/// and a light background, with a fix for each that's below WCAG's AA
+/// level. Returns how many are.
+pub fn print_check(screen: &mut Screen, theme: &Theme) -> usize {
+    let styles = [
+        ("overdue", theme.overdue),
+        ("high", theme.high),
+        ("done", theme.done),
+        ("match", theme.matched),
+    ];
+    writeln!(
+        screen,
+        "Contrast against dark and light backgrounds, {}:1 is needed\n",
+        AA
+    );
+    let mut failing = 0;
+    for (name, style) in styles {
+        let mut label = format!("{:<9}{:<22} ", name, describe(style));
+        for (background_name, background) in BACKGROUNDS {
+            let ratio = contrast(shown(style, background), background);
+            let verdict = match ratio >= AA {
+                true => "ok".to_string(),
+                false => {
+                    failing += 1;
+                    match suggest(style, background) {
+                        Some(fix) => format!("too low, try {:?}", describe(fix)),
+                        None => "too low".to_string(),
+                    }
+                }
+            };
+            writeln!(
+                screen,
+                "  {}{:<6}{:>5.1}:1  {}",
+                label, background_name, ratio, verdict
+            );
+            label = " ".repeat(label.chars().count());
+        }
+    }
+    match failing {
+        0 => writeln!(screen, "\nEverything is readable on both."),
+        _ => writeln!(
+            screen,
+            "\n{} below the threshold. Change them under [styles] in the config.",
+            failing
+        ),
+    }
+    writeln!(screen);
+    failing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_parse() {
+        assert_eq!(
+            "Magenta bold underline".parse(),
+            Ok(Style {
+                color: Some(Color::Magenta),
+                bold: true,
+                dim: false,
+                underline: true,
+            })
+        );
+        assert_eq!("plain".parse(), Ok(Style::default()));
+        assert_eq!(
+            "gray".parse::<Style>().map(|style| style.color),
+            Ok(Some(Color::Grey))
+        );
+        assert_eq!(
+            "dark-red".parse::<Style>().map(|style| style.color),
+            Ok(Some(Color::DarkRed))
+        );
+        assert!("blinking".parse::<Style>().is_err());
+    }
+
+    #[test]
+    fn contrast_check() {
+        assert_eq!(contrast((0, 0, 0), (255, 255, 255)).round(), 21.0);
+        let mut screen = Screen::plain();
+        for name in [ThemeName::Default, ThemeName::HighContrast] {
+            print_check(&mut screen, &bundled(name));
+        }
+        insta::assert_snapshot!(screen.contents());
+        let high_contrast = bundled(ThemeName::HighContrast);
+        assert_eq!(print_check(&mut Screen::plain(), &high_contrast), 0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Removed items aren't gone straight away: they go to `<list>.trash.json`
+//! next to the list, stamped with when they were removed. `todo trash`
+//! lists them and `todo trash restore <id>` puts them back at the end of
+//! the list. The daily maintenance empties out what's been in the trash
+//! longer than `trash_days` in the config, 30 days unless set.
+//!
+//! Removing can be undone like any change to the list too; the trash keeps
+//! its copy then, as the archive does.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+
+use crate::history::{self, Operation};
+use crate::screen::Screen;
+use crate::{clock, config, parse_indices, store, subtasks, write_to_file, Item, Todos};
+
+/// How many days items stay in the trash without `trash_days`.
+const DAYS: u32 = 30;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TrashCommand {
+    /// List what's in the trash, with the ids to restore it by
+    List,
+    /// Put items back on the list, by their ids in the trash
+    Restore { ids: Vec<String> },
+    /// Remove everything in the trash for good
+    Empty,
+}
+
+/// A removed item, with the subtasks removed along with it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Trashed {
+    pub removed: DateTime<Local>,
+    pub item: Item,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<Item>,
+}
+
+fn path() -> PathBuf {
+    store::sibling("trash.json")
+}
+
+/// Everything in the trash, oldest first.
+pub fn read() -> Result<Vec<Trashed>, String> {
+    let path = path();
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("{} couldn't be read: {}", path.display(), e)),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn write(trash: &[Trashed]) -> Result<(), String> {
+    let path = path();
+    std::fs::write(&path, serde_json::to_string_pretty(trash).unwrap())
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// What removing `indices` from `todos` puts in the trash, and the change
+/// to the list that does it.
+fn trashed(todos: &Todos, indices: &[usize], now: DateTime<Local>) -> (Vec<Trashed>, Operation) {
+    let mut after = todos.clone();
+    let mut trashed = Vec::new();
+    // Removing from the back keeps the other indices valid.
+    for &index in indices.iter().rev() {
+        let subtasks = subtasks::children(&after, index).count();
+        let mut removed = after.drain(index..=index + subtasks);
+        trashed.push(Trashed {
+            removed: now,
+            item: removed.next().unwrap(),
+            subtasks: removed.collect(),
+        });
+    }
+    trashed.reverse();
+    (trashed, Operation::remove_many(todos, indices))
+}
+
+/// Removes the items at `indices`, with their subtasks, to the trash.
+pub fn remove(todos: &mut Todos, indices: &[usize]) -> Result<(), String> {
+    let (removed, operation) = trashed(todos, indices, clock::now());
+    history::perform(todos, operation)?;
+    let mut trash = read()?;
+    trash.extend(removed);
+    write(&trash)
+}
+
+/// Puts the items with `ids` in the trash, like `1 3-4`, back at the end
+/// of the list. A subtask whose parent is gone comes back as an item of its
+/// own. Returns what to tell the user.
+pub fn restore(todos: &mut Todos, ids: &[String]) -> Result<String, String> {
+    let mut trash = read()?;
+    let ids = parse_indices(&ids.join(" "), trash.len())?;
+    let mut after = todos.clone();
+    let mut operations = Vec::new();
+    for &id in &ids {
+        let Trashed {
+            mut item, subtasks, ..
+        } = trash[id].clone();
+        item.subtask = false;
+        for item in std::iter::once(item).chain(subtasks) {
+            let insert = Operation::add(&after, item);
+            insert.apply(&mut after)?;
+            operations.push(insert);
+        }
+    }
+    let count = operations.len();
+    history::perform(todos, Operation::Group { operations })?;
+
+    // The list is saved before the trash lets go of the items.
+    write_to_file(todos)?;
+    for &id in ids.iter().rev() {
+        trash.remove(id);
+    }
+    write(&trash)?;
+    Ok(match count {
+        1 => "Restored 1 item".to_string(),
+        count => format!("Restored {} items", count),
+    })
+}
+
+/// Removes everything in the trash for good. Returns what to tell the user.
+pub fn empty() -> Result<String, String> {
+    let trash = read()?;
+    write(&[])?;
+    Ok(match trash.len() {
+        0 => "The trash was empty already".to_string(),
+        _ => "Emptied the trash".to_string(),
+    })
+}
+
+/// Removes what's been in the trash too long by `today` for good. Returns
+/// how many items that was.
+pub fn purge(today: NaiveDate) -> Result<usize, String> {
+    let days = config::get().trash_days.unwrap_or(DAYS);
+    let (kept, expired): (Vec<Trashed>, Vec<Trashed>) = read()?
+        .into_iter()
+        .partition(|trashed| today - trashed.removed.date_naive() < Duration::days(days.into()));
+    if !expired.is_empty() {
+        write(&kept)?;
+    }
+    Ok(expired.len())
+}
+
+/// The trash, oldest first, with the ids to restore items by.
+pub fn print(screen: &mut Screen, trash: &[Trashed]) {
+    if trash.is_empty() {
+        writeln!(screen, "[The trash is empty]\n");
+        return;
+    }
+    let width = (trash.len() - 1).to_string().len();
+    for (id, trashed) in trash.iter().enumerate() {
+        writeln!(
+            screen,
+            "{:>width$} {} (removed {})",
+            id,
+            trashed.item,
+            trashed.removed.format("%Y-%m-%d %H:%M"),
+            width = width
+        );
+        for item in &trashed.subtasks {
+            writeln!(screen, "{:width$}   {}", "", item, width = width);
+        }
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn subtasks_go_with_their_parent() {
+        let now = Local::now();
+        let mut todos = sample();
+        todos[1].subtask = true;
+        todos[2].subtask = true;
+
+        let (trashed, operation) = trashed(&todos, &[0, 2, 4], now);
+        let names: Vec<(&str, usize)> = trashed
+            .iter()
+            .map(|t| (&*t.item.name, t.subtasks.len()))
+            .collect();
+        // The subtask at 2 is removed on its own before its parent.
+        assert_eq!(
+            names,
+            [
+                ("send report to @bob +work", 1),
+                ("do taxes", 0),
+                ("book lunch", 0)
+            ]
+        );
+
+        operation.apply(&mut todos).unwrap();
+        assert_eq!(todos.len(), 1);
+    }
+}
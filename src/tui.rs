@@ -0,0 +1,533 @@
+//! `todo tui`: the list full screen, navigated with the keyboard instead
+//! of typed commands. Changes are saved and recorded for undo as they're
+//! made, just like the commands they stand for.
+//!
+//! The keys can be changed in the config, see `keys`. The mouse works too:
+//! clicking an item selects it, clicking its checkbox checks it, and the
+//! wheel moves up and down.
+//!
+//! On a wide enough terminal the selected item's details are shown next to
+//! the list: what `todo show` says, its subtasks and what happened to it.
+//! Tab hides or shows them.
+//!
+//! Checking or deleting an item says so for a few seconds, with the key
+//! that undoes it. Changes are saved in the background, see `autosave`,
+//! with the corner of the list saying whether they have been.
+//!
+//! Terminals that can't do this (`TERM=dumb`, or input and output that
+//! aren't a terminal) keep using the plain prompt.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::backend::FromCrossterm;
+use ratatui::layout::{Constraint, Layout, Margin, Position, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::app::View;
+use crate::autosave;
+use crate::history::{self, Operation};
+use crate::keys::{Action, Bindings};
+use crate::screen::{self, Screen};
+use crate::{
+    celebrate, clock, config, edit, priority, quickadd, render, search, show, store, subtasks,
+    theme, trash, Todos,
+};
+
+/// How long a change is shown with the key to undo it.
+const TOAST: Duration = Duration::from_secs(4);
+
+/// How often to look whether a save has finished while one is going on.
+const SAVE_CHECK: Duration = Duration::from_millis(100);
+
+/// The narrowest terminal the details are shown on to begin with.
+const DETAILS_WIDTH: u16 = 80;
+
+enum Mode {
+    Browse,
+    Add,
+    /// Editing the item at this index, written out in `input`
+    Edit(usize),
+    Filter,
+}
+
+struct Tui<'a> {
+    todos: &'a mut Todos,
+    view: View,
+    mode: Mode,
+    /// What's being typed for an item to add or edit
+    input: String,
+    /// Only items whose name contains this are shown
+    filter: String,
+    state: ListState,
+    message: Option<String>,
+    /// When `message` goes away by itself, if it does
+    message_until: Option<Instant>,
+    keys: Bindings,
+    /// Where the list was last drawn, to tell what's clicked
+    list_area: Rect,
+    /// Whether the selected item's details are shown beside the list
+    details: bool,
+    /// Whether anything has been changed, and whether it's still being saved
+    changed: bool,
+    saving: bool,
+}
+
+pub fn run(todos: &mut Todos, view: View) -> Result<(), String> {
+    if !Screen::new().is_interactive() {
+        return Err("tui needs a terminal, run `todo` for the plain prompt".to_string());
+    }
+    let keys = Bindings::load(&config::get().keys)?;
+
+    let mut tui = Tui {
+        todos,
+        view,
+        mode: Mode::Browse,
+        input: String::new(),
+        filter: String::new(),
+        state: ListState::default().with_selected(Some(0)),
+        message: None,
+        message_until: None,
+        keys,
+        list_area: Rect::default(),
+        details: false,
+        changed: false,
+        saving: false,
+    };
+    let mut terminal = ratatui::init();
+    tui.details = terminal
+        .size()
+        .is_ok_and(|size| size.width >= DETAILS_WIDTH);
+    // Without the mouse, the keys still do everything.
+    let _ = crossterm::execute!(std::io::stdout(), EnableMouseCapture);
+    let result = tui.run(&mut terminal);
+    let _ = crossterm::execute!(std::io::stdout(), DisableMouseCapture);
+    ratatui::restore();
+    result
+}
+
+/// A style of the theme, as ratatui draws it.
+fn style(style: screen::Style) -> Style {
+    let mut drawn = Style::new();
+    if let Some(color) = style.color {
+        drawn = drawn.fg(Color::from_crossterm(color));
+    }
+    if style.bold {
+        drawn = drawn.bold();
+    }
+    if style.dim {
+        drawn = drawn.dim();
+    }
+    if style.underline {
+        drawn = drawn.underlined();
+    }
+    drawn
+}
+
+impl Tui<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        loop {
+            match autosave::status() {
+                autosave::Status::Failed(e) => {
+                    self.message = Some(format!("That change wasn't saved: {}", e));
+                    self.message_until = None;
+                }
+                status => self.saving = status == autosave::Status::Saving,
+            }
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(|e| e.to_string())?;
+
+            // Input is waited for, but not past a message going away or a
+            // save finishing.
+            let wake = [
+                self.message_until
+                    .map(|until| until.saturating_duration_since(Instant::now())),
+                self.saving.then_some(SAVE_CHECK),
+            ];
+            if let Some(wake) = wake.into_iter().flatten().min() {
+                if !event::poll(wake).map_err(|e| e.to_string())? {
+                    if self
+                        .message_until
+                        .is_some_and(|until| until <= Instant::now())
+                    {
+                        self.message = None;
+                        self.message_until = None;
+                    }
+                    continue;
+                }
+            }
+            let key = match event::read().map_err(|e| e.to_string())? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                Event::Mouse(mouse) => {
+                    self.mouse(mouse)?;
+                    continue;
+                }
+                _ => continue,
+            };
+            if !self.handle(key)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The indices of the items on screen, in order.
+    fn visible(&self) -> Vec<usize> {
+        let (query, fuzzy) = self.query();
+        let found: HashSet<usize> = search::find(self.todos, query, fuzzy).into_iter().collect();
+        priority::display_order(self.todos, &self.view.sort)
+            .into_iter()
+            .filter(|&i| self.view.shows(&self.todos[i]) && found.contains(&i))
+            .collect()
+    }
+
+    /// What's being filtered by, and whether fuzzily, as `~` in front asks.
+    fn query(&self) -> (&str, bool) {
+        match self.filter.strip_prefix('~') {
+            Some(query) => (query, true),
+            None => (&self.filter, false),
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        let visible = self.visible();
+        self.state
+            .selected()
+            .and_then(|row| visible.get(row).copied())
+    }
+
+    /// Handles a key press, returning whether to carry on.
+    fn handle(&mut self, key: KeyEvent) -> Result<bool, String> {
+        self.message = None;
+        self.message_until = None;
+
+        match self.mode {
+            Mode::Browse => match self.keys.press(key.into()) {
+                Some(Action::Quit) => return Ok(false),
+                Some(Action::Down) => self.state.select_next(),
+                Some(Action::Up) => self.state.select_previous(),
+                Some(Action::Top) => self.state.select_first(),
+                Some(Action::Bottom) => self.state.select_last(),
+                Some(Action::Check) => {
+                    if let Some(index) = self.selected() {
+                        self.check(index)?;
+                    }
+                }
+                Some(Action::Delete) => {
+                    if let Some(index) = self.selected() {
+                        let name = self.name(index);
+                        self.change(|todos| {
+                            trash::remove(todos, &[index]).map(|()| String::new())
+                        })?;
+                        self.toast(format!("Deleted {}", name));
+                    }
+                }
+                Some(Action::Undo) => self.change(history::undo)?,
+                Some(Action::Add) => self.mode = Mode::Add,
+                Some(Action::Edit) => {
+                    if let Some(index) = self.selected() {
+                        self.input = quickadd::line(&self.todos[index]);
+                        self.mode = Mode::Edit(index);
+                    }
+                }
+                Some(Action::Filter) => self.mode = Mode::Filter,
+                Some(Action::Redact) => self.view.redact = !self.view.redact,
+                Some(Action::Details) => self.details = !self.details,
+                None => {}
+            },
+            Mode::Add | Mode::Edit(_) => match key.code {
+                KeyCode::Enter if !self.input.trim().is_empty() => {
+                    let input = std::mem::take(&mut self.input);
+                    let now = clock::now();
+                    let operation = match self.mode {
+                        Mode::Edit(index) => {
+                            let item = edit::retyped(&self.todos[index], &input, now);
+                            Operation::replace(self.todos, index, item)
+                        }
+                        _ => Operation::add(self.todos, quickadd::parse(&input, now)),
+                    };
+                    self.perform(operation)?;
+                    self.mode = Mode::Browse;
+                }
+                KeyCode::Esc => {
+                    self.input.clear();
+                    self.mode = Mode::Browse;
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char(c) => self.input.push(c),
+                _ => {}
+            },
+            Mode::Filter => match key.code {
+                KeyCode::Enter => self.mode = Mode::Browse,
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.mode = Mode::Browse;
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.state.select_first();
+                }
+                _ => {}
+            },
+        }
+
+        Ok(true)
+    }
+
+    fn mouse(&mut self, mouse: MouseEvent) -> Result<(), String> {
+        if !matches!(self.mode, Mode::Browse) {
+            return Ok(());
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.state.select_next(),
+            MouseEventKind::ScrollUp => self.state.select_previous(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some((row, index)) = self.clicked(mouse.column, mouse.row) else {
+                    return Ok(());
+                };
+                self.message = None;
+                self.state.select(Some(row));
+                if self.on_checkbox(index, mouse.column) {
+                    self.check(index)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The row on screen and the index of the item at `column`, `row`.
+    fn clicked(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let inside = self.list_area.inner(Margin::new(1, 1));
+        if !inside.contains(Position::new(column, row)) {
+            return None;
+        }
+        let row = self.state.offset() + usize::from(row - inside.y);
+        self.visible().get(row).map(|&index| (row, index))
+    }
+
+    /// Whether `column` is on the checkbox of the item at `index`, drawn
+    /// after its number and indent.
+    fn on_checkbox(&self, index: usize, column: u16) -> bool {
+        let item = &self.todos[index];
+        let number = format!("{:>3} ", index).len() as u16;
+        let start = self.list_area.x + 1 + number + if item.subtask { 2 } else { 0 };
+        let width = Span::raw(render::checkbox(item)).width() as u16;
+        (start..start + width).contains(&column)
+    }
+
+    fn check(&mut self, index: usize) -> Result<(), String> {
+        let name = self.name(index);
+        let done = match self.todos[index].completed {
+            true => "Reopened",
+            false => "Checked off",
+        };
+        self.perform(Operation::toggle(self.todos, index))?;
+        self.toast(format!("{} {}", done, name));
+        Ok(())
+    }
+
+    /// The name of the item at `index`, quoted for a message.
+    fn name(&self, index: usize) -> String {
+        let name = &self.todos[index].name;
+        match self.view.redact {
+            true => format!("“{}”", render::redact(name)),
+            false => format!("“{}”", name),
+        }
+    }
+
+    /// Shows what was just done for a few seconds, with the key to undo it,
+    /// unless the change had something else to say.
+    fn toast(&mut self, done: String) {
+        if self.message.is_none() {
+            self.message = Some(format!(
+                "{} · {} to undo",
+                done,
+                self.keys.key(Action::Undo)
+            ));
+            self.message_until = Some(Instant::now() + TOAST);
+        }
+    }
+
+    fn perform(&mut self, operation: Operation) -> Result<(), String> {
+        self.change(|todos| history::perform(todos, operation).map(|()| String::new()))
+    }
+
+    /// Makes a change and saves the list. A change that can't be made, like
+    /// undoing with nothing to undo, is shown rather than ending the session.
+    fn change(
+        &mut self,
+        change: impl FnOnce(&mut Todos) -> Result<String, String>,
+    ) -> Result<(), String> {
+        let today = clock::now().date_naive();
+        let progress = celebrate::Progress::of(self.todos, today);
+        match change(self.todos) {
+            Ok(message) => {
+                autosave::save(self.todos);
+                self.changed = true;
+                self.message = celebrate::celebrate(progress, self.todos, today)
+                    .or(Some(message))
+                    .filter(|message| !message.is_empty());
+            }
+            Err(e) => self.message = Some(e),
+        }
+        Ok(())
+    }
+
+    /// The details of the item at `index`, for the pane beside the list.
+    fn details(&self, index: usize) -> Vec<Line<'static>> {
+        let now = clock::now();
+        let item = &self.todos[index];
+        let hide = |text: &str| match self.view.redact {
+            true => render::redact(text),
+            false => text.to_string(),
+        };
+        let mut lines = vec![Line::from(hide(&item.name)).bold(), Line::default()];
+        for (label, value) in show::fields(self.todos, index, now) {
+            let value = match label {
+                "Under" | "Tags" | "People" | "Waiting on" => hide(&value),
+                _ => value,
+            };
+            lines.push(Line::from(format!("{:<11}{}", label, value)));
+        }
+
+        let children = subtasks::children(self.todos, index);
+        if !children.is_empty() {
+            lines.extend([Line::default(), Line::from("Subtasks").bold()]);
+            for child in children {
+                let child = &self.todos[child];
+                let text = format!("{} {}", render::checkbox(child), hide(&child.name));
+                lines.push(Line::from(text));
+            }
+        }
+
+        let mut history: Vec<(chrono::DateTime<chrono::Local>, &str)> = item
+            .created
+            .map(|created| (created, "added"))
+            .into_iter()
+            .chain(item.postponed.iter().map(|&at| (at, "put off")))
+            .chain(item.completed_at.map(|at| (at, "done")))
+            .collect();
+        history.sort();
+        if !history.is_empty() {
+            lines.extend([Line::default(), Line::from("History").bold()]);
+            for (at, what) in history {
+                lines.push(Line::from(format!(
+                    "{} {}",
+                    at.format("%Y-%m-%d %H:%M"),
+                    what
+                )));
+            }
+        }
+
+        if let Some(notes) = &item.notes {
+            lines.extend([Line::default(), Line::from("Notes").bold()]);
+            lines.extend(notes.lines().map(|line| Line::from(hide(line))));
+        }
+        lines
+    }
+
+    /// `name` with the letters the filter matches in it highlighted.
+    fn highlight(&self, name: &str) -> Vec<Span<'static>> {
+        let (query, fuzzy) = self.query();
+        if query.is_empty() {
+            return vec![Span::raw(name.to_string())];
+        }
+        let matched = search::matched(name, query, fuzzy);
+        let mut spans: Vec<Span> = Vec::new();
+        for (position, c) in name.chars().enumerate() {
+            let style = match matched.contains(&position) {
+                true => style(theme::get().matched),
+                false => Style::new(),
+            };
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.content.to_mut().push(c),
+                _ => spans.push(Span::styled(c.to_string(), style)),
+            }
+        }
+        spans
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main_area, status_area] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let (list_area, details_area) = match self.details {
+            true => {
+                let [list, details] =
+                    Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                        .areas(main_area);
+                (list, Some(details))
+            }
+            false => (main_area, None),
+        };
+
+        let now = clock::now();
+        let rows: Vec<ListItem> = self
+            .visible()
+            .into_iter()
+            .map(|i| {
+                let item = &self.todos[i];
+                let indent = if item.subtask { "  " } else { "" };
+                let line = match self.view.redact {
+                    true => Line::from(format!("{:>3} {}{}", i, indent, render::redacted(item))),
+                    false => {
+                        let start = format!("{:>3} {}{} ", i, indent, render::checkbox(item));
+                        let mut spans = vec![Span::raw(start)];
+                        spans.extend(self.highlight(&item.name));
+                        spans.push(Span::raw(render::details(item, false)));
+                        Line::from(spans)
+                    }
+                };
+                ListItem::new(line.style(style(render::style(item, now))))
+            })
+            .collect();
+
+        let mut title = format!(" {} ", store::list_name());
+        if !self.filter.is_empty() {
+            title = format!("{}· /{} ", title, self.filter);
+        }
+        let saved = match (self.saving, self.changed) {
+            (true, _) => " saving… ",
+            (false, true) => " saved ",
+            (false, false) => "",
+        };
+        let list = List::new(rows)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_top(Line::from(saved).right_aligned().dim()),
+            )
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, list_area, &mut self.state);
+        self.list_area = list_area;
+
+        if let Some(area) = details_area {
+            let lines = self.selected().map(|index| self.details(index));
+            let details = Paragraph::new(lines.unwrap_or_default())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(" Details "));
+            frame.render_widget(details, area);
+        }
+
+        let status = match (&self.mode, &self.message) {
+            (Mode::Add, _) => format!("Add: {}▏", self.input),
+            (Mode::Edit(index), _) => format!("Edit {}: {}▏", index, self.input),
+            (Mode::Filter, _) => format!("Filter (~ for fuzzy): {}▏", self.filter),
+            (Mode::Browse, Some(message)) => message.clone(),
+            (Mode::Browse, None) => self.keys.help(),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+    }
+}
@@ -0,0 +1,166 @@
+//! `todo stats --usage`: which commands you use and which you've never
+//! tried, to find what todo-rs can do. The counts are kept in
+//! `usage.stats.json` in the data directory and never leave it;
+//! `usage_stats = false` in the config stops keeping them.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::screen::Screen;
+use crate::{config, store};
+
+/// What the interactive prompt is counted as.
+pub const PROMPT: &str = "(prompt)";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Usage {
+    pub count: u64,
+    pub last: DateTime<Local>,
+}
+
+/// The dot keeps it from being taken for a list kept next to it.
+fn path() -> Option<PathBuf> {
+    Some(
+        dirs::data_dir()?
+            .join(store::APP_DIR)
+            .join("usage.stats.json"),
+    )
+}
+
+fn read() -> BTreeMap<String, Usage> {
+    path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Counts a use of `command`. Keeping count is never worth an error, so
+/// one that can't be kept is let go.
+pub fn record(command: &str, now: DateTime<Local>) {
+    if config::get().usage_stats == Some(false) {
+        return;
+    }
+    let Some(path) = path() else {
+        return;
+    };
+    let mut usage = read();
+    let entry = usage.entry(command.to_string()).or_insert(Usage {
+        count: 0,
+        last: now,
+    });
+    entry.count += 1;
+    entry.last = now;
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(path, serde_json::to_string_pretty(&usage).unwrap());
+}
+
+/// The commands used, most first, then the `commands` with what they do
+/// that haven't been.
+pub fn print(screen: &mut Screen, commands: &[(String, String)]) {
+    print_usage(screen, &read(), commands);
+}
+
+fn print_usage(
+    screen: &mut Screen,
+    usage: &BTreeMap<String, Usage>,
+    commands: &[(String, String)],
+) {
+    if config::get().usage_stats == Some(false) {
+        writeln!(
+            screen,
+            "[Usage isn't kept, as usage_stats = false in the config]\n"
+        );
+        return;
+    }
+    if usage.is_empty() {
+        writeln!(screen, "[Nothing used yet]\n");
+    } else {
+        let mut used: Vec<(&String, &Usage)> = usage.iter().collect();
+        used.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.count));
+        let width = used.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        writeln!(screen, "Used\n");
+        for (name, usage) in used {
+            writeln!(
+                screen,
+                "  {:<width$} {:>5}×  last {}",
+                name,
+                usage.count,
+                usage.last.format("%Y-%m-%d"),
+                width = width
+            );
+        }
+        writeln!(screen);
+    }
+
+    let untried: Vec<&(String, String)> = commands
+        .iter()
+        .filter(|(name, _)| !usage.contains_key(name))
+        .collect();
+    if !untried.is_empty() {
+        let width = untried
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        writeln!(screen, "Never tried\n");
+        for (name, about) in untried {
+            writeln!(screen, "  {:<width$}  {}", name, about, width = width);
+        }
+        writeln!(screen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn used_and_untried() {
+        let day = |d| Local.with_ymd_and_hms(2024, 5, d, 9, 0, 0).unwrap();
+        let usage = BTreeMap::from([
+            (
+                "add".to_string(),
+                Usage {
+                    count: 12,
+                    last: day(6),
+                },
+            ),
+            (
+                PROMPT.to_string(),
+                Usage {
+                    count: 3,
+                    last: day(2),
+                },
+            ),
+            (
+                "list".to_string(),
+                Usage {
+                    count: 30,
+                    last: day(5),
+                },
+            ),
+        ]);
+        let commands = [
+            ("add", "Add an item"),
+            ("list", "Print the list"),
+            (
+                "archive",
+                "Move completed items off the list into its archive",
+            ),
+            ("stats", "Show how much is open and done"),
+        ]
+        .map(|(name, about)| (name.to_string(), about.to_string()));
+
+        let mut screen = Screen::plain();
+        print_usage(&mut screen, &usage, &commands);
+        print_usage(&mut screen, &BTreeMap::new(), &commands[..2]);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,246 @@
+//! Checking the data file item by item. `read_from_file` gives up on the
+//! whole list when a single item doesn't parse, so this reports exactly
+//! what is wrong and can repair it.
+//!
+//! References between items are checked every time the list is loaded
+//! too, with `check_references`, and repaired there and then.
+
+use serde_json::Value;
+
+use crate::screen::Screen;
+use crate::{store, write_to_file, Item, Todos};
+
+/// A problem with the item at `item` in the file, and what `--fix` does
+/// about it.
+#[derive(Debug, PartialEq)]
+struct Problem {
+    item: usize,
+    what: String,
+    fix: String,
+}
+
+impl Problem {
+    fn new(item: usize, what: impl Into<String>, fix: impl Into<String>) -> Self {
+        Problem {
+            item,
+            what: what.into(),
+            fix: fix.into(),
+        }
+    }
+}
+
+/// The items in `content` as `--fix` leaves them, and the problems found.
+/// An error if it isn't a list of items at all.
+fn check(content: &str) -> Result<(Todos, Vec<Problem>), String> {
+    let values = match serde_json::from_str::<Value>(content) {
+        Ok(Value::Array(values)) => values,
+        Ok(_) => return Err("should contain a list of items".to_string()),
+        Err(e) => return Err(format!("is not valid JSON: {}", e)),
+    };
+
+    // Items keep their position in the file so reports point at the right
+    // place even after earlier items are dropped.
+    let mut problems = Vec::new();
+    let mut items: Vec<(usize, Item)> = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        match serde_json::from_value::<Item>(value.clone()) {
+            Ok(item) => items.push((i, item)),
+            Err(e) => match repair(value) {
+                Some(item) => {
+                    let fix = format!("drop the invalid fields of {}", item.name);
+                    problems.push(Problem::new(i, e.to_string(), fix));
+                    items.push((i, item));
+                }
+                None => problems.push(Problem::new(i, e.to_string(), "remove the item")),
+            },
+        }
+    }
+
+    for (i, item) in items.iter_mut() {
+        if item.waiting_since.is_some() && item.waiting_on.is_none() {
+            problems.push(Problem::new(
+                *i,
+                "waiting_since is set but waiting_on is not",
+                "clear waiting_since",
+            ));
+            item.waiting_since = None;
+        }
+    }
+
+    let mut todos: Todos = Vec::new();
+    let mut positions = Vec::new();
+    for (i, mut item) in items {
+        let duplicate =
+            item.source.is_some() && todos.iter().any(|earlier| earlier.source == item.source);
+        if duplicate {
+            problems.push(Problem::new(
+                i,
+                "tracks the same TODO comment as an earlier item",
+                "remove the item",
+            ));
+            continue;
+        }
+        // Sync tells items apart by id, so a copy would be taken for the
+        // same item.
+        if item.id.is_some() && todos.iter().any(|earlier| earlier.id == item.id) {
+            problems.push(Problem::new(
+                i,
+                "has the same id as an earlier item",
+                "give it a new id",
+            ));
+            item.id = Some(uuid::Uuid::new_v4());
+        }
+        todos.push(item);
+        positions.push(i);
+    }
+
+    if !check_references(&mut todos).is_empty() {
+        problems.push(Problem::new(
+            positions[0],
+            "is a subtask of nothing, with no item above it",
+            "make it an item of its own",
+        ));
+    }
+    Ok((todos, problems))
+}
+
+/// Repairs references between items that lead nowhere, returning what was
+/// changed. A subtask belongs to the closest item above it that isn't
+/// one, so one with nothing above it has lost its parent.
+pub fn check_references(todos: &mut Todos) -> Vec<String> {
+    match todos.first_mut().filter(|item| item.subtask) {
+        Some(item) => {
+            item.subtask = false;
+            vec![format!(
+                "\"{}\" was a subtask of nothing and is now an item of its own",
+                item.name
+            )]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Reports every problem in the list's file and, if `fix`, repairs them.
+/// An error unless the file is valid afterwards.
+pub fn run(screen: &mut Screen, fix: bool) -> Result<(), String> {
+    let path = store::path();
+    let file = path.display();
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", file, e))?;
+    let (todos, problems) = check(&content).map_err(|e| format!("{} {}", file, e))?;
+
+    for problem in &problems {
+        writeln!(screen, "item {}: {}", problem.item, problem.what);
+        writeln!(screen, "  fix: {}", problem.fix);
+    }
+    if problems.is_empty() {
+        writeln!(screen, "{} is valid", file);
+        screen.flush();
+        return Ok(());
+    }
+    writeln!(screen);
+    screen.flush();
+    if !fix {
+        return Err(format!(
+            "{} problems found, run with --fix to repair them",
+            problems.len()
+        ));
+    }
+
+    write_to_file(&todos)?;
+    writeln!(screen, "{} problems fixed", problems.len());
+    screen.flush();
+    Ok(())
+}
+
+/// Drops the fields that can't be parsed, keeping the item as long as it
+/// still has a name.
+fn repair(value: Value) -> Option<Item> {
+    let mut object = match value {
+        Value::Object(object) => object,
+        _ => return None,
+    };
+    let name = object.get("name").filter(|name| name.is_string())?.clone();
+
+    object.retain(|field, value| field == "name" || is_valid_field(&name, field, value));
+    object.entry("completed").or_insert(Value::Bool(false));
+
+    serde_json::from_value(Value::Object(object)).ok()
+}
+
+/// Checks a single field by parsing it on an otherwise minimal item.
+fn is_valid_field(name: &Value, field: &str, value: &Value) -> bool {
+    let mut probe = serde_json::Map::new();
+    probe.insert("name".to_string(), name.clone());
+    probe.insert("completed".to_string(), Value::Bool(false));
+    probe.insert(field.to_string(), value.clone());
+
+    serde_json::from_value::<Item>(Value::Object(probe)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_a_list_is_an_error() {
+        assert!(check("[{\"name\": ").is_err());
+        assert!(check("{\"name\": \"a\"}").is_err());
+    }
+
+    #[test]
+    fn problems_are_found_and_repaired() {
+        let id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let content = format!(
+            r#"[
+                {{"name": "orphan", "completed": false, "subtask": true, "due": "soon"}},
+                {{"name": "lunch", "completed": false, "waiting_since": "2024-05-01"}},
+                42,
+                {{"name": "first", "completed": false, "id": "{id}"}},
+                {{"name": "copy", "completed": true, "id": "{id}"}}
+            ]"#
+        );
+
+        let (todos, problems) = check(&content).unwrap();
+        let found: Vec<(usize, &str)> = problems
+            .iter()
+            .map(|problem| (problem.item, problem.fix.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0, "drop the invalid fields of orphan"),
+                (2, "remove the item"),
+                (1, "clear waiting_since"),
+                (4, "give it a new id"),
+                (0, "make it an item of its own"),
+            ]
+        );
+
+        let names: Vec<&str> = todos.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["orphan", "lunch", "first", "copy"]);
+        assert!(!todos[0].subtask && todos[0].due.is_none());
+        assert_eq!(todos[1].waiting_since, None);
+        assert_eq!(todos[2].id.unwrap().to_string(), id);
+        assert!(todos[3].id.is_some() && todos[3].id != todos[2].id);
+        assert!(check(&serde_json::to_string(&todos).unwrap())
+            .unwrap()
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn subtasks_without_a_parent_are_repaired() {
+        let mut todos = crate::tests::sample();
+        assert!(check_references(&mut todos).is_empty());
+
+        todos[0].subtask = true;
+        todos[1].subtask = true;
+        assert_eq!(
+            check_references(&mut todos),
+            ["\"send report to @bob +work\" was a subtask of nothing and is now an item of its own"]
+        );
+        assert!(!todos[0].subtask && todos[1].subtask);
+        assert!(check_references(&mut todos).is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+//! Items blocked on someone else, with how long they've been waiting.
+
+use chrono::NaiveDate;
+
+use crate::history::{self, Operation};
+use crate::screen::Screen;
+use crate::{check_index, clock, Todos};
+
+/// Marks an item as blocked on `person`, or clears it when no person is
+/// given.
+pub fn wait(
+    screen: &mut Screen,
+    todos: &mut Todos,
+    index: usize,
+    person: Vec<String>,
+) -> Result<(), String> {
+    check_index(todos, index)?;
+
+    let mut item = todos[index].clone();
+    if person.is_empty() {
+        item.waiting_on = None;
+        item.waiting_since = None;
+    } else {
+        item.waiting_on = Some(person.join(" "));
+        item.waiting_since = Some(clock::now().date_naive());
+    }
+    history::perform(todos, Operation::replace(todos, index, item))?;
+
+    print_waiting(screen, todos, clock::now().date_naive());
+    Ok(())
+}
+
+pub fn print_waiting(screen: &mut Screen, todos: &Todos, today: NaiveDate) {
+    let rows: Vec<(usize, String, &str, i64)> = todos
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !item.completed)
+        .filter_map(|(i, item)| {
+            let person = item.waiting_on.as_deref()?;
+            let age = item
+                .waiting_since
+                .map(|since| (today - since).num_days())
+                .unwrap_or_default();
+            Some((i, item.to_string(), person, age))
+        })
+        .collect();
+
+    if rows.is_empty() {
+        writeln!(screen, "[Not waiting on anyone]\n");
+        return;
+    }
+
+    let item_width = rows
+        .iter()
+        .map(|row| row.1.chars().count())
+        .max()
+        .unwrap_or(0);
+    let person_width = rows
+        .iter()
+        .map(|row| row.2.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (i, item, person, age) in rows {
+        writeln!(
+            screen,
+            "{} {:item_width$}  {:person_width$}  {}d",
+            i,
+            item,
+            person,
+            age,
+            item_width = item_width,
+            person_width = person_width
+        );
+    }
+    writeln!(screen);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::sample;
+
+    #[test]
+    fn waiting() {
+        let mut screen = Screen::plain();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        print_waiting(&mut screen, &sample(), today);
+        insta::assert_snapshot!(screen.contents());
+    }
+}
@@ -0,0 +1,54 @@
+//! The interactive prompt saves in the background, so what's left to save
+//! when it exits has to be saved on the way out, and nothing else.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Runs the prompt with `input` typed into it, until the input ends.
+fn prompt(root: &Path, input: &str) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_todo"))
+        .env("HOME", root)
+        .env("XDG_DATA_HOME", root.join("data"))
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .env("TODO_FILE", root.join("todo.json"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    assert!(
+        child.wait().unwrap().success(),
+        "todo failed on {:?}",
+        input
+    );
+}
+
+#[test]
+fn changes_are_saved_on_exit_and_nothing_else() {
+    let root = std::env::temp_dir().join(format!("todo-autosave-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let list = root.join("todo.json");
+
+    // Added, then the input ends straight away, which exits.
+    prompt(&root, "a\nbuy milk\n\n");
+    let saved = std::fs::read_to_string(&list).unwrap();
+    assert!(saved.contains("\"buy milk\""));
+
+    // Only looking doesn't touch the file.
+    let modified = std::fs::metadata(&list).unwrap().modified().unwrap();
+    prompt(&root, "p\n");
+    assert_eq!(std::fs::read_to_string(&list).unwrap(), saved);
+    assert_eq!(
+        std::fs::metadata(&list).unwrap().modified().unwrap(),
+        modified
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,79 @@
+//! A bundle from `todo export-all` puts back everything it was made from
+//! on a machine that has nothing yet.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn todo(root: &Path, args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_todo"))
+        .args(args)
+        .env("HOME", root)
+        .env("XDG_DATA_HOME", root.join("data"))
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .env_remove("TODO_FILE")
+        .env_remove("TODO_CONFIG")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "todo {:?} failed", args);
+}
+
+/// Every file under `dir` by where it is in `dir`, with its content. Locks
+/// are left out, and so are the usage counts, which stay on the machine.
+fn files(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    fn walk(dir: &Path, root: &Path, found: &mut BTreeMap<PathBuf, Vec<u8>>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, root, found);
+            } else if !path.ends_with("usage.stats.json")
+                && path.extension().is_none_or(|extension| extension != "lock")
+            {
+                let inside = path.strip_prefix(root).unwrap().to_path_buf();
+                found.insert(inside, std::fs::read(&path).unwrap());
+            }
+        }
+    }
+    let mut found = BTreeMap::new();
+    walk(dir, dir, &mut found);
+    found
+}
+
+#[test]
+fn bundles_carry_everything_over() {
+    let temp = std::env::temp_dir().join(format!("todo-bundle-test-{}", std::process::id()));
+    let (here, there) = (temp.join("here"), temp.join("there"));
+    let _ = std::fs::remove_dir_all(&temp);
+    std::fs::create_dir_all(here.join("config").join("todo-rs")).unwrap();
+    std::fs::create_dir_all(&there).unwrap();
+
+    std::fs::write(
+        here.join("config").join("todo-rs").join("config.toml"),
+        "sort = \"priority\"\n",
+    )
+    .unwrap();
+    todo(&here, &["add", "buy milk"]);
+    todo(&here, &["add", "call the bank"]);
+    todo(&here, &["check", "0"]);
+    todo(&here, &["archive"]);
+    todo(&here, &["--list", "work", "add", "send the report"]);
+
+    let lists = Path::new("data").join("todo-rs");
+    let made = files(&here);
+    for kept in [
+        "todo.json",
+        "todo.done.json",
+        "todo.history.json",
+        "work.json",
+    ] {
+        assert!(made.contains_key(&lists.join(kept)), "no {}", kept);
+    }
+
+    let bundle = temp.join("backup.tar.gz");
+    let bundle_arg = bundle.to_str().unwrap();
+    todo(&here, &["export-all", bundle_arg]);
+    todo(&there, &["import-all", bundle_arg]);
+    assert_eq!(files(&there), files(&here));
+
+    std::fs::remove_dir_all(&temp).unwrap();
+}
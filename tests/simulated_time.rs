@@ -0,0 +1,53 @@
+//! `--now` runs against the real store, so everything it would write with
+//! the simulated date is left alone.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn todo(root: &Path, args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_todo"))
+        .args(args)
+        .env("HOME", root)
+        .env("XDG_DATA_HOME", root.join("data"))
+        .env("XDG_CONFIG_HOME", root.join("config"))
+        .env("TODO_FILE", root.join("list").join("todo.json"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "todo {:?} failed", args);
+}
+
+/// Every file under `dir`, with its content.
+fn files(dir: &Path) -> BTreeMap<PathBuf, Vec<u8>> {
+    let mut found = BTreeMap::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            found.extend(files(&path));
+        } else {
+            found.insert(path.clone(), std::fs::read(&path).unwrap());
+        }
+    }
+    found
+}
+
+#[test]
+fn simulated_runs_leave_the_store_alone() {
+    let root = std::env::temp_dir().join(format!("todo-simulated-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("list")).unwrap();
+
+    todo(&root, &["add", "Renew passport", "--expires", "2030-06-01"]);
+    todo(&root, &["add", "Old idea"]);
+    todo(&root, &["remove", "1"]);
+    let before = files(&root);
+    for kept in ["todo.maintained", "todo.trash.json"] {
+        assert!(before.keys().any(|path| path.ends_with(kept)));
+    }
+
+    todo(&root, &["--now", "2031-01-01", "list"]);
+    todo(&root, &["--now", "2031-01-01", "stats"]);
+    assert_eq!(files(&root), before);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}